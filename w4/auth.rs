@@ -0,0 +1,192 @@
+//! bearer token(JWT) 인증.
+//!
+//! 서버는 `AuthInterceptor`를 `CabServiceServer::with_interceptor`, `DispatchServiceServer::with_interceptor`에 건다.
+//! interceptor는 token을 확인해서 `Claims`를 request extension에 넣고,
+//! 누가 무엇을 할 수 있는지는 각 rpc가 `Claims`를 보고 정한다.
+//! interceptor가 없거나 `AuthInterceptor::allow_all`이면 `Claims`가 없으므로 아무것도 검사하지 않는다.
+//!
+//! 클라이언트는 `BearerToken`을 `CabServiceClient::with_interceptor`에 건다.
+
+use std::fmt;
+use std::sync::Arc;
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use tonic::metadata::errors::InvalidMetadataValue;
+use tonic::metadata::AsciiMetadataValue;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+pub const AUTHORIZATION: &str = "authorization";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Driver,
+    Rider,
+    /// 운영팀. 지난 위치 기록을 볼 수 있다.
+    Operator,
+}
+
+/// token에 들어 있는 내용.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// 기사나 손님 id. 손님이면 `RideRequest.rider_id`와 같아야 한다.
+    pub sub: String,
+    pub role: Role,
+    /// 기사가 위치를 올릴 수 있는 cab. 기사 token에만 있다.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cab: Option<String>,
+    /// unix epoch 초
+    pub exp: u64,
+}
+
+impl Claims {
+    /// 기사이고 `cab_name`이 자기 cab이어야 한다.
+    pub fn check_driver_of(&self, cab_name: &str) -> Result<(), Forbidden> {
+        match (self.role, self.cab.as_deref()) {
+            (Role::Driver, Some(cab)) if cab == cab_name => Ok(()),
+            (Role::Driver, _) => Err(Forbidden(format!("driver may not act for cab {:?}", cab_name))),
+            (Role::Rider | Role::Operator, _) => Err(Forbidden("only drivers may do this".to_string())),
+        }
+    }
+
+    /// 손님이고 `rider_id`가 자기여야 한다.
+    pub fn check_rider(&self, rider_id: &str) -> Result<(), Forbidden> {
+        match self.role {
+            Role::Rider if self.sub == rider_id => Ok(()),
+            Role::Rider => Err(Forbidden(format!("rider may not act for {:?}", rider_id))),
+            Role::Driver | Role::Operator => Err(Forbidden("only riders may do this".to_string())),
+        }
+    }
+
+    /// 운영팀이거나 `cab_name`의 기사여야 한다.
+    pub fn check_operator_or_driver_of(&self, cab_name: &str) -> Result<(), Forbidden> {
+        match self.role {
+            Role::Operator => Ok(()),
+            _ => self.check_driver_of(cab_name),
+        }
+    }
+}
+
+/// 인증은 됐지만 할 수 없는 일. `PermissionDenied`가 된다.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forbidden(pub String);
+
+impl From<Forbidden> for Status {
+    fn from(e: Forbidden) -> Self {
+        Status::permission_denied(e.0)
+    }
+}
+
+/// interceptor가 넣어 둔 `Claims`. 인증 없이 띄운 서버면 `None`.
+pub fn claims<T>(req: &Request<T>) -> Option<&Claims> {
+    req.extensions().get::<Claims>()
+}
+
+/// 인증 없이 띄운 서버면 통과.
+pub fn authorize_driver<T>(req: &Request<T>, cab_name: &str) -> Result<(), Forbidden> {
+    claims(req).map_or(Ok(()), |claims| claims.check_driver_of(cab_name))
+}
+
+/// 인증 없이 띄운 서버면 통과.
+pub fn authorize_operator_or_driver<T>(req: &Request<T>, cab_name: &str) -> Result<(), Forbidden> {
+    claims(req).map_or(Ok(()), |claims| claims.check_operator_or_driver_of(cab_name))
+}
+
+/// 인증 없이 띄운 서버면 통과.
+pub fn authorize_rider<T>(req: &Request<T>, rider_id: &str) -> Result<(), Forbidden> {
+    claims(req).map_or(Ok(()), |claims| claims.check_rider(rider_id))
+}
+
+/// token을 확인할 key와 알고리즘.
+#[derive(Clone)]
+pub struct AuthConfig {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // key는 찍지 않는다
+        f.debug_struct("AuthConfig").field("algorithms", &self.validation.algorithms).finish()
+    }
+}
+
+impl AuthConfig {
+    /// 공유 secret으로 서명한 token.
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self { key: DecodingKey::from_secret(secret), validation: Validation::new(Algorithm::HS256) }
+    }
+
+    /// 인증 서버의 RSA 공개 key(PEM)로 확인하는 token.
+    pub fn rs256_pem(public_key: &[u8]) -> Result<Self, jsonwebtoken::errors::Error> {
+        let key = DecodingKey::from_rsa_pem(public_key)?;
+        Ok(Self { key, validation: Validation::new(Algorithm::RS256) })
+    }
+
+    pub fn decode(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+        jsonwebtoken::decode::<Claims>(token, &self.key, &self.validation).map(|data| data.claims)
+    }
+}
+
+/// `authorization: Bearer <token>`을 확인한다. 없거나 틀리면 `Unauthenticated`.
+#[derive(Debug, Clone)]
+pub struct AuthInterceptor {
+    config: Option<Arc<AuthConfig>>,
+}
+
+impl AuthInterceptor {
+    pub fn new(config: AuthConfig) -> Self {
+        Self { config: Some(Arc::new(config)) }
+    }
+
+    /// 아무것도 확인하지 않는다. key를 설정하지 않은 개발용 서버에서 쓴다.
+    pub fn allow_all() -> Self {
+        Self { config: None }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut req: Request<()>) -> Result<Request<()>, Status> {
+        let config = match &self.config {
+            Some(config) => config,
+            None => return Ok(req),
+        };
+        let header = req
+            .metadata()
+            .get(AUTHORIZATION)
+            .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+        let token = header
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("authorization must be a bearer token"))?;
+        let claims = config
+            .decode(token.trim())
+            .map_err(|e| Status::unauthenticated(format!("invalid token: {}", e)))?;
+
+        req.extensions_mut().insert(claims);
+        Ok(req)
+    }
+}
+
+/// 클라이언트 쪽. 모든 요청에 token을 붙인다.
+#[derive(Debug, Clone)]
+pub struct BearerToken {
+    header: AsciiMetadataValue,
+}
+
+impl BearerToken {
+    pub fn new(token: &str) -> Result<Self, InvalidMetadataValue> {
+        let header = format!("Bearer {}", token).parse()?;
+        Ok(Self { header })
+    }
+}
+
+impl Interceptor for BearerToken {
+    fn call(&mut self, mut req: Request<()>) -> Result<Request<()>, Status> {
+        req.metadata_mut().insert(AUTHORIZATION, self.header.clone());
+        Ok(req)
+    }
+}
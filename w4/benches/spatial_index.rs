@@ -0,0 +1,112 @@
+//! `SpatialIndex`와 전체 스캔 비교.
+//!
+//!     cargo bench --bench spatial_index
+//!
+//! 서울 근처(±1도)에 cab을 10k, 100k, 1M 대 뿌려놓고
+//! 가장 가까운 10대, 반경 2km 검색, 위치 갱신 시간을 잰다.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use learning_grpc::distance::distance_meters;
+use learning_grpc::hello::Location;
+use learning_grpc::spatial::SpatialIndex;
+
+const SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+const K: usize = 10;
+const RADIUS_METERS: f64 = 2_000.0;
+
+fn seoul() -> Location {
+    Location::new(37.5665, 126.978)
+}
+
+// 매번 같은 결과가 나오도록 간단한 LCG 사용
+struct Lcg(u64);
+
+impl Lcg {
+    fn next_unit(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 11) as f64 / (1u64 << 53) as f64) * 2.0 - 1.0
+    }
+
+    fn location(&mut self) -> Location {
+        let center = seoul();
+        Location::new(center.latitude + self.next_unit(), center.longitude + self.next_unit())
+    }
+}
+
+fn cabs(n: usize) -> Vec<(String, Location)> {
+    let mut rng = Lcg(n as u64);
+    (0..n).map(|i| (format!("cab-{}", i), rng.location())).collect()
+}
+
+fn linear_nearest<'a>(cabs: &'a [(String, Location)], origin: &Location, k: usize) -> Vec<(&'a str, f64)> {
+    let mut found: Vec<(&str, f64)> = cabs
+        .iter()
+        .map(|(name, location)| (name.as_str(), distance_meters(origin, location)))
+        .collect();
+    if found.len() > k {
+        found.select_nth_unstable_by(k, |a, b| a.1.total_cmp(&b.1));
+        found.truncate(k);
+    }
+    found.sort_by(|a, b| a.1.total_cmp(&b.1));
+    found
+}
+
+fn linear_within<'a>(cabs: &'a [(String, Location)], origin: &Location, radius: f64) -> Vec<(&'a str, f64)> {
+    let mut found: Vec<(&str, f64)> = cabs
+        .iter()
+        .map(|(name, location)| (name.as_str(), distance_meters(origin, location)))
+        .filter(|(_, distance)| *distance <= radius)
+        .collect();
+    found.sort_by(|a, b| a.1.total_cmp(&b.1));
+    found
+}
+
+fn bench_queries(c: &mut Criterion) {
+    let origin = seoul();
+
+    for &n in SIZES.iter() {
+        let cabs = cabs(n);
+        let index = SpatialIndex::bulk_load(cabs.iter().map(|(name, location)| (name.as_str(), location)));
+
+        let mut group = c.benchmark_group("nearest_10");
+        group.bench_with_input(BenchmarkId::new("rtree", n), &n, |b, _| {
+            b.iter(|| index.nearest(black_box(&origin), K))
+        });
+        group.bench_with_input(BenchmarkId::new("linear", n), &n, |b, _| {
+            b.iter(|| linear_nearest(&cabs, black_box(&origin), K))
+        });
+        group.finish();
+
+        let mut group = c.benchmark_group("within_2km");
+        group.bench_with_input(BenchmarkId::new("rtree", n), &n, |b, _| {
+            b.iter(|| index.within(black_box(&origin), RADIUS_METERS))
+        });
+        group.bench_with_input(BenchmarkId::new("linear", n), &n, |b, _| {
+            b.iter(|| linear_within(&cabs, black_box(&origin), RADIUS_METERS))
+        });
+        group.finish();
+    }
+}
+
+fn bench_updates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("upsert");
+    for &n in SIZES.iter() {
+        let cabs = cabs(n);
+        let mut index = SpatialIndex::bulk_load(cabs.iter().map(|(name, location)| (name.as_str(), location)));
+        let mut rng = Lcg(42);
+        let mut i = 0;
+
+        group.bench_with_input(BenchmarkId::new("rtree", n), &n, |b, _| {
+            b.iter(|| {
+                let (name, _) = &cabs[i % cabs.len()];
+                index.upsert(name, &rng.location());
+                i += 1;
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_queries, bench_updates);
+criterion_main!(benches);
@@ -0,0 +1,147 @@
+//! `GetCabs`의 page token.
+//!
+//! 첫 page를 찾을 때 조건에 맞는 cab을 전부 순서대로 정하고, 아직 주지 않은 cab 이름을 token에 넣는다.
+//! 그래서 사이에 cab이 움직여도 순서가 바뀌거나 같은 cab이 두 번 나오지 않는다.
+//! token은 HMAC으로 서명해서 클라이언트가 고치면 받지 않는다.
+
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use prost::Message;
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use tonic::Status;
+
+use crate::hello::GetCabRequest;
+use crate::store::{from_unix_millis, unix_millis};
+
+pub const MAX_PAGE_SIZE: usize = 100;
+/// page로 받을 때 `max_results`의 상한.
+pub const MAX_PAGED_RESULTS: usize = 500;
+/// 첫 page에서 이만큼 지나면 token을 받지 않는다. 오래된 순서는 믿을 수 없다.
+pub const PAGE_TOKEN_TTL: Duration = Duration::from_secs(5 * 60);
+
+// 클라이언트에게는 서명까지 붙인 base64 문자열로만 보인다
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct PageToken {
+    /// page_size, page_token을 비운 첫 요청의 SHA-256
+    #[prost(bytes = "vec", tag = "1")]
+    request_digest: ::prost::alloc::vec::Vec<u8>,
+    /// 첫 page를 찾은 시각
+    #[prost(int64, tag = "2")]
+    searched_at_ms: i64,
+    /// 아직 주지 않은 cab. 가까운 순서
+    #[prost(string, repeated, tag = "3")]
+    remaining: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageTokenError {
+    /// 읽을 수 없거나 서명이 맞지 않는다.
+    Malformed,
+    /// 첫 요청과 조건이 다르다.
+    Mismatch,
+    Expired,
+}
+
+impl fmt::Display for PageTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageTokenError::Malformed => f.write_str("page_token is malformed"),
+            PageTokenError::Mismatch => f.write_str("page_token does not match the request"),
+            PageTokenError::Expired => f.write_str("page_token has expired, search again"),
+        }
+    }
+}
+
+impl std::error::Error for PageTokenError {}
+
+impl From<PageTokenError> for Status {
+    fn from(e: PageTokenError) -> Self {
+        Status::invalid_argument(e.to_string())
+    }
+}
+
+/// 0이면 None(한 번에 다), 너무 크면 상한으로 자른다.
+pub fn page_size(req: &GetCabRequest) -> Option<usize> {
+    match req.page_size {
+        0 => None,
+        size => Some((size as usize).min(MAX_PAGE_SIZE)),
+    }
+}
+
+/// 이어서 줄 cab.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Continuation {
+    pub searched_at: SystemTime,
+    pub remaining: Vec<String>,
+}
+
+/// page token을 만들고 검사한다. 서버가 여러 대면 같은 secret을 써야 한다.
+#[derive(Debug)]
+pub struct CabPager {
+    key: hmac::Key,
+}
+
+impl Default for CabPager {
+    /// 프로세스마다 새 secret. 다시 띄우면 전에 준 token은 쓸 수 없다.
+    fn default() -> Self {
+        let mut secret = [0; 32];
+        SystemRandom::new().fill(&mut secret).expect("system random number generator failed");
+        Self::new(&secret)
+    }
+}
+
+impl CabPager {
+    pub fn new(secret: &[u8]) -> Self {
+        Self { key: hmac::Key::new(hmac::HMAC_SHA256, secret) }
+    }
+
+    /// 첫 page면 None.
+    pub fn open(&self, req: &GetCabRequest, now: SystemTime) -> Result<Option<Continuation>, PageTokenError> {
+        if req.page_token.is_empty() {
+            return Ok(None);
+        }
+        let signed = URL_SAFE_NO_PAD.decode(&req.page_token).map_err(|_| PageTokenError::Malformed)?;
+        let tag_len = hmac::HMAC_SHA256.digest_algorithm().output_len();
+        if signed.len() < tag_len {
+            return Err(PageTokenError::Malformed);
+        }
+        let (payload, tag) = signed.split_at(signed.len() - tag_len);
+        hmac::verify(&self.key, payload, tag).map_err(|_| PageTokenError::Malformed)?;
+        let token = PageToken::decode(payload).map_err(|_| PageTokenError::Malformed)?;
+
+        if token.request_digest != request_digest(req) {
+            return Err(PageTokenError::Mismatch);
+        }
+        let searched_at = from_unix_millis(token.searched_at_ms);
+        if now.duration_since(searched_at).is_ok_and(|age| age > PAGE_TOKEN_TTL) {
+            return Err(PageTokenError::Expired);
+        }
+        Ok(Some(Continuation { searched_at, remaining: token.remaining }))
+    }
+
+    /// 남은 cab이 없으면 빈 문자열.
+    pub fn seal(&self, req: &GetCabRequest, continuation: Continuation) -> String {
+        if continuation.remaining.is_empty() {
+            return String::new();
+        }
+        let token = PageToken {
+            request_digest: request_digest(req),
+            searched_at_ms: unix_millis(continuation.searched_at),
+            remaining: continuation.remaining,
+        };
+        let mut signed = token.encode_to_vec();
+        let tag = hmac::sign(&self.key, &signed);
+        signed.extend_from_slice(tag.as_ref());
+        URL_SAFE_NO_PAD.encode(signed)
+    }
+}
+
+// page마다 바뀌는 필드를 뺀 요청
+fn request_digest(req: &GetCabRequest) -> Vec<u8> {
+    let first = GetCabRequest { page_size: 0, page_token: String::new(), ..req.clone() };
+    ring::digest::digest(&ring::digest::SHA256, &first.encode_to_vec()).as_ref().to_vec()
+}
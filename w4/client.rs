@@ -0,0 +1,375 @@
+//! 여러 서버에 나눠 보내고, 실패하면 다시 보내는 `CabService` 클라이언트.
+//!
+//! `CabClient::builder()`에 서버 주소를 주고 `build()`한다. 요청은 돌아가며 보내고,
+//! 닿지 못하거나 UNAVAILABLE을 계속 주는 서버는 circuit breaker가 잠시 뺀다.
+//!
+//! 다시 보내는 것은 두 경우다.
+//! - 서버에 닿지 못했다. 요청이 나가지 않았으니 어떤 RPC든 다른 서버로 다시 보낸다.
+//! - 같은 요청을 두 번 받아도 되는 RPC(`hello_world`, `get_cabs` 같은 읽기)가 UNAVAILABLE 같은 일시적인 오류를 받았다.
+//!
+//! 다시 보내는 것까지 합쳐서 deadline 안에 끝나지 않으면 DEADLINE_EXCEEDED.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ring::rand::{SecureRandom, SystemRandom};
+use tokio::time::Instant;
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Request, Response, Status};
+
+use crate::auth::BearerToken;
+use crate::hello::cab_service_client::CabServiceClient;
+use crate::hello::{
+    CabLocationRequest, CabLocationResponse, GetCabRequest, GetCabResponse, GetCabTrackRequest, GetCabTrackResponse,
+    HelloRequest, HelloResponse, LookupZonesRequest, LookupZonesResponse,
+};
+
+pub const DEFAULT_DEADLINE: Duration = Duration::from_secs(5);
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+// 일시적인 오류. 같은 요청을 두 번 받아도 되는 RPC만 다시 보낸다
+const RETRYABLE: &[Code] = &[Code::Unavailable, Code::ResourceExhausted, Code::Aborted];
+
+// 생성된 클라이언트는 연결하지 못한 것도 UNKNOWN으로 준다
+const NOT_READY: &str = "Service was not ready";
+
+// 서버에 닿지 못해서 요청이 나가지 않았다
+fn never_sent(status: &Status) -> bool {
+    if status.code() == Code::Unknown && status.message().starts_with(NOT_READY) {
+        return true;
+    }
+    let mut source = std::error::Error::source(status);
+    while let Some(e) = source {
+        if e.downcast_ref::<hyper::Error>().is_some_and(hyper::Error::is_connect) {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+pub type Client = CabServiceClient<InterceptedService<Channel, Auth>>;
+
+/// 다시 보내는 횟수와 사이에 기다리는 시간.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// 처음 보내는 것까지 센다. 1이면 다시 보내지 않는다.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(1),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// `attempt`번째(1부터) 실패 뒤에 기다릴 시간.
+    /// 여러 클라이언트가 한꺼번에 다시 보내지 않게 0부터 상한 사이에서 고른다(full jitter).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let ceiling = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let ceiling = ceiling.min(self.max_backoff.as_secs_f64());
+        Duration::from_secs_f64(ceiling * random_fraction())
+    }
+}
+
+// [0, 1)
+fn random_fraction() -> f64 {
+    let mut bytes = [0u8; 8];
+    SystemRandom::new().fill(&mut bytes).expect("system random number generator failed");
+    (u64::from_le_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// 서버를 언제 빼고 언제 다시 넣을지.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BreakerPolicy {
+    /// 이만큼 연달아 실패하면 뺀다.
+    pub failure_threshold: u32,
+    /// 뺀 뒤 이만큼 지나면 요청 하나를 보내 보고, 성공하면 다시 넣는다.
+    pub open_for: Duration,
+}
+
+impl Default for BreakerPolicy {
+    fn default() -> Self {
+        Self { failure_threshold: 5, open_for: Duration::from_secs(30) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Breaker {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    // 보내 본 요청 하나의 결과를 기다린다. 그 요청이 취소되면 `open_for` 뒤에 다시 보내 본다
+    HalfOpen { until: Instant },
+}
+
+impl Breaker {
+    fn try_acquire(&mut self, policy: &BreakerPolicy, now: Instant) -> bool {
+        match *self {
+            Breaker::Closed { .. } => true,
+            Breaker::Open { until } | Breaker::HalfOpen { until } if now >= until => {
+                *self = Breaker::HalfOpen { until: now + policy.open_for };
+                true
+            }
+            Breaker::Open { .. } | Breaker::HalfOpen { .. } => false,
+        }
+    }
+
+    fn record(&mut self, policy: &BreakerPolicy, failed: bool, now: Instant) {
+        *self = match (*self, failed) {
+            (_, false) => Breaker::Closed { failures: 0 },
+            (Breaker::Closed { failures }, true) if failures + 1 < policy.failure_threshold => {
+                Breaker::Closed { failures: failures + 1 }
+            }
+            (_, true) => Breaker::Open { until: now + policy.open_for },
+        };
+    }
+}
+
+/// 모든 요청에 token을 붙이거나 아무것도 하지 않는다.
+#[derive(Debug, Clone, Default)]
+pub struct Auth(Option<BearerToken>);
+
+impl Interceptor for Auth {
+    fn call(&mut self, req: Request<()>) -> Result<Request<()>, Status> {
+        match &mut self.0 {
+            Some(token) => token.call(req),
+            None => Ok(req),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Backend {
+    uri: String,
+    client: Client,
+    breaker: Mutex<Breaker>,
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    NoEndpoints,
+    InvalidUri(String, tonic::transport::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::NoEndpoints => f.write_str("at least one endpoint is required"),
+            ClientError::InvalidUri(uri, e) => write!(f, "invalid endpoint {:?}: {}", uri, e),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// `CabClient` 설정.
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    endpoints: Vec<String>,
+    deadline: Duration,
+    connect_timeout: Duration,
+    retry: RetryPolicy,
+    breaker: BreakerPolicy,
+    token: Option<BearerToken>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            deadline: DEFAULT_DEADLINE,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            retry: RetryPolicy::default(),
+            breaker: BreakerPolicy::default(),
+            token: None,
+        }
+    }
+}
+
+impl ClientBuilder {
+    /// `http://host:port` 꼴. 여러 번 부르면 돌아가며 보낸다.
+    pub fn endpoint(mut self, uri: impl Into<String>) -> Self {
+        self.endpoints.push(uri.into());
+        self
+    }
+
+    pub fn endpoints<I>(mut self, uris: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.endpoints.extend(uris.into_iter().map(Into::into));
+        self
+    }
+
+    /// 요청 하나에 쓸 수 있는 시간. 다시 보내는 것까지 포함한다.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn circuit_breaker(mut self, breaker: BreakerPolicy) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    pub fn bearer_token(mut self, token: BearerToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// 연결은 처음 보낼 때 한다. 그래서 꺼져 있는 서버가 있어도 만들 수 있다.
+    pub fn build(self) -> Result<CabClient, ClientError> {
+        let Self { endpoints, deadline, connect_timeout, retry, breaker, token } = self;
+        if endpoints.is_empty() {
+            return Err(ClientError::NoEndpoints);
+        }
+        let backends = endpoints
+            .into_iter()
+            .map(|uri| {
+                let endpoint = match Endpoint::from_shared(uri.clone()) {
+                    Ok(endpoint) => endpoint.connect_timeout(connect_timeout),
+                    Err(e) => return Err(ClientError::InvalidUri(uri, e)),
+                };
+                let channel = InterceptedService::new(endpoint.connect_lazy(), Auth(token.clone()));
+                let client = CabServiceClient::new(channel);
+                Ok(Backend { uri, client, breaker: Mutex::new(Breaker::Closed { failures: 0 }) })
+            })
+            .collect::<Result<_, _>>()?;
+        let shared = Shared { backends, next: AtomicUsize::new(0), retry, breaker };
+        Ok(CabClient { shared: Arc::new(shared), deadline })
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    backends: Vec<Backend>,
+    next: AtomicUsize,
+    retry: RetryPolicy,
+    breaker: BreakerPolicy,
+}
+
+impl Shared {
+    // 돌아가며 고르되 빠진 서버는 건너뛴다
+    fn pick(&self) -> Option<&Backend> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        (0..self.backends.len())
+            .map(|i| &self.backends[(start + i) % self.backends.len()])
+            .find(|backend| backend.breaker.lock().unwrap().try_acquire(&self.breaker, now))
+    }
+}
+
+/// 부를 때마다 다른 서버로 보내는 `CabService` 클라이언트. clone은 싸다.
+#[derive(Debug, Clone)]
+pub struct CabClient {
+    shared: Arc<Shared>,
+    deadline: Duration,
+}
+
+impl CabClient {
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// deadline만 다른 클라이언트. 서버와 circuit breaker는 같이 쓴다.
+    pub fn with_deadline(&self, deadline: Duration) -> Self {
+        Self { shared: self.shared.clone(), deadline }
+    }
+
+    pub async fn hello_world(&self, req: HelloRequest) -> Result<Response<HelloResponse>, Status> {
+        self.call(true, req, |mut client, req| async move { client.hello_world(req).await }).await
+    }
+
+    /// 같은 위치를 두 번 기록하면 `updated_at`이 바뀌므로 닿지 못했을 때만 다시 보낸다.
+    pub async fn record_cab_location(&self, req: CabLocationRequest) -> Result<Response<CabLocationResponse>, Status> {
+        self.call(false, req, |mut client, req| async move { client.record_cab_location(req).await }).await
+    }
+
+    pub async fn get_cabs(&self, req: GetCabRequest) -> Result<Response<GetCabResponse>, Status> {
+        self.call(true, req, |mut client, req| async move { client.get_cabs(req).await }).await
+    }
+
+    pub async fn get_cab_track(&self, req: GetCabTrackRequest) -> Result<Response<GetCabTrackResponse>, Status> {
+        self.call(true, req, |mut client, req| async move { client.get_cab_track(req).await }).await
+    }
+
+    pub async fn lookup_zones(&self, req: LookupZonesRequest) -> Result<Response<LookupZonesResponse>, Status> {
+        self.call(true, req, |mut client, req| async move { client.lookup_zones(req).await }).await
+    }
+
+    async fn call<T, U, F, Fut>(&self, idempotent: bool, message: T, send: F) -> Result<Response<U>, Status>
+    where
+        T: Clone,
+        F: Fn(Client, Request<T>) -> Fut,
+        Fut: Future<Output = Result<Response<U>, Status>>,
+    {
+        let shared = &self.shared;
+        let deadline = Instant::now() + self.deadline;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let backend = shared
+                .pick()
+                .ok_or_else(|| Status::unavailable(format!("all {} endpoint(s) are failing", shared.backends.len())))?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let mut request = Request::new(message.clone());
+            request.set_timeout(remaining);
+
+            let timed_out = || Status::deadline_exceeded(format!("{} did not answer in time", backend.uri));
+            let (result, sent) = match tokio::time::timeout(remaining, send(backend.client.clone(), request)).await {
+                Ok(Err(status)) if never_sent(&status) => {
+                    (Err(Status::unavailable(format!("{}: {}", backend.uri, status.message()))), false)
+                }
+                // channel은 grpc-timeout이 지나면 CANCELLED를 준다
+                Ok(Err(status)) if status.code() == Code::Cancelled && Instant::now() >= deadline => {
+                    (Err(timed_out()), true)
+                }
+                Ok(result) => (result, true),
+                Err(_) => (Err(timed_out()), true),
+            };
+            // 닿지 못했거나 UNAVAILABLE일 때만 서버 탓이다.
+            // deadline은 부른 쪽이 짧게 줬을 수도 있으니 성공으로도 실패로도 세지 않는다
+            if !matches!(&result, Err(status) if status.code() == Code::DeadlineExceeded) {
+                let failed = matches!(&result, Err(status) if status.code() == Code::Unavailable);
+                backend.breaker.lock().unwrap().record(&shared.breaker, failed, Instant::now());
+            }
+
+            let status = match result {
+                Ok(response) => return Ok(response),
+                Err(status) => status,
+            };
+            let retryable = !sent || (idempotent && RETRYABLE.contains(&status.code()));
+            let backoff = shared.retry.backoff(attempt);
+            if !retryable || attempt >= shared.retry.max_attempts || Instant::now() + backoff >= deadline {
+                return Err(status);
+            }
+            tokio::time::sleep(backoff).await;
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::rand::{SecureRandom, SystemRandom};
+use tokio::sync::{mpsc, oneshot};
+use tonic::{Request, Response, Status};
+
+use crate::hello::dispatch_service_server::DispatchService as DispatchServiceRpc;
+use crate::hello::{
+    CabStatus, CabTripRequest, CancelTripRequest, GetCabRequest, GetTripRequest, RideRequest, Trip, TripAction, TripParty,
+    TripState,
+};
+use crate::auth::{self, Forbidden};
+use crate::query::CabQuery;
+use crate::registry::CabRegistry;
+use crate::store::Store;
+use crate::trip::{self, InvalidTransition, TripEvent};
+use crate::validate;
+
+pub const DEFAULT_OFFER_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 끝난 trip을 메모리에 이만큼 남겨 둔다. 더 오래된 것은 `store`에서만 읽을 수 있다.
+pub const DEFAULT_FINISHED_TRIPS_KEPT: usize = 1024;
+
+/// 서비스 안에서 쓰는 오류. 밖으로 나갈 때 `Status`가 된다.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DispatchError {
+    TripNotFound(String),
+    /// 다른 cab에게 간 trip을 건드렸다.
+    NotAssigned { trip_id: String, cab_name: String },
+    Transition(InvalidTransition),
+    Forbidden(Forbidden),
+    /// 저장해 둔 trip을 읽지 못했다.
+    Store(String),
+}
+
+impl From<InvalidTransition> for DispatchError {
+    fn from(e: InvalidTransition) -> Self {
+        DispatchError::Transition(e)
+    }
+}
+
+impl From<Forbidden> for DispatchError {
+    fn from(e: Forbidden) -> Self {
+        DispatchError::Forbidden(e)
+    }
+}
+
+impl From<DispatchError> for Status {
+    fn from(e: DispatchError) -> Self {
+        match e {
+            DispatchError::TripNotFound(trip_id) => Status::not_found(format!("trip {:?} not found", trip_id)),
+            DispatchError::NotAssigned { trip_id, cab_name } => {
+                Status::permission_denied(format!("trip {} is not assigned to cab {:?}", trip_id, cab_name))
+            }
+            DispatchError::Transition(e) => Status::failed_precondition(e.to_string()),
+            DispatchError::Forbidden(e) => e.into(),
+            DispatchError::Store(e) => Status::internal(format!("failed to load trip: {}", e)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TripRecord {
+    trip: Trip,
+    query: CabQuery,
+    // 제안할 때마다 늘어난다. 이미 끝난 제안의 timeout을 무시하려고 쓴다
+    offer_id: u64,
+}
+
+#[derive(Debug, Default)]
+struct Trips {
+    trips: HashMap<String, TripRecord>,
+    // 제안을 받았거나 배차된 cab -> trip_id. 여기 있는 cab에게는 새로 제안하지 않는다
+    by_cab: HashMap<String, String>,
+    // 끝난 trip_id. 오래된 것부터 `trips`에서 뺀다
+    finished: VecDeque<String>,
+}
+
+impl Trips {
+    // 방금 끝난 trip은 `keep`이 0이어도 남긴다. 응답으로 돌려줘야 하므로
+    fn finish(&mut self, trip_id: &str, keep: usize) {
+        self.finished.push_back(trip_id.to_string());
+        while self.finished.len() > keep.max(1) {
+            if let Some(old) = self.finished.pop_front() {
+                self.trips.remove(&old);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum TripWrite {
+    Save(Box<Trip>),
+    // 앞에 보낸 것을 다 쓰면 알려 준다
+    Flush(oneshot::Sender<()>),
+}
+
+/// `cabs.v1.DispatchService` 구현. 진행 중인 trip과 최근에 끝난 trip은 메모리에 있고,
+/// `store`가 있으면 바뀔 때마다 거기에도 쓴다.
+#[derive(Debug, Clone)]
+pub struct DispatchService {
+    registry: Arc<CabRegistry>,
+    trips: Arc<Mutex<Trips>>,
+    offer_timeout: Duration,
+    finished_trips_kept: usize,
+    store: Option<Arc<dyn Store>>,
+    writes: Option<mpsc::UnboundedSender<TripWrite>>,
+}
+
+impl DispatchService {
+    pub fn new(registry: Arc<CabRegistry>) -> Self {
+        Self {
+            registry,
+            trips: Arc::default(),
+            offer_timeout: DEFAULT_OFFER_TIMEOUT,
+            finished_trips_kept: DEFAULT_FINISHED_TRIPS_KEPT,
+            store: None,
+            writes: None,
+        }
+    }
+
+    /// 서버를 다시 띄운 뒤에도 `get_trip`으로 지난 trip을 볼 수 있다.
+    /// 다시 띄우기 전에 진행 중이던 trip은 이어서 진행하지 않는다.
+    /// 쓰는 task를 띄우므로 tokio runtime 안에서 부른다.
+    pub fn with_store(mut self, store: Arc<dyn Store>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(write_trips(store.clone(), rx));
+        self.store = Some(store);
+        self.writes = Some(tx);
+        self
+    }
+
+    /// 끝난 trip을 메모리에 몇 개까지 남길지.
+    pub fn with_finished_trips_kept(mut self, kept: usize) -> Self {
+        self.finished_trips_kept = kept;
+        self
+    }
+
+    /// 기사가 이 안에 accept/decline하지 않으면 거절한 것으로 보고 다음 cab에게 넘긴다.
+    pub fn with_offer_timeout(mut self, offer_timeout: Duration) -> Self {
+        self.offer_timeout = offer_timeout;
+        self
+    }
+
+    // SEARCHING인 trip을 다음 cab에게 제안하거나 NO_CAB_AVAILABLE로 끝낸다
+    fn offer_next(&self, trips: &mut Trips, trip_id: &str) -> Result<(), DispatchError> {
+        let Trips { trips: records, by_cab, .. } = trips;
+        let record = records.get_mut(trip_id).ok_or_else(|| not_found(trip_id))?;
+        let pickup = record.trip.pickup.clone().unwrap_or_default();
+        let declined = &record.trip.declined_cabs;
+        let found = self
+            .registry
+            .search_by(&pickup, &record.query, |name, _| {
+                !by_cab.contains_key(name) && !declined.iter().any(|d| d == name)
+            })
+            .into_iter()
+            .next();
+
+        match found {
+            Some((cab, _)) => {
+                apply(&mut record.trip, TripEvent::Offer)?;
+                record.offer_id += 1;
+                record.trip.offer_expires_at_ms = unix_millis(SystemTime::now() + self.offer_timeout);
+                by_cab.insert(cab.name.clone(), trip_id.to_string());
+                record.trip.cab_name = cab.name;
+                self.expire_offer_later(trip_id.to_string(), record.offer_id);
+            }
+            None => apply(&mut record.trip, TripEvent::NoCab)?,
+        }
+        self.persist(&record.trip);
+        if trip::is_finished(state(&record.trip)) {
+            trips.finish(trip_id, self.finished_trips_kept);
+        }
+        Ok(())
+    }
+
+    // trips lock을 쥔 채로 불러야 같은 trip을 쓰는 순서가 뒤바뀌지 않는다. 쓰는 것은 `write_trips`가 한다
+    fn persist(&self, trip: &Trip) {
+        if let Some(writes) = &self.writes {
+            let _ = writes.send(TripWrite::Save(Box::new(trip.clone())));
+        }
+    }
+
+    // 응답하기 전에 부른다. trips lock을 놓고 불러야 한다
+    async fn flush(&self) {
+        if let Some(writes) = &self.writes {
+            let (done, wait) = oneshot::channel();
+            if writes.send(TripWrite::Flush(done)).is_ok() {
+                let _ = wait.await;
+            }
+        }
+    }
+
+    fn expire_offer_later(&self, trip_id: String, offer_id: u64) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(service.offer_timeout).await;
+            let mut trips = service.trips.lock().unwrap();
+            let waiting = trips
+                .trips
+                .get(&trip_id)
+                .is_some_and(|record| record.offer_id == offer_id && state(&record.trip) == TripState::Offered);
+            if waiting {
+                let _ = service.decline(&mut trips, &trip_id);
+            }
+        });
+    }
+
+    fn decline(&self, trips: &mut Trips, trip_id: &str) -> Result<(), DispatchError> {
+        let record = trips.trips.get_mut(trip_id).ok_or_else(|| not_found(trip_id))?;
+        apply(&mut record.trip, TripEvent::Decline)?;
+        let cab_name = std::mem::take(&mut record.trip.cab_name);
+        record.trip.offer_expires_at_ms = 0;
+        record.trip.declined_cabs.push(cab_name.clone());
+        trips.by_cab.remove(&cab_name);
+        self.offer_next(trips, trip_id)
+    }
+
+    // 배차가 확정되면 ON_TRIP, 확정된 trip이 끝나면 다시 AVAILABLE
+    fn update_cab_status(&self, cab_name: &str, before: TripState, after: TripState) {
+        let on_trip = |state| matches!(state, TripState::Accepted | TripState::InProgress);
+        match (on_trip(before), on_trip(after)) {
+            (false, true) => {
+                self.registry.set_status(cab_name, CabStatus::OnTrip);
+            }
+            (true, false) => {
+                self.registry.set_status(cab_name, CabStatus::Available);
+            }
+            _ => {}
+        }
+    }
+
+    fn stored_trip(&self, trip_id: &str) -> Result<Option<Trip>, DispatchError> {
+        match &self.store {
+            Some(store) => store.load_trip(trip_id).map_err(|e| DispatchError::Store(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    // id를 붙이고 첫 cab에게 제안한다
+    fn create_trip(&self, mut trip: Trip, query: CabQuery) -> Result<Trip, DispatchError> {
+        let mut trips = self.trips.lock().unwrap();
+        let trip_id = loop {
+            let trip_id = new_trip_id();
+            if !trips.trips.contains_key(&trip_id) {
+                break trip_id;
+            }
+        };
+        trip.trip_id = trip_id.clone();
+        trips.trips.insert(trip_id.clone(), TripRecord { trip, query, offer_id: 0 });
+        self.offer_next(&mut trips, &trip_id)?;
+        Ok(trips.trips[&trip_id].trip.clone())
+    }
+
+    // 기사가 자기 trip에 하는 일
+    fn driver_action(&self, req: Request<TripAction>, event: TripEvent) -> Result<Trip, DispatchError> {
+        auth::authorize_driver(&req, &req.get_ref().cab_name)?;
+        let action = req.into_inner();
+        let mut trips = self.trips.lock().unwrap();
+        let record = trips.trips.get_mut(&action.trip_id).ok_or_else(|| not_found(&action.trip_id))?;
+        if record.trip.cab_name.is_empty() || record.trip.cab_name != action.cab_name {
+            return Err(DispatchError::NotAssigned { trip_id: action.trip_id, cab_name: action.cab_name });
+        }
+
+        match event {
+            TripEvent::Decline => self.decline(&mut trips, &action.trip_id)?,
+            event => {
+                let before = state(&record.trip);
+                apply(&mut record.trip, event)?;
+                record.trip.offer_expires_at_ms = 0;
+                let after = state(&record.trip);
+                let cab_name = record.trip.cab_name.clone();
+                self.update_cab_status(&cab_name, before, after);
+                self.persist(&record.trip);
+                if trip::is_finished(after) {
+                    trips.by_cab.remove(&cab_name);
+                    trips.finish(&action.trip_id, self.finished_trips_kept);
+                }
+            }
+        }
+        Ok(trips.trips[&action.trip_id].trip.clone())
+    }
+}
+
+#[tonic::async_trait]
+impl DispatchServiceRpc for DispatchService {
+    async fn request_ride(&self, req: Request<RideRequest>) -> Result<Response<Trip>, Status> {
+        auth::authorize_rider(&req, &req.get_ref().rider_id)?;
+        let req = req.into_inner();
+        if req.rider_id.is_empty() {
+            return Err(Status::invalid_argument("rider_id is required"));
+        }
+        let pickup = validate::location("pickup", req.pickup.as_ref())?;
+        let dropoff = match &req.dropoff {
+            Some(dropoff) => Some(validate::location("dropoff", Some(dropoff))?),
+            None => None,
+        };
+        // 제일 가까운 하나만 보면 된다
+        let query = CabQuery {
+            max_results: 1,
+            ..CabQuery::from_request(&GetCabRequest { filter: req.filter, ..GetCabRequest::default() })
+        };
+
+        let trip = Trip {
+            rider_id: req.rider_id,
+            pickup: Some(pickup),
+            dropoff,
+            state: TripState::Searching as i32,
+            ..Trip::default()
+        };
+        let trip = self.create_trip(trip, query)?;
+        self.flush().await;
+        Ok(Response::new(trip))
+    }
+
+    async fn get_cab_trip(&self, req: Request<CabTripRequest>) -> Result<Response<Trip>, Status> {
+        auth::authorize_driver(&req, &req.get_ref().cab_name)?;
+        let cab_name = req.into_inner().cab_name;
+        let trips = self.trips.lock().unwrap();
+        let trip = trips
+            .by_cab
+            .get(&cab_name)
+            .and_then(|trip_id| trips.trips.get(trip_id))
+            .ok_or_else(|| Status::not_found(format!("cab {:?} has no trip", cab_name)))?;
+        Ok(Response::new(trip.trip.clone()))
+    }
+
+    async fn get_trip(&self, req: Request<GetTripRequest>) -> Result<Response<Trip>, Status> {
+        let claims = auth::claims(&req).cloned();
+        let trip_id = req.into_inner().trip_id;
+        let in_memory = self.trips.lock().unwrap().trips.get(&trip_id).map(|record| record.trip.clone());
+        let trip = match in_memory {
+            Some(trip) => trip,
+            // 다시 띄우기 전의 trip
+            None => self.stored_trip(&trip_id)?.ok_or_else(|| not_found(&trip_id))?,
+        };
+        // 손님 본인이나 배차된 기사만 볼 수 있다
+        if let Some(claims) = claims {
+            let rider = claims.check_rider(&trip.rider_id);
+            rider.or_else(|_| claims.check_driver_of(&trip.cab_name))?;
+        }
+        Ok(Response::new(trip))
+    }
+
+    async fn accept_trip(&self, req: Request<TripAction>) -> Result<Response<Trip>, Status> {
+        let trip = self.driver_action(req, TripEvent::Accept)?;
+        self.flush().await;
+        Ok(Response::new(trip))
+    }
+
+    async fn decline_trip(&self, req: Request<TripAction>) -> Result<Response<Trip>, Status> {
+        let trip = self.driver_action(req, TripEvent::Decline)?;
+        self.flush().await;
+        Ok(Response::new(trip))
+    }
+
+    async fn start_trip(&self, req: Request<TripAction>) -> Result<Response<Trip>, Status> {
+        let trip = self.driver_action(req, TripEvent::Start)?;
+        self.flush().await;
+        Ok(Response::new(trip))
+    }
+
+    async fn complete_trip(&self, req: Request<TripAction>) -> Result<Response<Trip>, Status> {
+        let trip = self.driver_action(req, TripEvent::Complete)?;
+        self.flush().await;
+        Ok(Response::new(trip))
+    }
+
+    async fn cancel_trip(&self, req: Request<CancelTripRequest>) -> Result<Response<Trip>, Status> {
+        let party = match TripParty::from_i32(req.get_ref().cancelled_by) {
+            Some(TripParty::Rider) => TripParty::Rider,
+            Some(TripParty::Driver) => {
+                let req = req.map(|req| TripAction { trip_id: req.trip_id, cab_name: req.cab_name });
+                let trip = self.driver_action(req, TripEvent::Cancel(TripParty::Driver))?;
+                self.flush().await;
+                return Ok(Response::new(trip));
+            }
+            _ => return Err(Status::invalid_argument("cancelled_by must be RIDER or DRIVER")),
+        };
+
+        let claims = auth::claims(&req).cloned();
+        let req = req.into_inner();
+        let trip = {
+            let mut trips = self.trips.lock().unwrap();
+            let record = trips.trips.get_mut(&req.trip_id).ok_or_else(|| not_found(&req.trip_id))?;
+            if let Some(claims) = claims {
+                claims.check_rider(&record.trip.rider_id)?;
+            }
+            let before = state(&record.trip);
+            apply(&mut record.trip, TripEvent::Cancel(party)).map_err(DispatchError::from)?;
+            record.trip.offer_expires_at_ms = 0;
+            let (cab_name, trip) = (record.trip.cab_name.clone(), record.trip.clone());
+            trips.by_cab.remove(&cab_name);
+            trips.finish(&req.trip_id, self.finished_trips_kept);
+            self.update_cab_status(&cab_name, before, state(&trip));
+            self.persist(&trip);
+            trip
+        };
+        self.flush().await;
+        Ok(Response::new(trip))
+    }
+}
+
+// 보낸 순서대로 쓴다. 밀린 것은 모아서 blocking thread에서 한 번에 쓴다. 저장에 실패해도 배차는 계속한다
+async fn write_trips(store: Arc<dyn Store>, mut writes: mpsc::UnboundedReceiver<TripWrite>) {
+    while let Some(first) = writes.recv().await {
+        let mut batch = vec![first];
+        while let Ok(write) = writes.try_recv() {
+            batch.push(write);
+        }
+        let store = store.clone();
+        let saved = tokio::task::spawn_blocking(move || {
+            let mut done = Vec::new();
+            for write in batch {
+                match write {
+                    TripWrite::Save(trip) => {
+                        if let Err(e) = store.save_trip(&trip) {
+                            println!("ERROR: failed to save trip {}: {}", trip.trip_id, e);
+                        }
+                    }
+                    TripWrite::Flush(flushed) => done.push(flushed),
+                }
+            }
+            done
+        })
+        .await;
+        match saved {
+            Ok(done) => done.into_iter().for_each(|flushed| {
+                let _ = flushed.send(());
+            }),
+            Err(e) => println!("ERROR: trip writer panicked: {}", e),
+        }
+    }
+}
+
+fn state(trip: &Trip) -> TripState {
+    TripState::from_i32(trip.state).unwrap_or(TripState::Unspecified)
+}
+
+// 상태는 `trip::transition`을 거쳐서만 바꾼다
+fn apply(trip: &mut Trip, event: TripEvent) -> Result<(), InvalidTransition> {
+    let next = trip::transition(state(trip), event)?;
+    trip.state = next as i32;
+    if let TripEvent::Cancel(party) = event {
+        trip.cancelled_by = party as i32;
+    }
+    Ok(())
+}
+
+// 다시 띄워도, 서버 여러 대가 store를 같이 써도 겹치지 않게 무작위로 만든다
+fn new_trip_id() -> String {
+    let mut bytes = [0u8; 12];
+    SystemRandom::new().fill(&mut bytes).expect("system random number generator failed");
+    format!("trip-{}", URL_SAFE_NO_PAD.encode(bytes))
+}
+
+fn not_found(trip_id: &str) -> DispatchError {
+    DispatchError::TripNotFound(trip_id.to_string())
+}
+
+fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as i64)
+}
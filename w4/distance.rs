@@ -0,0 +1,45 @@
+use crate::hello::Location;
+
+// 지구를 구로 보고 계산한다. 평균 반지름 (IUGG)
+pub const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// 두 좌표 사이의 대원 거리(미터). haversine 공식.
+pub fn distance_meters(a: &Location, b: &Location) -> f64 {
+    let (lat1, lon1) = (a.latitude.to_radians(), a.longitude.to_radians());
+    let (lat2, lon2) = (b.latitude.to_radians(), b.longitude.to_radians());
+
+    let dlat = lat2 - lat1;
+    let dlon = lon2 - lon1;
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_METERS * h.sqrt().min(1.0).asin()
+}
+
+/// `from`에서 `to`로 출발할 때의 방위각. 진북에서 시계 방향, 도 [0, 360)
+pub fn bearing_degrees(from: &Location, to: &Location) -> f64 {
+    let (lat1, lat2) = (from.latitude.to_radians(), to.latitude.to_radians());
+    let dlon = (to.longitude - from.longitude).to_radians();
+
+    let y = dlon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// 단위 구 위의 3차원 좌표. 두 점의 직선(chord) 거리가 대원 거리와 같은 순서라서
+/// 공간 인덱스에서 그대로 유클리드 거리로 쓸 수 있다.
+pub fn to_unit_vector(location: &Location) -> [f64; 3] {
+    let lat = location.latitude.to_radians();
+    let lon = location.longitude.to_radians();
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+/// 단위 구 위의 chord 길이 -> 대원 거리(미터)
+pub fn chord_to_meters(chord: f64) -> f64 {
+    2.0 * EARTH_RADIUS_METERS * (chord / 2.0).min(1.0).asin()
+}
+
+/// 대원 거리(미터) -> 단위 구 위의 chord 길이
+pub fn meters_to_chord(meters: f64) -> f64 {
+    let angle = (meters / EARTH_RADIUS_METERS).min(std::f64::consts::PI);
+    2.0 * (angle / 2.0).sin()
+}
@@ -0,0 +1,150 @@
+//! `GetCabs`가 cab마다 붙이는 거리와 도착 시간.
+//!
+//! 기본은 `HeuristicEta`로 직선 거리에서 어림하고, 도로망 파일이 있으면 `RoadGraph`로 길을 찾는다.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::distance::{bearing_degrees, distance_meters};
+use crate::hello::{Cab, Location};
+
+// 이보다 느리면 서 있는 것으로 보고 heading도 믿지 않는다
+const MOVING_SPEED_MPS: f64 = 1.0;
+
+/// cab 하나가 손님에게 오는 길.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub route_meters: f64,
+    pub duration: Duration,
+}
+
+/// 도착 시간 추정. 계산에 CPU를 쓸 수 있으니 async 코드에서는 `spawn_blocking`으로 부른다.
+pub trait EtaEstimator: Send + Sync + fmt::Debug {
+    /// `cab`에 있는 cab이 `pickup`까지 오는 길.
+    fn estimate(&self, cab: &Location, pickup: &Location) -> Estimate;
+
+    /// 같은 `pickup`으로 오는 여러 cab. 한 번에 계산하는 것이 빠르면 다시 구현한다.
+    fn estimate_many(&self, cabs: &[Location], pickup: &Location) -> Vec<Estimate> {
+        cabs.iter().map(|cab| self.estimate(cab, pickup)).collect()
+    }
+}
+
+/// 도로 정보 없이 직선 거리와 cab의 속도, 방향으로 어림한다.
+/// 속도가 0이면 시간을 계산할 수 없으니 값은 `new`로 검사해서 넣는다.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeuristicEta {
+    detour_factor: f64,
+    default_speed_mps: f64,
+    max_speed_mps: f64,
+    turn_around: Duration,
+}
+
+/// `HeuristicEta::new`에 쓸 수 없는 값을 줬다.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidHeuristic(String);
+
+impl fmt::Display for InvalidHeuristic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidHeuristic {}
+
+impl Default for HeuristicEta {
+    // 시내 평균 30km/h, 최대 60km/h
+    fn default() -> Self {
+        Self { detour_factor: 1.3, default_speed_mps: 8.0, max_speed_mps: 16.7, turn_around: Duration::from_secs(90) }
+    }
+}
+
+impl HeuristicEta {
+    /// 세 값 모두 0보다 큰 유한한 수여야 한다.
+    /// - `detour_factor`: 길이 직선보다 이만큼 길다고 본다.
+    /// - `default_speed_mps`: 속도를 모르거나 서 있는 cab의 평균 속도, 초속 미터.
+    /// - `max_speed_mps`: 보내온 속도가 이보다 빨라도 이 속도로 온다고 본다.
+    /// - `turn_around`: 정반대로 가고 있는 cab이 돌아오는 데 더 걸리는 시간. 옆으로 가고 있으면 절반.
+    pub fn new(
+        detour_factor: f64,
+        default_speed_mps: f64,
+        max_speed_mps: f64,
+        turn_around: Duration,
+    ) -> Result<Self, InvalidHeuristic> {
+        for (name, value) in [
+            ("detour_factor", detour_factor),
+            ("default_speed_mps", default_speed_mps),
+            ("max_speed_mps", max_speed_mps),
+        ] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(InvalidHeuristic(format!(
+                    "{} must be a finite number greater than 0, not {}",
+                    name, value
+                )));
+            }
+        }
+        Ok(Self { detour_factor, default_speed_mps, max_speed_mps, turn_around })
+    }
+
+    pub fn detour_factor(&self) -> f64 {
+        self.detour_factor
+    }
+
+    pub fn default_speed_mps(&self) -> f64 {
+        self.default_speed_mps
+    }
+
+    pub fn max_speed_mps(&self) -> f64 {
+        self.max_speed_mps
+    }
+
+    pub fn turn_around(&self) -> Duration {
+        self.turn_around
+    }
+
+    /// `meters`를 속도를 모르는 cab이 가는 데 걸리는 시간.
+    pub fn travel_time(&self, meters: f64) -> Duration {
+        Duration::from_secs_f64(meters / self.default_speed_mps)
+    }
+
+    fn speed_mps(&self, cab: &Location) -> f64 {
+        match cab.speed_mps {
+            Some(speed) if speed >= MOVING_SPEED_MPS => speed.min(self.max_speed_mps),
+            _ => self.default_speed_mps,
+        }
+    }
+
+    /// 가고 있는 방향이 `pickup`에서 벗어난 만큼 더 걸리는 시간.
+    pub fn turn_penalty(&self, cab: &Location, pickup: &Location) -> Duration {
+        match (cab.heading_degrees, cab.speed_mps) {
+            (Some(heading), Some(speed)) if speed >= MOVING_SPEED_MPS => {
+                let off = (bearing_degrees(cab, pickup) - heading).to_radians();
+                self.turn_around.mul_f64((1.0 - off.cos()) / 2.0)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+impl EtaEstimator for HeuristicEta {
+    fn estimate(&self, cab: &Location, pickup: &Location) -> Estimate {
+        let route_meters = distance_meters(cab, pickup) * self.detour_factor;
+        let duration = Duration::from_secs_f64(route_meters / self.speed_mps(cab)) + self.turn_penalty(cab, pickup);
+        Estimate { route_meters, duration }
+    }
+}
+
+/// `found`(cab, 직선 거리)에 거리와 도착 시간을 채운다.
+pub fn fill_estimates(estimator: &dyn EtaEstimator, pickup: &Location, found: Vec<(Cab, f64)>) -> Vec<Cab> {
+    let locations: Vec<_> = found.iter().map(|(cab, _)| cab.location.clone().unwrap_or_default()).collect();
+    let estimates = estimator.estimate_many(&locations, pickup);
+    found
+        .into_iter()
+        .zip(estimates)
+        .map(|((mut cab, distance), estimate)| {
+            cab.distance_meters = Some(distance);
+            cab.route_distance_meters = Some(estimate.route_meters);
+            cab.eta_seconds = Some(estimate.duration.as_secs_f64().round().min(f64::from(u32::MAX)) as u32);
+            cab
+        })
+        .collect()
+}
@@ -0,0 +1,237 @@
+//! GeoJSON으로 받은 zone.
+//!
+//! `FeatureCollection`의 feature 하나가 zone 하나다. geometry는 `Polygon`이나 `MultiPolygon`이고,
+//! properties에 `name`과 `kind`(`service_area`, `airport_queue`, `no_pickup`)를 넣는다.
+//!
+//! ```json
+//! {"type": "FeatureCollection", "features": [{
+//!     "type": "Feature",
+//!     "properties": {"name": "seoul", "kind": "service_area"},
+//!     "geometry": {"type": "Polygon", "coordinates": [[[126.7, 37.4], [127.2, 37.4], [127.2, 37.7], [126.7, 37.4]]]}
+//! }]}
+//! ```
+//!
+//! 경계 위의 점은 안에 있는 것으로 본다.
+
+use std::fmt;
+
+use geo::{BoundingRect, Coord, Intersects, LineString, MultiPolygon, Point, Polygon, Rect};
+use serde::Deserialize;
+
+use crate::hello::{Location, ZoneKind};
+
+#[derive(Debug)]
+pub enum GeofenceError {
+    Json(serde_json::Error),
+    /// JSON은 읽었지만 zone으로 쓸 수 없다.
+    Invalid(String),
+}
+
+impl fmt::Display for GeofenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeofenceError::Json(e) => write!(f, "invalid GeoJSON: {}", e),
+            GeofenceError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for GeofenceError {}
+
+impl From<serde_json::Error> for GeofenceError {
+    fn from(e: serde_json::Error) -> Self {
+        GeofenceError::Json(e)
+    }
+}
+
+// GeoJSON에서 쓰는 것만
+#[derive(Deserialize)]
+struct FeatureCollection {
+    features: Vec<Feature>,
+}
+
+#[derive(Deserialize)]
+struct Feature {
+    geometry: Geometry,
+    properties: Option<Properties>,
+}
+
+// 좌표는 [경도, 위도]
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "coordinates")]
+enum Geometry {
+    Polygon(Vec<Vec<Vec<f64>>>),
+    MultiPolygon(Vec<Vec<Vec<Vec<f64>>>>),
+}
+
+#[derive(Deserialize)]
+struct Properties {
+    name: Option<String>,
+    kind: Option<Kind>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    ServiceArea,
+    AirportQueue,
+    NoPickup,
+}
+
+impl From<Kind> for ZoneKind {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::ServiceArea => ZoneKind::ServiceArea,
+            Kind::AirportQueue => ZoneKind::AirportQueue,
+            Kind::NoPickup => ZoneKind::NoPickup,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub kind: ZoneKind,
+    area: MultiPolygon<f64>,
+    // 대부분의 점은 여기서 걸러진다
+    bbox: Rect<f64>,
+}
+
+impl Zone {
+    pub fn contains(&self, location: &Location) -> bool {
+        let point = Point::new(location.longitude, location.latitude);
+        self.bbox.intersects(&point) && self.area.intersects(&point)
+    }
+
+    fn from_feature(index: usize, feature: Feature) -> Result<Self, GeofenceError> {
+        let invalid = |message: &str| GeofenceError::Invalid(format!("feature {}: {}", index, message));
+        let properties = feature.properties.ok_or_else(|| invalid("properties are required"))?;
+        let name = properties.name.filter(|name| !name.is_empty()).ok_or_else(|| invalid("name is required"))?;
+        let kind = properties.kind.ok_or_else(|| invalid("kind is required"))?.into();
+
+        let polygons = match feature.geometry {
+            Geometry::Polygon(rings) => vec![rings],
+            Geometry::MultiPolygon(polygons) => polygons,
+        };
+        let area = polygons
+            .into_iter()
+            .map(to_polygon)
+            .collect::<Result<MultiPolygon<f64>, String>>()
+            .map_err(|message| invalid(&message))?;
+        let bbox = area.bounding_rect().ok_or_else(|| invalid("geometry is empty"))?;
+        Ok(Self { name, kind, area, bbox })
+    }
+}
+
+// 첫 ring이 바깥, 나머지는 구멍
+fn to_polygon(rings: Vec<Vec<Vec<f64>>>) -> Result<Polygon<f64>, String> {
+    let mut rings = rings.into_iter().map(to_ring);
+    let exterior = rings.next().ok_or("polygon has no rings")??;
+    let interiors = rings.collect::<Result<Vec<_>, _>>()?;
+    Ok(Polygon::new(exterior, interiors))
+}
+
+fn to_ring(positions: Vec<Vec<f64>>) -> Result<LineString<f64>, String> {
+    // 닫힌 ring이라 처음과 마지막이 같은 점이다
+    if positions.len() < 4 {
+        return Err("ring needs at least 4 positions".to_string());
+    }
+    positions
+        .into_iter()
+        .map(|position| match position[..] {
+            [longitude, latitude, ..]
+                if (-180.0..=180.0).contains(&longitude) && (-90.0..=90.0).contains(&latitude) =>
+            {
+                Ok(Coord { x: longitude, y: latitude })
+            }
+            _ => Err(format!("invalid position {:?}", position)),
+        })
+        .collect()
+}
+
+/// 서비스 지역 밖에서 들어온 cab 위치를 어떻게 할지.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutsidePolicy {
+    /// 기록하지 않는다.
+    #[default]
+    Reject,
+    /// 기록하고 응답에 표시만 한다.
+    Flag,
+}
+
+/// `Geofences::admit`의 결과.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    Inside,
+    /// 밖이지만 `OutsidePolicy::Flag`라서 받는다.
+    Flagged,
+    Rejected,
+}
+
+/// 설정된 zone 전체. 비어 있으면 어디든 서비스 지역이고 특별한 zone도 없다.
+#[derive(Debug, Clone, Default)]
+pub struct Geofences {
+    zones: Vec<Zone>,
+    outside: OutsidePolicy,
+}
+
+impl Geofences {
+    /// zone은 파일에 적힌 순서대로 둔다.
+    pub fn from_geojson(json: &str) -> Result<Self, GeofenceError> {
+        let collection: FeatureCollection = serde_json::from_str(json)?;
+        let zones = collection
+            .features
+            .into_iter()
+            .enumerate()
+            .map(|(index, feature)| Zone::from_feature(index, feature))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { zones, outside: OutsidePolicy::default() })
+    }
+
+    pub fn with_outside_policy(mut self, outside: OutsidePolicy) -> Self {
+        self.outside = outside;
+        self
+    }
+
+    pub fn zones(&self) -> &[Zone] {
+        &self.zones
+    }
+
+    /// `location`이 들어 있는 zone.
+    pub fn zones_at<'a>(&'a self, location: &'a Location) -> impl Iterator<Item = &'a Zone> + 'a {
+        self.zones.iter().filter(move |zone| zone.contains(location))
+    }
+
+    /// service_area zone 중 하나에라도 들어 있는지. service_area가 없으면 항상 true.
+    pub fn in_service_area(&self, location: &Location) -> bool {
+        let mut areas = self.zones.iter().filter(|zone| zone.kind == ZoneKind::ServiceArea).peekable();
+        areas.peek().is_none() || areas.any(|zone| zone.contains(location))
+    }
+
+    /// cab 위치를 받을지.
+    pub fn admit(&self, location: &Location) -> Admission {
+        match (self.in_service_area(location), self.outside) {
+            (true, _) => Admission::Inside,
+            (false, OutsidePolicy::Flag) => Admission::Flagged,
+            (false, OutsidePolicy::Reject) => Admission::Rejected,
+        }
+    }
+
+    /// `origin`에서 찾는 손님에게 `cab`에 있는 cab을 보여줄지.
+    /// 서비스 지역 밖이나 no_pickup zone의 cab은 보이지 않고,
+    /// airport_queue zone 안팎이 다르면 보이지 않는다.
+    pub fn allows_pickup(&self, origin: &Location, cab: &Location) -> bool {
+        if !self.in_service_area(cab) || self.zones_at(cab).any(|zone| zone.kind == ZoneKind::NoPickup) {
+            return false;
+        }
+        self.queue_at(cab) == self.queue_at(origin)
+    }
+
+    // 겹쳐 있으면 먼저 적힌 것
+    fn queue_at(&self, location: &Location) -> Option<&str> {
+        self.zones
+            .iter()
+            .find(|zone| zone.kind == ZoneKind::AirportQueue && zone.contains(location))
+            .map(|zone| zone.name.as_str())
+    }
+}
@@ -0,0 +1,36 @@
+//! `grpc.health.v1.Health`는 tonic-health로 띄운다. Kubernetes probe나 `grpc_health_probe`가 부른다.
+//!
+//! 여기서는 어떤 이름에 상태를 매길지만 정한다. 빈 이름("")은 서버 전체다.
+//! 예전 이름(`Hello.Hello`, `Hello.Dispatch`)으로 probe하는 곳도 있어서 같이 넣는다.
+//! 서버는 cab registry가 준비되기 전까지 NOT_SERVING으로 답한다.
+
+use tonic::transport::NamedService;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+
+use crate::dispatch::DispatchService;
+use crate::hello::cab_service_server::CabServiceServer;
+use crate::hello::dispatch_service_server::DispatchServiceServer;
+use crate::legacy::Renamed;
+use crate::service::CabService;
+
+/// 서버 전체를 가리키는 이름.
+pub const SERVER: &str = "";
+
+/// 상태를 매기는 이름. 서버 전체, 지금 서비스, 예전 서비스.
+pub fn service_names() -> [&'static str; 5] {
+    [
+        SERVER,
+        CabServiceServer::<CabService>::NAME,
+        DispatchServiceServer::<DispatchService>::NAME,
+        <CabServiceServer<CabService> as Renamed>::OLD_NAME,
+        <DispatchServiceServer<DispatchService> as Renamed>::OLD_NAME,
+    ]
+}
+
+/// `service_names`의 상태를 한 번에 바꾼다. 처음 부르면 등록된다.
+pub async fn set_all(reporter: &mut HealthReporter, status: ServingStatus) {
+    for name in service_names() {
+        reporter.set_service_status(name, status).await;
+    }
+}
@@ -1,56 +1,619 @@
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct HelloRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct HelloResponse {
     #[prost(string, tag = "1")]
     pub message: ::prost::alloc::string::String,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CabLocationRequest {
     #[prost(string, tag = "1")]
     pub name: ::prost::alloc::string::String,
     #[prost(message, optional, tag = "2")]
     pub location: ::core::option::Option<Location>,
+    /// 비어 있거나 0이면 지금 값을 그대로 둔다
+    #[prost(enumeration = "CabType", tag = "3")]
+    pub cab_type: i32,
+    #[prost(uint32, tag = "4")]
+    pub capacity: u32,
+    /// 비어 있으면 지금 상태를 그대로 둔다. 처음 보는 cab이면 AVAILABLE
+    #[prost(enumeration = "CabStatus", tag = "5")]
+    pub status: i32,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CabLocationResponse {
     #[prost(bool, tag = "1")]
     pub accepted: bool,
+    /// accepted가 false일 때 이유. 단건 호출에서 잘못된 요청은 이 응답 대신 INVALID_ARGUMENT와
+    /// field violation으로 오고, 남의 cab이면 PERMISSION_DENIED로 온다. 그래서 여기에는
+    /// OUTSIDE_SERVICE_AREA만 온다. 다른 이유는 stream ack의 last_reject_reason에서 쓴다
+    #[prost(enumeration = "RejectReason", tag = "2")]
+    pub reject_reason: i32,
+    /// 받았지만 서비스 지역 밖이다
+    #[prost(bool, tag = "3")]
+    pub outside_service_area: bool,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CabLocationAck {
+    /// stream을 연 뒤로 받은 요청 수
+    #[prost(uint64, tag = "1")]
+    pub received: u64,
+    /// 지난 ack 이후 기록한 수
+    #[prost(uint32, tag = "2")]
+    pub accepted: u32,
+    /// 지난 ack 이후 잘못된 요청이라 버린 수
+    #[prost(uint32, tag = "3")]
+    pub rejected: u32,
+    /// 지난 ack 이후 너무 자주 와서 버린 수
+    #[prost(uint32, tag = "4")]
+    pub throttled: u32,
+    /// 0이 아니면 cab 하나당 이 간격보다 자주 보내지 말라는 뜻
+    #[prost(uint32, tag = "5")]
+    pub min_interval_ms: u32,
+    /// 지난 ack 이후 마지막으로 버린 요청의 이유
+    #[prost(enumeration = "RejectReason", tag = "6")]
+    pub last_reject_reason: i32,
+    /// accepted 중에 서비스 지역 밖이었던 것
+    #[prost(uint32, tag = "7")]
+    pub outside_service_area: u32,
+}
+/// 0이나 비어 있는 값은 서버 기본값을 쓴다.
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetCabRequest {
     #[prost(message, optional, tag = "1")]
     pub location: ::core::option::Option<Location>,
+    #[prost(double, tag = "2")]
+    pub radius_meters: f64,
+    #[prost(uint32, tag = "3")]
+    pub max_results: u32,
+    #[prost(message, optional, tag = "4")]
+    pub filter: ::core::option::Option<CabFilter>,
+    /// 이보다 오래전에 들어온 위치는 돌려주지 않는다.
+    #[prost(uint32, tag = "5")]
+    pub max_staleness_seconds: u32,
+    /// 비어 있으면 AVAILABLE만
+    #[prost(enumeration = "CabStatus", repeated, tag = "6")]
+    pub statuses: ::prost::alloc::vec::Vec<i32>,
+    /// 0이면 max_results개를 한 번에 준다. 주면 max_results를 더 크게 잡을 수 있다
+    #[prost(uint32, tag = "7")]
+    pub page_size: u32,
+    /// 앞 응답의 next_page_token. 나머지 필드는 첫 요청과 같아야 한다
+    #[prost(string, tag = "8")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CabFilter {
+    /// 비어 있으면 모든 종류
+    #[prost(enumeration = "CabType", repeated, tag = "1")]
+    pub cab_types: ::prost::alloc::vec::Vec<i32>,
+    #[prost(uint32, tag = "2")]
+    pub min_capacity: u32,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetCabResponse {
+    /// 가까운 순서. 순서는 첫 page를 찾을 때 정해지고, 뒤 page는 그 사이에 조건에서 벗어난 cab만 빠진다
+    #[prost(message, repeated, tag = "1")]
+    pub cabs: ::prost::alloc::vec::Vec<Cab>,
+    /// 비어 있으면 마지막 page. 몇 분 뒤에는 쓸 수 없다
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+/// 0이면 서버 기본 반경
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchCabsRequest {
+    #[prost(message, optional, tag = "1")]
+    pub location: ::core::option::Option<Location>,
+    #[prost(double, tag = "2")]
+    pub radius_meters: f64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchCabsResponse {
+    #[prost(oneof = "watch_cabs_response::Event", tags = "1, 2")]
+    pub event: ::core::option::Option<watch_cabs_response::Event>,
+}
+/// Nested message and enum types in `WatchCabsResponse`.
+pub mod watch_cabs_response {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Event {
+        #[prost(message, tag = "1")]
+        Snapshot(super::CabSnapshot),
+        #[prost(message, tag = "2")]
+        Update(super::CabUpdate),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CabSnapshot {
     #[prost(message, repeated, tag = "1")]
     pub cabs: ::prost::alloc::vec::Vec<Cab>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CabUpdate {
+    #[prost(enumeration = "CabUpdateKind", tag = "1")]
+    pub kind: i32,
+    #[prost(message, optional, tag = "2")]
+    pub cab: ::core::option::Option<Cab>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetCabTrackRequest {
+    #[prost(string, tag = "1")]
+    pub cab_name: ::prost::alloc::string::String,
+    /// unix epoch 밀리초. start_time_ms 이상 end_time_ms 미만. end_time_ms가 0이면 첫 요청 때까지
+    #[prost(int64, tag = "2")]
+    pub start_time_ms: i64,
+    #[prost(int64, tag = "3")]
+    pub end_time_ms: i64,
+    /// 비어 있으면 기록된 점을 다 준다
+    #[prost(message, optional, tag = "4")]
+    pub downsampling: ::core::option::Option<TrackDownsampling>,
+    /// 0이면 500, 많아도 5000
+    #[prost(uint32, tag = "5")]
+    pub page_size: u32,
+    /// 앞 응답의 next_page_token. 나머지 필드는 첫 요청과 같아야 한다
+    #[prost(string, tag = "6")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TrackDownsampling {
+    #[prost(oneof = "track_downsampling::Method", tags = "1, 2")]
+    pub method: ::core::option::Option<track_downsampling::Method>,
+}
+/// Nested message and enum types in `TrackDownsampling`.
+pub mod track_downsampling {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Method {
+        /// 앞에 남긴 점에서 이만큼 지난 뒤의 첫 점만 남긴다
+        #[prost(int64, tag = "1")]
+        MinIntervalMs(i64),
+        /// Douglas-Peucker. 남긴 선에서 이만큼(미터)도 벗어나지 않는 점은 버린다
+        #[prost(double, tag = "2")]
+        ToleranceMeters(f64),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CabTrackPoint {
+    #[prost(message, optional, tag = "1")]
+    pub location: ::core::option::Option<Location>,
+    /// 서버가 받은 시각. unix epoch 밀리초
+    #[prost(int64, tag = "2")]
+    pub recorded_at_ms: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetCabTrackResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub points: ::prost::alloc::vec::Vec<CabTrackPoint>,
+    /// 비어 있으면 마지막 page
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LookupZonesRequest {
+    #[prost(message, optional, tag = "1")]
+    pub location: ::core::option::Option<Location>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LookupZonesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub zones: ::prost::alloc::vec::Vec<Zone>,
+    /// 서비스 지역이 하나도 없으면 어디든 true
+    #[prost(bool, tag = "2")]
+    pub in_service_area: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Zone {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(enumeration = "ZoneKind", tag = "2")]
+    pub kind: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RideRequest {
+    #[prost(string, tag = "1")]
+    pub rider_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub pickup: ::core::option::Option<Location>,
+    #[prost(message, optional, tag = "3")]
+    pub dropoff: ::core::option::Option<Location>,
+    #[prost(message, optional, tag = "4")]
+    pub filter: ::core::option::Option<CabFilter>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CabTripRequest {
+    #[prost(string, tag = "1")]
+    pub cab_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetTripRequest {
+    #[prost(string, tag = "1")]
+    pub trip_id: ::prost::alloc::string::String,
+}
+/// 기사가 하는 요청. cab_name이 trip의 cab과 같아야 한다.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TripAction {
+    #[prost(string, tag = "1")]
+    pub trip_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub cab_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelTripRequest {
+    #[prost(string, tag = "1")]
+    pub trip_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "TripParty", tag = "2")]
+    pub cancelled_by: i32,
+    /// DRIVER가 취소할 때는 trip의 cab이어야 한다
+    #[prost(string, tag = "3")]
+    pub cab_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Trip {
+    #[prost(string, tag = "1")]
+    pub trip_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub rider_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub pickup: ::core::option::Option<Location>,
+    #[prost(message, optional, tag = "4")]
+    pub dropoff: ::core::option::Option<Location>,
+    #[prost(enumeration = "TripState", tag = "5")]
+    pub state: i32,
+    /// 제안을 받았거나 배차된 cab. 없으면 비어 있다
+    #[prost(string, tag = "6")]
+    pub cab_name: ::prost::alloc::string::String,
+    /// OFFERED일 때 제안이 끝나는 시각, unix epoch 밀리초
+    #[prost(int64, tag = "7")]
+    pub offer_expires_at_ms: i64,
+    /// 거절했거나 응답하지 않은 cab. 이 cab들에게는 다시 제안하지 않는다
+    #[prost(string, repeated, tag = "8")]
+    pub declined_cabs: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(enumeration = "TripParty", tag = "9")]
+    pub cancelled_by: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Cab {
     #[prost(string, tag = "1")]
     pub name: ::prost::alloc::string::String,
     #[prost(message, optional, tag = "2")]
     pub location: ::core::option::Option<Location>,
+    #[prost(enumeration = "CabType", tag = "3")]
+    pub cab_type: i32,
+    #[prost(uint32, tag = "4")]
+    pub capacity: u32,
+    #[prost(enumeration = "CabStatus", tag = "5")]
+    pub status: i32,
+    /// 아래는 GetCabs에서만 채운다. 요청한 위치까지의 직선 거리, 미터
+    #[prost(double, optional, tag = "6")]
+    pub distance_meters: ::core::option::Option<f64>,
+    /// 길을 따라간 거리, 미터. 서버에 도로 정보가 없으면 직선 거리로 어림한 값
+    #[prost(double, optional, tag = "7")]
+    pub route_distance_meters: ::core::option::Option<f64>,
+    /// 요청한 위치까지 오는 데 걸릴 시간
+    #[prost(uint32, optional, tag = "8")]
+    pub eta_seconds: ::core::option::Option<u32>,
 }
+/// 1, 2번은 float이라 몇 미터씩 틀어진다. 새 클라이언트는 3, 4번을 쓰고,
+/// 서버는 응답에 둘 다 채워서 예전 클라이언트도 읽을 수 있게 한다.
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Location {
+    #[deprecated]
     #[prost(float, tag = "1")]
-    pub latitude: f32,
+    pub legacy_latitude: f32,
+    #[deprecated]
     #[prost(float, tag = "2")]
-    pub longitude: f32,
+    pub legacy_longitude: f32,
+    #[prost(double, tag = "3")]
+    pub latitude: f64,
+    #[prost(double, tag = "4")]
+    pub longitude: f64,
+    /// 진북에서 시계 방향, 도 [0, 360)
+    #[prost(double, optional, tag = "5")]
+    pub heading_degrees: ::core::option::Option<f64>,
+    /// 초속, 미터
+    #[prost(double, optional, tag = "6")]
+    pub speed_mps: ::core::option::Option<f64>,
+    /// 수평 정확도(반경), 미터
+    #[prost(double, optional, tag = "7")]
+    pub accuracy_meters: ::core::option::Option<f64>,
+    /// 단말기에서 위치를 잡은 시각, unix epoch 밀리초
+    #[prost(int64, optional, tag = "8")]
+    pub timestamp_ms: ::core::option::Option<i64>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RejectReason {
+    Unspecified = 0,
+    /// name이 비었거나 너무 길다
+    InvalidName = 1,
+    MissingLocation = 2,
+    /// 위도/경도가 범위 밖이거나 NaN
+    InvalidLocation = 3,
+    /// token의 cab이 아닌 cab의 위치
+    NotOwner = 4,
+    /// 서비스 지역 밖. 서버가 밖의 위치를 받도록 설정돼 있으면 거절하지 않고 flag만 한다
+    OutsideServiceArea = 5,
 }
-#[doc = r" Generated client implementations."]
-pub mod hello_client {
+impl RejectReason {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            RejectReason::Unspecified => "REJECT_REASON_UNSPECIFIED",
+            RejectReason::InvalidName => "REJECT_REASON_INVALID_NAME",
+            RejectReason::MissingLocation => "REJECT_REASON_MISSING_LOCATION",
+            RejectReason::InvalidLocation => "REJECT_REASON_INVALID_LOCATION",
+            RejectReason::NotOwner => "REJECT_REASON_NOT_OWNER",
+            RejectReason::OutsideServiceArea => "REJECT_REASON_OUTSIDE_SERVICE_AREA",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "REJECT_REASON_UNSPECIFIED" => Some(Self::Unspecified),
+            "REJECT_REASON_INVALID_NAME" => Some(Self::InvalidName),
+            "REJECT_REASON_MISSING_LOCATION" => Some(Self::MissingLocation),
+            "REJECT_REASON_INVALID_LOCATION" => Some(Self::InvalidLocation),
+            "REJECT_REASON_NOT_OWNER" => Some(Self::NotOwner),
+            "REJECT_REASON_OUTSIDE_SERVICE_AREA" => Some(Self::OutsideServiceArea),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CabUpdateKind {
+    Unspecified = 0,
+    /// 반경 밖에서 안으로 들어왔다
+    Enter = 1,
+    /// 반경 안에서 움직였다
+    Move = 2,
+    /// 반경 밖으로 나갔다. cab에는 나간 위치가 들어 있다
+    Leave = 3,
+}
+impl CabUpdateKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CabUpdateKind::Unspecified => "CAB_UPDATE_KIND_UNSPECIFIED",
+            CabUpdateKind::Enter => "CAB_UPDATE_KIND_ENTER",
+            CabUpdateKind::Move => "CAB_UPDATE_KIND_MOVE",
+            CabUpdateKind::Leave => "CAB_UPDATE_KIND_LEAVE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CAB_UPDATE_KIND_UNSPECIFIED" => Some(Self::Unspecified),
+            "CAB_UPDATE_KIND_ENTER" => Some(Self::Enter),
+            "CAB_UPDATE_KIND_MOVE" => Some(Self::Move),
+            "CAB_UPDATE_KIND_LEAVE" => Some(Self::Leave),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ZoneKind {
+    Unspecified = 0,
+    /// 이 안에서만 영업한다. 여러 개면 합친 것
+    ServiceArea = 1,
+    /// 안에 있는 손님에게는 안에서 기다리는 cab만, 안에서 기다리는 cab은 안에 있는 손님에게만 보인다
+    AirportQueue = 2,
+    /// 안에 있는 cab은 GetCabs에 나오지 않는다
+    NoPickup = 3,
+}
+impl ZoneKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ZoneKind::Unspecified => "ZONE_KIND_UNSPECIFIED",
+            ZoneKind::ServiceArea => "ZONE_KIND_SERVICE_AREA",
+            ZoneKind::AirportQueue => "ZONE_KIND_AIRPORT_QUEUE",
+            ZoneKind::NoPickup => "ZONE_KIND_NO_PICKUP",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ZONE_KIND_UNSPECIFIED" => Some(Self::Unspecified),
+            "ZONE_KIND_SERVICE_AREA" => Some(Self::ServiceArea),
+            "ZONE_KIND_AIRPORT_QUEUE" => Some(Self::AirportQueue),
+            "ZONE_KIND_NO_PICKUP" => Some(Self::NoPickup),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TripState {
+    Unspecified = 0,
+    /// 다음에 제안할 cab을 찾는 중
+    Searching = 1,
+    /// cab_name에게 제안하고 응답을 기다리는 중
+    Offered = 2,
+    /// 기사가 받아서 손님에게 가는 중
+    Accepted = 3,
+    InProgress = 4,
+    Completed = 5,
+    Cancelled = 6,
+    NoCabAvailable = 7,
+}
+impl TripState {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            TripState::Unspecified => "TRIP_STATE_UNSPECIFIED",
+            TripState::Searching => "TRIP_STATE_SEARCHING",
+            TripState::Offered => "TRIP_STATE_OFFERED",
+            TripState::Accepted => "TRIP_STATE_ACCEPTED",
+            TripState::InProgress => "TRIP_STATE_IN_PROGRESS",
+            TripState::Completed => "TRIP_STATE_COMPLETED",
+            TripState::Cancelled => "TRIP_STATE_CANCELLED",
+            TripState::NoCabAvailable => "TRIP_STATE_NO_CAB_AVAILABLE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TRIP_STATE_UNSPECIFIED" => Some(Self::Unspecified),
+            "TRIP_STATE_SEARCHING" => Some(Self::Searching),
+            "TRIP_STATE_OFFERED" => Some(Self::Offered),
+            "TRIP_STATE_ACCEPTED" => Some(Self::Accepted),
+            "TRIP_STATE_IN_PROGRESS" => Some(Self::InProgress),
+            "TRIP_STATE_COMPLETED" => Some(Self::Completed),
+            "TRIP_STATE_CANCELLED" => Some(Self::Cancelled),
+            "TRIP_STATE_NO_CAB_AVAILABLE" => Some(Self::NoCabAvailable),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TripParty {
+    Unspecified = 0,
+    Rider = 1,
+    Driver = 2,
+}
+impl TripParty {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            TripParty::Unspecified => "TRIP_PARTY_UNSPECIFIED",
+            TripParty::Rider => "TRIP_PARTY_RIDER",
+            TripParty::Driver => "TRIP_PARTY_DRIVER",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TRIP_PARTY_UNSPECIFIED" => Some(Self::Unspecified),
+            "TRIP_PARTY_RIDER" => Some(Self::Rider),
+            "TRIP_PARTY_DRIVER" => Some(Self::Driver),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CabType {
+    Unspecified = 0,
+    Standard = 1,
+    Xl = 2,
+    Premium = 3,
+    Accessible = 4,
+}
+impl CabType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CabType::Unspecified => "CAB_TYPE_UNSPECIFIED",
+            CabType::Standard => "CAB_TYPE_STANDARD",
+            CabType::Xl => "CAB_TYPE_XL",
+            CabType::Premium => "CAB_TYPE_PREMIUM",
+            CabType::Accessible => "CAB_TYPE_ACCESSIBLE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CAB_TYPE_UNSPECIFIED" => Some(Self::Unspecified),
+            "CAB_TYPE_STANDARD" => Some(Self::Standard),
+            "CAB_TYPE_XL" => Some(Self::Xl),
+            "CAB_TYPE_PREMIUM" => Some(Self::Premium),
+            "CAB_TYPE_ACCESSIBLE" => Some(Self::Accessible),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CabStatus {
+    Unspecified = 0,
+    Available = 1,
+    /// 배차를 받아서 손님에게 가거나 태우고 있다
+    OnTrip = 2,
+    /// 기사가 쉬는 중이거나, 위치가 TTL 넘게 안 들어와서 지워졌다
+    Offline = 3,
+}
+impl CabStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CabStatus::Unspecified => "CAB_STATUS_UNSPECIFIED",
+            CabStatus::Available => "CAB_STATUS_AVAILABLE",
+            CabStatus::OnTrip => "CAB_STATUS_ON_TRIP",
+            CabStatus::Offline => "CAB_STATUS_OFFLINE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CAB_STATUS_UNSPECIFIED" => Some(Self::Unspecified),
+            "CAB_STATUS_AVAILABLE" => Some(Self::Available),
+            "CAB_STATUS_ON_TRIP" => Some(Self::OnTrip),
+            "CAB_STATUS_OFFLINE" => Some(Self::Offline),
+            _ => None,
+        }
+    }
+}
+/// Generated client implementations.
+pub mod cab_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
     use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
     #[derive(Debug, Clone)]
-    pub struct HelloClient<T> {
+    pub struct CabServiceClient<T> {
         inner: tonic::client::Grpc<T>,
     }
-    impl HelloClient<tonic::transport::Channel> {
-        #[doc = r" Attempt to create a new client by connecting to a given endpoint."]
+    impl CabServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
         pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
         where
             D: std::convert::TryInto<tonic::transport::Endpoint>,
@@ -60,98 +623,437 @@ pub mod hello_client {
             Ok(Self::new(conn))
         }
     }
-    impl<T> HelloClient<T>
+    impl<T> CabServiceClient<T>
     where
         T: tonic::client::GrpcService<tonic::body::BoxBody>,
-        T::ResponseBody: Body + Send + Sync + 'static,
         T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
         <T::ResponseBody as Body>::Error: Into<StdError> + Send,
     {
         pub fn new(inner: T) -> Self {
             let inner = tonic::client::Grpc::new(inner);
             Self { inner }
         }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
         pub fn with_interceptor<F>(
             inner: T,
             interceptor: F,
-        ) -> HelloClient<InterceptedService<T, F>>
+        ) -> CabServiceClient<InterceptedService<T, F>>
         where
             F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
             T: tonic::codegen::Service<
                 http::Request<tonic::body::BoxBody>,
                 Response = http::Response<
                     <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
                 >,
             >,
-            <T as tonic::codegen::Service<http::Request<tonic::body::BoxBody>>>::Error:
-                Into<StdError> + Send + Sync,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
         {
-            HelloClient::new(InterceptedService::new(inner, interceptor))
-        }
-        #[doc = r" Compress requests with `gzip`."]
-        #[doc = r""]
-        #[doc = r" This requires the server to support it otherwise it might respond with an"]
-        #[doc = r" error."]
-        pub fn send_gzip(mut self) -> Self {
-            self.inner = self.inner.send_gzip();
+            CabServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
             self
         }
-        #[doc = r" Enable decompressing responses with `gzip`."]
-        pub fn accept_gzip(mut self) -> Self {
-            self.inner = self.inner.accept_gzip();
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
             self
         }
         pub async fn hello_world(
             &mut self,
             request: impl tonic::IntoRequest<super::HelloRequest>,
         ) -> Result<tonic::Response<super::HelloResponse>, tonic::Status> {
-            self.inner.ready().await.map_err(|e| {
-                tonic::Status::new(
-                    tonic::Code::Unknown,
-                    format!("Service was not ready: {}", e.into()),
-                )
-            })?;
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/Hello.Hello/HelloWorld");
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.CabService/HelloWorld",
+            );
             self.inner.unary(request.into_request(), path, codec).await
         }
         pub async fn record_cab_location(
             &mut self,
             request: impl tonic::IntoRequest<super::CabLocationRequest>,
         ) -> Result<tonic::Response<super::CabLocationResponse>, tonic::Status> {
-            self.inner.ready().await.map_err(|e| {
-                tonic::Status::new(
-                    tonic::Code::Unknown,
-                    format!("Service was not ready: {}", e.into()),
-                )
-            })?;
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/Hello.Hello/record_cab_location");
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.CabService/RecordCabLocation",
+            );
             self.inner.unary(request.into_request(), path, codec).await
         }
         pub async fn get_cabs(
             &mut self,
             request: impl tonic::IntoRequest<super::GetCabRequest>,
         ) -> Result<tonic::Response<super::GetCabResponse>, tonic::Status> {
-            self.inner.ready().await.map_err(|e| {
-                tonic::Status::new(
-                    tonic::Code::Unknown,
-                    format!("Service was not ready: {}", e.into()),
-                )
-            })?;
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.CabService/GetCabs",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// 처음에 반경 안의 cab 전체를 보내고, 그 뒤로는 바뀐 것만 보낸다.
+        pub async fn watch_cabs(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WatchCabsRequest>,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::WatchCabsResponse>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.CabService/WatchCabs",
+            );
+            self.inner.server_streaming(request.into_request(), path, codec).await
+        }
+        /// 기사 앱이 한 번 열어두고 위치를 계속 보낸다. 서버는 몇 개씩 모아서 ack한다.
+        pub async fn stream_cab_locations(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<
+                Message = super::CabLocationRequest,
+            >,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::CabLocationAck>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.CabService/StreamCabLocations",
+            );
+            self.inner.streaming(request.into_streaming_request(), path, codec).await
+        }
+        /// 저장해 둔 위치 기록을 시간 순서로. 서버가 저장소 없이 떠 있으면 FAILED_PRECONDITION.
+        pub async fn get_cab_track(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetCabTrackRequest>,
+        ) -> Result<tonic::Response<super::GetCabTrackResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.CabService/GetCabTrack",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// location이 들어 있는 zone 전체.
+        pub async fn lookup_zones(
+            &mut self,
+            request: impl tonic::IntoRequest<super::LookupZonesRequest>,
+        ) -> Result<tonic::Response<super::LookupZonesResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.CabService/LookupZones",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+}
+/// Generated client implementations.
+pub mod dispatch_service_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// 배차. cab 위치는 CabService.RecordCabLocation으로 들어온 것을 쓴다.
+    #[derive(Debug, Clone)]
+    pub struct DispatchServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl DispatchServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> DispatchServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> DispatchServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
+        {
+            DispatchServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// 가장 가까운 cab에게 배차를 제안한다. 제안할 cab이 없으면 NO_CAB_AVAILABLE.
+        pub async fn request_ride(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RideRequest>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.DispatchService/RequestRide",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// 기사 앱이 자기에게 온 제안이나 진행 중인 trip을 확인한다.
+        pub async fn get_cab_trip(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CabTripRequest>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.DispatchService/GetCabTrip",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn get_trip(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetTripRequest>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.DispatchService/GetTrip",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// 제안을 받은 cab만 할 수 있다. 거절하거나 시간이 지나면 다음 cab에게 간다.
+        pub async fn accept_trip(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TripAction>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.DispatchService/AcceptTrip",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn decline_trip(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TripAction>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.DispatchService/DeclineTrip",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn start_trip(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TripAction>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.DispatchService/StartTrip",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn complete_trip(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TripAction>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.DispatchService/CompleteTrip",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// 손님이든 기사든 출발 전까지만 취소할 수 있다.
+        pub async fn cancel_trip(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelTripRequest>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/Hello.Hello/get_cabs");
+            let path = http::uri::PathAndQuery::from_static(
+                "/cabs.v1.DispatchService/CancelTrip",
+            );
             self.inner.unary(request.into_request(), path, codec).await
         }
     }
 }
-#[doc = r" Generated server implementations."]
-pub mod hello_server {
+/// Generated server implementations.
+pub mod cab_service_server {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
     use tonic::codegen::*;
-    #[doc = "Generated trait containing gRPC methods that should be implemented for use with HelloServer."]
+    /// Generated trait containing gRPC methods that should be implemented for use with CabServiceServer.
     #[async_trait]
-    pub trait Hello: Send + Sync + 'static {
+    pub trait CabService: Send + Sync + 'static {
         async fn hello_world(
             &self,
             request: tonic::Request<super::HelloRequest>,
@@ -164,17 +1066,51 @@ pub mod hello_server {
             &self,
             request: tonic::Request<super::GetCabRequest>,
         ) -> Result<tonic::Response<super::GetCabResponse>, tonic::Status>;
+        /// Server streaming response type for the WatchCabs method.
+        type WatchCabsStream: futures_core::Stream<
+                Item = Result<super::WatchCabsResponse, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// 처음에 반경 안의 cab 전체를 보내고, 그 뒤로는 바뀐 것만 보낸다.
+        async fn watch_cabs(
+            &self,
+            request: tonic::Request<super::WatchCabsRequest>,
+        ) -> Result<tonic::Response<Self::WatchCabsStream>, tonic::Status>;
+        /// Server streaming response type for the StreamCabLocations method.
+        type StreamCabLocationsStream: futures_core::Stream<
+                Item = Result<super::CabLocationAck, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// 기사 앱이 한 번 열어두고 위치를 계속 보낸다. 서버는 몇 개씩 모아서 ack한다.
+        async fn stream_cab_locations(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::CabLocationRequest>>,
+        ) -> Result<tonic::Response<Self::StreamCabLocationsStream>, tonic::Status>;
+        /// 저장해 둔 위치 기록을 시간 순서로. 서버가 저장소 없이 떠 있으면 FAILED_PRECONDITION.
+        async fn get_cab_track(
+            &self,
+            request: tonic::Request<super::GetCabTrackRequest>,
+        ) -> Result<tonic::Response<super::GetCabTrackResponse>, tonic::Status>;
+        /// location이 들어 있는 zone 전체.
+        async fn lookup_zones(
+            &self,
+            request: tonic::Request<super::LookupZonesRequest>,
+        ) -> Result<tonic::Response<super::LookupZonesResponse>, tonic::Status>;
     }
     #[derive(Debug)]
-    pub struct HelloServer<T: Hello> {
+    pub struct CabServiceServer<T: CabService> {
         inner: _Inner<T>,
-        accept_compression_encodings: (),
-        send_compression_encodings: (),
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
     }
     struct _Inner<T>(Arc<T>);
-    impl<T: Hello> HelloServer<T> {
+    impl<T: CabService> CabServiceServer<T> {
         pub fn new(inner: T) -> Self {
-            let inner = Arc::new(inner);
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
             let inner = _Inner(inner);
             Self {
                 inner,
@@ -182,34 +1118,56 @@ pub mod hello_server {
                 send_compression_encodings: Default::default(),
             }
         }
-        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
         where
             F: tonic::service::Interceptor,
         {
             InterceptedService::new(Self::new(inner), interceptor)
         }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
     }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for HelloServer<T>
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for CabServiceServer<T>
     where
-        T: Hello,
-        B: Body + Send + Sync + 'static,
+        T: CabService,
+        B: Body + Send + 'static,
         B::Error: Into<StdError> + Send + 'static,
     {
         type Response = http::Response<tonic::body::BoxBody>;
-        type Error = Never;
+        type Error = std::convert::Infallible;
         type Future = BoxFuture<Self::Response, Self::Error>;
-        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
             Poll::Ready(Ok(()))
         }
         fn call(&mut self, req: http::Request<B>) -> Self::Future {
             let inner = self.inner.clone();
             match req.uri().path() {
-                "/Hello.Hello/HelloWorld" => {
+                "/cabs.v1.CabService/HelloWorld" => {
                     #[allow(non_camel_case_types)]
-                    struct HelloWorldSvc<T: Hello>(pub Arc<T>);
-                    impl<T: Hello> tonic::server::UnaryService<super::HelloRequest> for HelloWorldSvc<T> {
+                    struct HelloWorldSvc<T: CabService>(pub Arc<T>);
+                    impl<T: CabService> tonic::server::UnaryService<super::HelloRequest>
+                    for HelloWorldSvc<T> {
                         type Response = super::HelloResponse;
-                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::HelloRequest>,
@@ -226,29 +1184,36 @@ pub mod hello_server {
                         let inner = inner.0;
                         let method = HelloWorldSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
-                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
-                            accept_compression_encodings,
-                            send_compression_encodings,
-                        );
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
                         let res = grpc.unary(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
                 }
-                "/Hello.Hello/record_cab_location" => {
+                "/cabs.v1.CabService/RecordCabLocation" => {
                     #[allow(non_camel_case_types)]
-                    struct record_cab_locationSvc<T: Hello>(pub Arc<T>);
-                    impl<T: Hello> tonic::server::UnaryService<super::CabLocationRequest>
-                        for record_cab_locationSvc<T>
-                    {
+                    struct RecordCabLocationSvc<T: CabService>(pub Arc<T>);
+                    impl<
+                        T: CabService,
+                    > tonic::server::UnaryService<super::CabLocationRequest>
+                    for RecordCabLocationSvc<T> {
                         type Response = super::CabLocationResponse;
-                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::CabLocationRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).record_cab_location(request).await };
+                            let fut = async move {
+                                (*inner).record_cab_location(request).await
+                            };
                             Box::pin(fut)
                         }
                     }
@@ -257,23 +1222,28 @@ pub mod hello_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = record_cab_locationSvc(inner);
+                        let method = RecordCabLocationSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
-                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
-                            accept_compression_encodings,
-                            send_compression_encodings,
-                        );
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
                         let res = grpc.unary(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
                 }
-                "/Hello.Hello/get_cabs" => {
+                "/cabs.v1.CabService/GetCabs" => {
                     #[allow(non_camel_case_types)]
-                    struct get_cabsSvc<T: Hello>(pub Arc<T>);
-                    impl<T: Hello> tonic::server::UnaryService<super::GetCabRequest> for get_cabsSvc<T> {
+                    struct GetCabsSvc<T: CabService>(pub Arc<T>);
+                    impl<T: CabService> tonic::server::UnaryService<super::GetCabRequest>
+                    for GetCabsSvc<T> {
                         type Response = super::GetCabResponse;
-                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::GetCabRequest>,
@@ -288,29 +1258,651 @@ pub mod hello_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = get_cabsSvc(inner);
+                        let method = GetCabsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
-                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
-                            accept_compression_encodings,
-                            send_compression_encodings,
-                        );
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
                         let res = grpc.unary(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
                 }
-                _ => Box::pin(async move {
-                    Ok(http::Response::builder()
-                        .status(200)
-                        .header("grpc-status", "12")
-                        .header("content-type", "application/grpc")
-                        .body(empty_body())
-                        .unwrap())
-                }),
+                "/cabs.v1.CabService/WatchCabs" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchCabsSvc<T: CabService>(pub Arc<T>);
+                    impl<
+                        T: CabService,
+                    > tonic::server::ServerStreamingService<super::WatchCabsRequest>
+                    for WatchCabsSvc<T> {
+                        type Response = super::WatchCabsResponse;
+                        type ResponseStream = T::WatchCabsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WatchCabsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).watch_cabs(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = WatchCabsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/cabs.v1.CabService/StreamCabLocations" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamCabLocationsSvc<T: CabService>(pub Arc<T>);
+                    impl<
+                        T: CabService,
+                    > tonic::server::StreamingService<super::CabLocationRequest>
+                    for StreamCabLocationsSvc<T> {
+                        type Response = super::CabLocationAck;
+                        type ResponseStream = T::StreamCabLocationsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<
+                                tonic::Streaming<super::CabLocationRequest>,
+                            >,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).stream_cab_locations(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StreamCabLocationsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/cabs.v1.CabService/GetCabTrack" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetCabTrackSvc<T: CabService>(pub Arc<T>);
+                    impl<
+                        T: CabService,
+                    > tonic::server::UnaryService<super::GetCabTrackRequest>
+                    for GetCabTrackSvc<T> {
+                        type Response = super::GetCabTrackResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetCabTrackRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).get_cab_track(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetCabTrackSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/cabs.v1.CabService/LookupZones" => {
+                    #[allow(non_camel_case_types)]
+                    struct LookupZonesSvc<T: CabService>(pub Arc<T>);
+                    impl<
+                        T: CabService,
+                    > tonic::server::UnaryService<super::LookupZonesRequest>
+                    for LookupZonesSvc<T> {
+                        type Response = super::LookupZonesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::LookupZonesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).lookup_zones(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = LookupZonesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        Ok(
+                            http::Response::builder()
+                                .status(200)
+                                .header("grpc-status", "12")
+                                .header("content-type", "application/grpc")
+                                .body(empty_body())
+                                .unwrap(),
+                        )
+                    })
+                }
+            }
+        }
+    }
+    impl<T: CabService> Clone for CabServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+            }
+        }
+    }
+    impl<T: CabService> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+    impl<T: CabService> tonic::server::NamedService for CabServiceServer<T> {
+        const NAME: &'static str = "cabs.v1.CabService";
+    }
+}
+/// Generated server implementations.
+pub mod dispatch_service_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with DispatchServiceServer.
+    #[async_trait]
+    pub trait DispatchService: Send + Sync + 'static {
+        /// 가장 가까운 cab에게 배차를 제안한다. 제안할 cab이 없으면 NO_CAB_AVAILABLE.
+        async fn request_ride(
+            &self,
+            request: tonic::Request<super::RideRequest>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status>;
+        /// 기사 앱이 자기에게 온 제안이나 진행 중인 trip을 확인한다.
+        async fn get_cab_trip(
+            &self,
+            request: tonic::Request<super::CabTripRequest>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status>;
+        async fn get_trip(
+            &self,
+            request: tonic::Request<super::GetTripRequest>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status>;
+        /// 제안을 받은 cab만 할 수 있다. 거절하거나 시간이 지나면 다음 cab에게 간다.
+        async fn accept_trip(
+            &self,
+            request: tonic::Request<super::TripAction>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status>;
+        async fn decline_trip(
+            &self,
+            request: tonic::Request<super::TripAction>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status>;
+        async fn start_trip(
+            &self,
+            request: tonic::Request<super::TripAction>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status>;
+        async fn complete_trip(
+            &self,
+            request: tonic::Request<super::TripAction>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status>;
+        /// 손님이든 기사든 출발 전까지만 취소할 수 있다.
+        async fn cancel_trip(
+            &self,
+            request: tonic::Request<super::CancelTripRequest>,
+        ) -> Result<tonic::Response<super::Trip>, tonic::Status>;
+    }
+    /// 배차. cab 위치는 CabService.RecordCabLocation으로 들어온 것을 쓴다.
+    #[derive(Debug)]
+    pub struct DispatchServiceServer<T: DispatchService> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: DispatchService> DispatchServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            let inner = _Inner(inner);
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for DispatchServiceServer<T>
+    where
+        T: DispatchService,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/cabs.v1.DispatchService/RequestRide" => {
+                    #[allow(non_camel_case_types)]
+                    struct RequestRideSvc<T: DispatchService>(pub Arc<T>);
+                    impl<
+                        T: DispatchService,
+                    > tonic::server::UnaryService<super::RideRequest>
+                    for RequestRideSvc<T> {
+                        type Response = super::Trip;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RideRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).request_ride(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RequestRideSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/cabs.v1.DispatchService/GetCabTrip" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetCabTripSvc<T: DispatchService>(pub Arc<T>);
+                    impl<
+                        T: DispatchService,
+                    > tonic::server::UnaryService<super::CabTripRequest>
+                    for GetCabTripSvc<T> {
+                        type Response = super::Trip;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CabTripRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).get_cab_trip(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetCabTripSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/cabs.v1.DispatchService/GetTrip" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetTripSvc<T: DispatchService>(pub Arc<T>);
+                    impl<
+                        T: DispatchService,
+                    > tonic::server::UnaryService<super::GetTripRequest>
+                    for GetTripSvc<T> {
+                        type Response = super::Trip;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetTripRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_trip(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetTripSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/cabs.v1.DispatchService/AcceptTrip" => {
+                    #[allow(non_camel_case_types)]
+                    struct AcceptTripSvc<T: DispatchService>(pub Arc<T>);
+                    impl<
+                        T: DispatchService,
+                    > tonic::server::UnaryService<super::TripAction>
+                    for AcceptTripSvc<T> {
+                        type Response = super::Trip;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TripAction>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).accept_trip(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = AcceptTripSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/cabs.v1.DispatchService/DeclineTrip" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeclineTripSvc<T: DispatchService>(pub Arc<T>);
+                    impl<
+                        T: DispatchService,
+                    > tonic::server::UnaryService<super::TripAction>
+                    for DeclineTripSvc<T> {
+                        type Response = super::Trip;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TripAction>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).decline_trip(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeclineTripSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/cabs.v1.DispatchService/StartTrip" => {
+                    #[allow(non_camel_case_types)]
+                    struct StartTripSvc<T: DispatchService>(pub Arc<T>);
+                    impl<
+                        T: DispatchService,
+                    > tonic::server::UnaryService<super::TripAction>
+                    for StartTripSvc<T> {
+                        type Response = super::Trip;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TripAction>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).start_trip(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StartTripSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/cabs.v1.DispatchService/CompleteTrip" => {
+                    #[allow(non_camel_case_types)]
+                    struct CompleteTripSvc<T: DispatchService>(pub Arc<T>);
+                    impl<
+                        T: DispatchService,
+                    > tonic::server::UnaryService<super::TripAction>
+                    for CompleteTripSvc<T> {
+                        type Response = super::Trip;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TripAction>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).complete_trip(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CompleteTripSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/cabs.v1.DispatchService/CancelTrip" => {
+                    #[allow(non_camel_case_types)]
+                    struct CancelTripSvc<T: DispatchService>(pub Arc<T>);
+                    impl<
+                        T: DispatchService,
+                    > tonic::server::UnaryService<super::CancelTripRequest>
+                    for CancelTripSvc<T> {
+                        type Response = super::Trip;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelTripRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).cancel_trip(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CancelTripSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        Ok(
+                            http::Response::builder()
+                                .status(200)
+                                .header("grpc-status", "12")
+                                .header("content-type", "application/grpc")
+                                .body(empty_body())
+                                .unwrap(),
+                        )
+                    })
+                }
             }
         }
     }
-    impl<T: Hello> Clone for HelloServer<T> {
+    impl<T: DispatchService> Clone for DispatchServiceServer<T> {
         fn clone(&self) -> Self {
             let inner = self.inner.clone();
             Self {
@@ -320,7 +1912,7 @@ pub mod hello_server {
             }
         }
     }
-    impl<T: Hello> Clone for _Inner<T> {
+    impl<T: DispatchService> Clone for _Inner<T> {
         fn clone(&self) -> Self {
             Self(self.0.clone())
         }
@@ -330,7 +1922,7 @@ pub mod hello_server {
             write!(f, "{:?}", self.0)
         }
     }
-    impl<T: Hello> tonic::transport::NamedService for HelloServer<T> {
-        const NAME: &'static str = "Hello.Hello";
+    impl<T: DispatchService> tonic::server::NamedService for DispatchServiceServer<T> {
+        const NAME: &'static str = "cabs.v1.DispatchService";
     }
 }
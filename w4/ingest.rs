@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::{self, Instant, MissedTickBehavior};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::Status;
+
+use crate::geofence::{Admission, Geofences};
+use crate::hello::{CabLocationAck, CabLocationRequest, RejectReason};
+use crate::registry::{CabAttributes, CabRegistry};
+use crate::validate;
+
+// 이만큼 받았거나 이만큼 시간이 지나면 ack한다
+pub const ACK_BATCH_SIZE: u32 = 32;
+pub const ACK_INTERVAL: Duration = Duration::from_secs(1);
+// cab 하나가 이보다 자주 보내면 버리고 throttle hint를 준다
+pub const MIN_UPDATE_INTERVAL: Duration = Duration::from_millis(500);
+
+pub type IngestStream = ReceiverStream<Result<CabLocationAck, Status>>;
+
+/// stream 하나에서 받은 것을 세고, cab마다 마지막으로 기록한 시각을 들고 있는다.
+#[derive(Debug, Default)]
+pub struct Batch {
+    // 있으면 이 cab의 위치만 받는다
+    owner: Option<String>,
+    geofences: Arc<Geofences>,
+    received: u64,
+    pending: CabLocationAck,
+    last_recorded: HashMap<String, Instant>,
+}
+
+impl Batch {
+    pub fn new(owner: Option<String>, geofences: Arc<Geofences>) -> Self {
+        Self { owner, geofences, ..Self::default() }
+    }
+
+    /// 요청 하나를 기록하거나 버린다. 이제 ack할 때가 되면 true.
+    pub fn push(&mut self, registry: &CabRegistry, req: CabLocationRequest, now: Instant) -> bool {
+        self.received += 1;
+        match validate::cab_location_request(&req) {
+            Err(invalid) => self.reject(invalid.reason),
+            Ok(_) if self.owner.as_ref().is_some_and(|owner| *owner != req.name) => self.reject(RejectReason::NotOwner),
+            Ok(_) if self.too_soon(&req.name, now) => {
+                self.pending.throttled += 1;
+                self.pending.min_interval_ms = MIN_UPDATE_INTERVAL.as_millis() as u32;
+            }
+            Ok(location) => match self.geofences.admit(&location) {
+                Admission::Rejected => self.reject(RejectReason::OutsideServiceArea),
+                admission => {
+                    if admission == Admission::Flagged {
+                        self.pending.outside_service_area += 1;
+                    }
+                    registry.record(&req.name, location, CabAttributes::from_request(&req));
+                    self.last_recorded.insert(req.name, now);
+                    self.pending.accepted += 1;
+                }
+            },
+        }
+        self.len() >= ACK_BATCH_SIZE
+    }
+
+    /// 지난 ack 이후 받은 수.
+    pub fn len(&self) -> u32 {
+        self.pending.accepted + self.pending.rejected + self.pending.throttled
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 모아둔 것을 ack로 만들고 다시 센다.
+    pub fn take_ack(&mut self) -> CabLocationAck {
+        let mut ack = std::mem::take(&mut self.pending);
+        ack.received = self.received;
+        ack
+    }
+
+    fn reject(&mut self, reason: RejectReason) {
+        self.pending.rejected += 1;
+        self.pending.last_reject_reason = reason as i32;
+    }
+
+    fn too_soon(&self, name: &str, now: Instant) -> bool {
+        self.last_recorded
+            .get(name)
+            .is_some_and(|last| now.saturating_duration_since(*last) < MIN_UPDATE_INTERVAL)
+    }
+}
+
+/// 들어오는 위치를 기록하면서 `ACK_BATCH_SIZE`개나 `ACK_INTERVAL`마다 ack를 보내는 stream.
+/// 클라이언트가 보내기를 끝내면 남은 것을 ack하고 닫는다.
+pub fn ingest<S>(
+    registry: Arc<CabRegistry>,
+    geofences: Arc<Geofences>,
+    owner: Option<String>,
+    mut incoming: S,
+) -> IngestStream
+where
+    S: Stream<Item = Result<CabLocationRequest, Status>> + Unpin + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(1);
+
+    tokio::spawn(async move {
+        let mut batch = Batch::new(owner, geofences);
+        let mut ticker = time::interval_at(Instant::now() + ACK_INTERVAL, ACK_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            let flush = tokio::select! {
+                req = incoming.next() => match req {
+                    Some(Ok(req)) => batch.push(&registry, req, Instant::now()),
+                    Some(Err(status)) => {
+                        // 요청 stream이 깨졌으면 클라이언트도 없다
+                        println!("stream_cab_locations: {}", status);
+                        break;
+                    }
+                    None => {
+                        let _ = tx.send(Ok(batch.take_ack())).await;
+                        break;
+                    }
+                },
+                _ = ticker.tick() => !batch.is_empty(),
+                _ = tx.closed() => break,
+            };
+
+            if flush {
+                if tx.send(Ok(batch.take_ack())).await.is_err() {
+                    break;
+                }
+                ticker.reset();
+            }
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
@@ -0,0 +1,104 @@
+//! 예전 이름으로 오는 요청.
+//!
+//! 처음에는 package와 service가 모두 `Hello`였고 method 이름도 snake_case였다
+//! (`/Hello.Hello/record_cab_location`). 지금은 `cabs.v1.CabService`, `cabs.v1.DispatchService`에
+//! PascalCase method를 쓴다. 예전 클라이언트가 다 옮겨갈 때까지 `LegacyRoutes`로 감싼 서버를
+//! 같이 띄워 두면, 예전 path를 지금 path로 바꿔서 같은 서버로 넘긴다.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::codegen::http::{Request, Uri};
+use tonic::codegen::{Context, Poll, Service};
+use tonic::transport::NamedService;
+
+use crate::hello::cab_service_server::{CabService, CabServiceServer};
+use crate::hello::dispatch_service_server::{DispatchService, DispatchServiceServer};
+
+/// 이름이 바뀐 서비스.
+pub trait Renamed: NamedService {
+    /// 예전 package까지 붙인 서비스 이름.
+    const OLD_NAME: &'static str;
+    /// (예전 method, 지금 method). 이름이 그대로인 method는 없어도 된다.
+    const OLD_METHODS: &'static [(&'static str, &'static str)];
+}
+
+impl<T: CabService> Renamed for CabServiceServer<T> {
+    const OLD_NAME: &'static str = "Hello.Hello";
+    const OLD_METHODS: &'static [(&'static str, &'static str)] = &[
+        ("record_cab_location", "RecordCabLocation"),
+        ("get_cabs", "GetCabs"),
+        ("watch_cabs", "WatchCabs"),
+        ("stream_cab_locations", "StreamCabLocations"),
+    ];
+}
+
+impl<T: DispatchService> Renamed for DispatchServiceServer<T> {
+    const OLD_NAME: &'static str = "Hello.Dispatch";
+    const OLD_METHODS: &'static [(&'static str, &'static str)] = &[
+        ("request_ride", "RequestRide"),
+        ("get_cab_trip", "GetCabTrip"),
+        ("get_trip", "GetTrip"),
+        ("accept_trip", "AcceptTrip"),
+        ("decline_trip", "DeclineTrip"),
+        ("start_trip", "StartTrip"),
+        ("complete_trip", "CompleteTrip"),
+        ("cancel_trip", "CancelTrip"),
+    ];
+}
+
+/// `/{OLD_NAME}/{method}`을 `/{NAME}/{new method}`로 바꿔서 `inner`에 넘긴다.
+/// 모르는 method는 이름만 그대로 옮기므로 `inner`가 UNIMPLEMENTED로 답한다.
+#[derive(Debug, Clone)]
+pub struct LegacyRoutes<S> {
+    inner: S,
+    // 예전 이름으로 처음 들어왔을 때 한 번만 경고한다
+    warned: Arc<AtomicBool>,
+}
+
+impl<S: Renamed> LegacyRoutes<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner, warned: Arc::new(AtomicBool::new(false)) }
+    }
+}
+
+/// 예전 path를 지금 path로. 예전 서비스의 path가 아니면 `None`.
+pub fn rename_path<S: Renamed>(path: &str) -> Option<String> {
+    let method = path.strip_prefix('/')?.strip_prefix(S::OLD_NAME)?.strip_prefix('/')?;
+    let method = S::OLD_METHODS.iter().find(|(old, _)| *old == method).map_or(method, |(_, new)| new);
+    Some(format!("/{}/{}", S::NAME, method))
+}
+
+impl<S: Renamed> NamedService for LegacyRoutes<S> {
+    const NAME: &'static str = S::OLD_NAME;
+}
+
+impl<S, B> Service<Request<B>> for LegacyRoutes<S>
+where
+    S: Service<Request<B>> + Renamed,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        if let Some(path) = rename_path::<S>(req.uri().path()) {
+            if !self.warned.swap(true, Ordering::Relaxed) {
+                println!("WARNING: deprecated service {} called, use {}", S::OLD_NAME, S::NAME);
+            }
+            let mut parts = req.uri().clone().into_parts();
+            if let Ok(path) = path.parse::<PathAndQuery>() {
+                parts.path_and_query = Some(path);
+                if let Ok(uri) = Uri::from_parts(parts) {
+                    *req.uri_mut() = uri;
+                }
+            }
+        }
+        self.inner.call(req)
+    }
+}
@@ -0,0 +1,25 @@
+pub mod auth;
+pub mod cab_page;
+pub mod client;
+pub mod dispatch;
+pub mod distance;
+pub mod eta;
+pub mod geofence;
+pub mod health;
+pub mod hello;
+pub mod ingest;
+pub mod legacy;
+pub mod location;
+pub mod query;
+pub mod reflection;
+pub mod registry;
+pub mod road_graph;
+pub mod service;
+pub mod spatial;
+pub mod sqlite;
+pub mod store;
+pub mod track;
+pub mod trip;
+pub mod unknown_methods;
+pub mod validate;
+pub mod watch;
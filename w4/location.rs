@@ -0,0 +1,52 @@
+use geo::Point;
+
+use crate::hello::Location;
+
+// 예전 float 필드를 읽고 쓰는 곳은 여기뿐이다
+#[allow(deprecated)]
+impl Location {
+    /// 위도/경도만 있는 위치. 예전 필드도 같이 채운다.
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            legacy_latitude: latitude as f32,
+            legacy_longitude: longitude as f32,
+            latitude,
+            longitude,
+            ..Self::default()
+        }
+    }
+
+    /// 예전 클라이언트는 float 필드만 보낸다. double 필드가 비어 있으면 float 값을 옮기고,
+    /// 응답을 예전 클라이언트도 읽을 수 있게 float 필드를 다시 채운다.
+    pub fn normalized(&self) -> Self {
+        let mut location = self.clone();
+        let has_double = location.latitude != 0.0 || location.longitude != 0.0;
+        if !has_double {
+            location.latitude = f64::from(location.legacy_latitude);
+            location.longitude = f64::from(location.legacy_longitude);
+        }
+        location.legacy_latitude = location.latitude as f32;
+        location.legacy_longitude = location.longitude as f32;
+        location
+    }
+}
+
+/// x는 경도, y는 위도.
+impl From<&Location> for Point<f64> {
+    fn from(location: &Location) -> Self {
+        Point::new(location.longitude, location.latitude)
+    }
+}
+
+impl From<Location> for Point<f64> {
+    fn from(location: Location) -> Self {
+        Point::from(&location)
+    }
+}
+
+/// heading 같은 나머지 정보는 비어 있다.
+impl From<Point<f64>> for Location {
+    fn from(point: Point<f64>) -> Self {
+        Location::new(point.y(), point.x())
+    }
+}
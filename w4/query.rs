@@ -0,0 +1,90 @@
+use std::time::{Duration, SystemTime};
+
+use crate::cab_page::MAX_PAGED_RESULTS;
+use crate::hello::{CabStatus, GetCabRequest};
+use crate::registry::CabEntry;
+
+// 요청에 값이 없으면(0) 쓰는 기본값과 상한
+pub const DEFAULT_RADIUS_METERS: f64 = 5_000.0;
+pub const MAX_RADIUS_METERS: f64 = 50_000.0;
+pub const DEFAULT_MAX_RESULTS: usize = 10;
+pub const MAX_RESULTS_LIMIT: usize = 100;
+pub const DEFAULT_MAX_STALENESS: Duration = Duration::from_secs(300);
+
+/// 0이나 이상한 값이면 기본 반경, 너무 크면 상한으로 자른다.
+pub fn radius_or_default(radius_meters: f64) -> f64 {
+    if radius_meters.is_finite() && radius_meters > 0.0 {
+        radius_meters.min(MAX_RADIUS_METERS)
+    } else {
+        DEFAULT_RADIUS_METERS
+    }
+}
+
+/// 서버 기본값을 채운 `GetCabRequest` 검색 조건.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CabQuery {
+    pub radius_meters: f64,
+    pub max_results: usize,
+    // 비어 있으면 모든 종류
+    pub cab_types: Vec<i32>,
+    pub min_capacity: u32,
+    pub max_staleness: Duration,
+    // 비어 있으면 AVAILABLE만
+    pub statuses: Vec<i32>,
+}
+
+impl Default for CabQuery {
+    fn default() -> Self {
+        Self {
+            radius_meters: DEFAULT_RADIUS_METERS,
+            max_results: DEFAULT_MAX_RESULTS,
+            cab_types: Vec::new(),
+            min_capacity: 0,
+            max_staleness: DEFAULT_MAX_STALENESS,
+            statuses: vec![CabStatus::Available as i32],
+        }
+    }
+}
+
+impl CabQuery {
+    /// 예전 클라이언트는 새 필드를 안 보내므로 전부 0으로 들어온다. 그러면 기본값.
+    pub fn from_request(req: &GetCabRequest) -> Self {
+        let defaults = Self::default();
+
+        let radius_meters = radius_or_default(req.radius_meters);
+        // page로 나눠 받으면 한 응답이 커지지 않으니 더 많이 찾는다
+        let limit = if req.page_size == 0 { MAX_RESULTS_LIMIT } else { MAX_PAGED_RESULTS };
+        let max_results = match req.max_results {
+            0 => defaults.max_results,
+            n => (n as usize).min(limit),
+        };
+        let max_staleness = match req.max_staleness_seconds {
+            0 => defaults.max_staleness,
+            seconds => Duration::from_secs(u64::from(seconds)),
+        };
+        let (cab_types, min_capacity) = match &req.filter {
+            Some(filter) => (filter.cab_types.clone(), filter.min_capacity),
+            None => (defaults.cab_types, defaults.min_capacity),
+        };
+
+        let statuses = match req.statuses.len() {
+            0 => defaults.statuses,
+            _ => req.statuses.clone(),
+        };
+
+        Self { radius_meters, max_results, cab_types, min_capacity, max_staleness, statuses }
+    }
+
+    /// 거리 말고 나머지 조건(종류, 인원, 상태, 신선도)을 보는 것.
+    pub fn matches(&self, entry: &CabEntry, now: SystemTime) -> bool {
+        let type_ok = self.cab_types.is_empty() || self.cab_types.contains(&(entry.cab_type as i32));
+        // 시계가 거꾸로 간 경우는 방금 들어온 것으로 본다
+        let fresh = now
+            .duration_since(entry.updated_at)
+            .map_or(true, |age| age <= self.max_staleness);
+
+        let status_ok = self.statuses.contains(&(entry.status as i32));
+
+        type_ok && entry.capacity >= self.min_capacity && status_ok && fresh
+    }
+}
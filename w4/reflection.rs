@@ -0,0 +1,17 @@
+//! `grpc.reflection.v1alpha.ServerReflection`은 tonic-reflection으로 띄운다.
+//! grpcurl이 proto 파일 없이도 서비스를 볼 수 있다.
+//!
+//! 보여주는 것은 `descriptor.bin`(hello.rs를 만들 때 같이 나오는 FileDescriptorSet)과 health, reflection 자신이다.
+//! 예전 이름(`Hello.Hello`)은 proto에 없으므로 나오지 않는다.
+
+use tonic_reflection::server::Builder;
+
+/// hello.proto의 descriptor.
+pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("descriptor.bin");
+
+/// 보여줄 descriptor를 다 넣어 둔 builder. `build()`해서 서버에 건다.
+pub fn builder() -> Builder<'static> {
+    Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::proto::GRPC_HEALTH_V1_FILE_DESCRIPTOR_SET)
+}
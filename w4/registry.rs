@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::distance::distance_meters;
+use crate::hello::{Cab, CabLocationRequest, CabStatus, CabType, Location};
+use crate::query::CabQuery;
+use crate::spatial::SpatialIndex;
+
+/// cab 하나의 마지막 위치.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CabEntry {
+    pub location: Location,
+    pub cab_type: CabType,
+    pub capacity: u32,
+    pub status: CabStatus,
+    pub updated_at: SystemTime,
+}
+
+/// 위치와 같이 들어오는 cab 정보. 비어 있는 값은 지금 값을 그대로 둔다.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CabAttributes {
+    /// `Unspecified`면 지금 종류를 그대로 둔다.
+    pub cab_type: CabType,
+    /// 0이면 지금 인원을 그대로 둔다.
+    pub capacity: u32,
+    /// `Unspecified`면 지금 상태를 그대로 둔다.
+    pub status: CabStatus,
+}
+
+impl Default for CabAttributes {
+    fn default() -> Self {
+        Self { cab_type: CabType::Unspecified, capacity: 0, status: CabStatus::Unspecified }
+    }
+}
+
+impl CabAttributes {
+    /// 모르는 cab_type, status는 `Unspecified`로 본다.
+    pub fn from_request(req: &CabLocationRequest) -> Self {
+        Self {
+            cab_type: CabType::from_i32(req.cab_type).unwrap_or(CabType::Unspecified),
+            capacity: req.capacity,
+            status: CabStatus::from_i32(req.status).unwrap_or(CabStatus::Unspecified),
+        }
+    }
+}
+
+impl CabEntry {
+    pub fn to_cab(&self, name: &str) -> Cab {
+        Cab {
+            name: name.to_string(),
+            location: Some(self.location.clone()),
+            cab_type: self.cab_type as i32,
+            capacity: self.capacity,
+            status: self.status as i32,
+            ..Cab::default()
+        }
+    }
+}
+
+/// 위치나 상태가 바뀔 때마다 구독자에게 가는 알림.
+/// TTL이 지나 지워진 cab은 `CabStatus::Offline`으로 온다.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CabChange {
+    pub name: String,
+    pub entry: CabEntry,
+}
+
+// 느린 구독자는 이만큼 밀리면 Lagged를 받는다
+const CHANGE_BUFFER: usize = 1024;
+
+pub const DEFAULT_CAB_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default)]
+struct Inner {
+    cabs: HashMap<String, CabEntry>,
+    index: SpatialIndex,
+}
+
+/// `Cab.name`으로 찾는 cab 위치 저장소. 여러 요청이 동시에 읽고 쓴다.
+/// 가까운 cab 검색은 `SpatialIndex`로 한다.
+#[derive(Debug)]
+pub struct CabRegistry {
+    inner: RwLock<Inner>,
+    changes: broadcast::Sender<CabChange>,
+}
+
+impl Default for CabRegistry {
+    fn default() -> Self {
+        let (changes, _) = broadcast::channel(CHANGE_BUFFER);
+        Self { inner: RwLock::default(), changes }
+    }
+}
+
+impl CabRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 이후의 변경을 전부 받는다. 이미 들어 있는 cab은 오지 않는다.
+    pub fn subscribe(&self) -> broadcast::Receiver<CabChange> {
+        self.changes.subscribe()
+    }
+
+    /// 위치를 덮어쓰고 기록한 시각을 돌려준다.
+    pub fn record(&self, name: &str, location: Location, attributes: CabAttributes) -> SystemTime {
+        let updated_at = SystemTime::now();
+        let mut inner = self.inner.write().unwrap();
+        // 위치만 보내는 요청이 종류와 인원을 지우지 않게 한다
+        let previous = inner.cabs.get(name);
+        let cab_type = match attributes.cab_type {
+            CabType::Unspecified => previous.map_or(CabType::Unspecified, |entry| entry.cab_type),
+            cab_type => cab_type,
+        };
+        let capacity = match attributes.capacity {
+            0 => previous.map_or(0, |entry| entry.capacity),
+            capacity => capacity,
+        };
+        let status = match attributes.status {
+            CabStatus::Unspecified => previous.map_or(CabStatus::Available, |entry| entry.status),
+            status => status,
+        };
+        let entry = CabEntry { location, cab_type, capacity, status, updated_at };
+        inner.index.upsert(name, &entry.location);
+        inner.cabs.insert(name.to_string(), entry.clone());
+        self.notify(name, entry);
+        updated_at
+    }
+
+    /// 위치는 그대로 두고 상태만 바꾼다. 없는 cab이면 false.
+    pub fn set_status(&self, name: &str, status: CabStatus) -> bool {
+        let mut inner = self.inner.write().unwrap();
+        let entry = match inner.cabs.get_mut(name) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        entry.status = status;
+        let entry = entry.clone();
+        self.notify(name, entry);
+        true
+    }
+
+    /// `now - ttl`보다 전에 마지막으로 들어온 cab을 지우고 이름을 돌려준다.
+    pub fn expire(&self, ttl: Duration, now: SystemTime) -> Vec<String> {
+        let mut inner = self.inner.write().unwrap();
+        let expired: Vec<String> = inner
+            .cabs
+            .iter()
+            .filter(|(_, entry)| now.duration_since(entry.updated_at).is_ok_and(|age| age > ttl))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in &expired {
+            inner.index.remove(name);
+            if let Some(mut entry) = inner.cabs.remove(name) {
+                entry.status = CabStatus::Offline;
+                self.notify(name, entry);
+            }
+        }
+        expired
+    }
+
+    // 같은 cab의 알림 순서가 뒤바뀌지 않게 write lock을 쥔 채로 부른다. 구독자가 없으면 버린다
+    fn notify(&self, name: &str, entry: CabEntry) {
+        let _ = self.changes.send(CabChange { name: name.to_string(), entry });
+    }
+
+    /// 저장해 둔 cab을 다시 넣는다. 기록한 시각은 그대로 두고 알림은 보내지 않는다.
+    pub fn restore(&self, cabs: Vec<(String, CabEntry)>) {
+        let mut inner = self.inner.write().unwrap();
+        for (name, entry) in cabs {
+            inner.index.upsert(&name, &entry.location);
+            inner.cabs.insert(name, entry);
+        }
+    }
+
+    /// 지금 들어 있는 cab 전체. 순서는 정해져 있지 않다.
+    pub fn entries(&self) -> Vec<(String, CabEntry)> {
+        let inner = self.inner.read().unwrap();
+        inner.cabs.iter().map(|(name, entry)| (name.clone(), entry.clone())).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<CabEntry> {
+        self.inner.read().unwrap().cabs.get(name).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().cabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `origin`에서 가까운 순서로 최대 `limit`개. (cab, 미터)
+    pub fn nearest(&self, origin: &Location, limit: usize) -> Vec<(Cab, f64)> {
+        let inner = self.inner.read().unwrap();
+        let found = inner.index.nearest(origin, limit);
+        to_cabs(&inner, found)
+    }
+
+    /// `origin`에서 `radius_meters` 안에 있는 cab을 가까운 순서로. (cab, 미터)
+    pub fn within(&self, origin: &Location, radius_meters: f64) -> Vec<(Cab, f64)> {
+        let inner = self.inner.read().unwrap();
+        let found = inner.index.within(origin, radius_meters);
+        to_cabs(&inner, found)
+    }
+
+    /// 가까운 순서로 보면서 반경을 넘으면 멈추고, 조건에 맞는 것만 `max_results`개까지.
+    pub fn search(&self, origin: &Location, query: &CabQuery) -> Vec<(Cab, f64)> {
+        self.search_by(origin, query, |_, _| true)
+    }
+
+    /// `search`에 조건 하나를 더 건다. `accept`가 false인 cab은 `max_results`에 세지 않는다.
+    pub fn search_by<F>(&self, origin: &Location, query: &CabQuery, mut accept: F) -> Vec<(Cab, f64)>
+    where
+        F: FnMut(&str, &CabEntry) -> bool,
+    {
+        let now = SystemTime::now();
+        let inner = self.inner.read().unwrap();
+        let found = inner
+            .index
+            .nearest_iter(origin)
+            .take_while(|(_, distance)| *distance <= query.radius_meters)
+            .filter(|(name, _)| {
+                inner
+                    .cabs
+                    .get(*name)
+                    .is_some_and(|entry| query.matches(entry, now) && accept(name, entry))
+            })
+            .take(query.max_results)
+            .collect();
+        to_cabs(&inner, found)
+    }
+
+    /// `names` 중 아직 `search_by`의 조건에 맞는 cab만 `names` 순서대로. 거리는 지금 위치로 잰다.
+    pub fn search_among<F>(
+        &self,
+        origin: &Location,
+        query: &CabQuery,
+        names: &[String],
+        mut accept: F,
+    ) -> Vec<(Cab, f64)>
+    where
+        F: FnMut(&str, &CabEntry) -> bool,
+    {
+        let now = SystemTime::now();
+        let inner = self.inner.read().unwrap();
+        names
+            .iter()
+            .filter_map(|name| {
+                let entry = inner.cabs.get(name)?;
+                let distance = distance_meters(origin, &entry.location);
+                let ok = distance <= query.radius_meters && query.matches(entry, now) && accept(name, entry);
+                ok.then(|| (entry.to_cab(name), distance))
+            })
+            .collect()
+    }
+}
+
+fn to_cabs(inner: &Inner, found: Vec<(&str, f64)>) -> Vec<(Cab, f64)> {
+    found
+        .into_iter()
+        .filter_map(|(name, distance)| {
+            let entry = inner.cabs.get(name)?;
+            Some((entry.to_cab(name), distance))
+        })
+        .collect()
+}
+
+/// `ttl`마다 한 번씩 위치가 오래된 cab을 지운다. 그래서 cab은 길어야 `2 * ttl` 동안 남아 있다.
+pub fn spawn_expiry(registry: Arc<CabRegistry>, ttl: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(ttl);
+        loop {
+            ticker.tick().await;
+            let expired = registry.expire(ttl, SystemTime::now());
+            if !expired.is_empty() {
+                println!("expired {} cab(s): {}", expired.len(), expired.join(", "));
+            }
+        }
+    })
+}
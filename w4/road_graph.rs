@@ -0,0 +1,352 @@
+//! OSM에서 뽑은 도로망으로 길을 찾는 `EtaEstimator`.
+//!
+//! Overpass API가 `[out:json]`으로 주는 파일을 읽는다. 예를 들면
+//! `[out:json];way["highway"](37.4,126.8,37.7,127.2);(._;>;);out;`
+//! 차가 다닐 수 있는 `highway` way만 쓰고, 속도는 `maxspeed`가 없으면 도로 종류로 정한다.
+//!
+//! cab과 손님은 가장 가까운 도로 위의 점에 붙인다. 붙일 점이 멀거나 길이 없으면 `HeuristicEta`로 어림한다.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::time::Duration;
+
+use rstar::primitives::GeomWithData;
+use rstar::RTree;
+use serde::Deserialize;
+
+use crate::distance::{chord_to_meters, distance_meters, to_unit_vector};
+use crate::eta::{Estimate, EtaEstimator, HeuristicEta};
+use crate::hello::Location;
+
+/// 도로에서 이보다 멀리 있는 위치는 도로망으로 찾지 않는다.
+pub const MAX_SNAP_METERS: f64 = 300.0;
+
+const MPH_TO_KPH: f64 = 1.609_344;
+
+#[derive(Debug)]
+pub enum RoadGraphError {
+    Json(serde_json::Error),
+    /// 차가 다닐 수 있는 도로가 하나도 없다.
+    NoRoads,
+}
+
+impl fmt::Display for RoadGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoadGraphError::Json(e) => write!(f, "invalid OSM JSON: {}", e),
+            RoadGraphError::NoRoads => f.write_str("no drivable roads in the extract"),
+        }
+    }
+}
+
+impl std::error::Error for RoadGraphError {}
+
+impl From<serde_json::Error> for RoadGraphError {
+    fn from(e: serde_json::Error) -> Self {
+        RoadGraphError::Json(e)
+    }
+}
+
+// Overpass JSON에서 쓰는 것만
+#[derive(Deserialize)]
+struct Extract {
+    elements: Vec<Element>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Element {
+    Node {
+        id: i64,
+        lat: f64,
+        lon: f64,
+    },
+    Way {
+        nodes: Vec<i64>,
+        #[serde(default)]
+        tags: HashMap<String, String>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+// 차가 못 다니는 도로면 None
+fn default_speed_kph(highway: &str) -> Option<f64> {
+    let kph = match highway {
+        "motorway" => 100.0,
+        "trunk" => 80.0,
+        "primary" => 60.0,
+        "motorway_link" | "secondary" => 50.0,
+        "trunk_link" | "primary_link" | "secondary_link" | "tertiary" => 40.0,
+        "tertiary_link" | "unclassified" | "residential" | "road" => 30.0,
+        "service" => 20.0,
+        "living_street" => 10.0,
+        _ => return None,
+    };
+    Some(kph)
+}
+
+// "50", "30 mph". "none"이나 "signals" 같은 것은 None
+fn parse_maxspeed(maxspeed: &str) -> Option<f64> {
+    let (number, mph) = match maxspeed.trim().strip_suffix("mph") {
+        Some(number) => (number.trim(), true),
+        None => (maxspeed.trim(), false),
+    };
+    let speed = number.parse::<f64>().ok().filter(|speed| speed.is_finite() && *speed > 0.0)?;
+    Some(if mph { speed * MPH_TO_KPH } else { speed })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Both,
+    Forward,
+    Backward,
+}
+
+fn direction(tags: &HashMap<String, String>) -> Direction {
+    match tags.get("oneway").map(String::as_str) {
+        Some("yes") | Some("true") | Some("1") => Direction::Forward,
+        Some("-1") | Some("reverse") => Direction::Backward,
+        Some(_) => Direction::Both,
+        // 고속도로와 회전교차로는 따로 적지 않아도 일방통행이다
+        None if tags.get("highway").map(String::as_str) == Some("motorway")
+            || tags.get("junction").map(String::as_str) == Some("roundabout") =>
+        {
+            Direction::Forward
+        }
+        None => Direction::Both,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Edge {
+    to: usize,
+    meters: f64,
+    seconds: f64,
+}
+
+/// 찾은 길의 길이와 걸리는 시간.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Route {
+    meters: f64,
+    seconds: f64,
+}
+
+// BinaryHeap은 큰 것부터 꺼내므로 거꾸로 비교한다
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Queued {
+    priority: f64,
+    node: usize,
+}
+
+impl Eq for Queued {}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.total_cmp(&self.priority).then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 교차점과 도로 조각으로 만든 방향 그래프.
+#[derive(Debug)]
+pub struct RoadGraph {
+    nodes: Vec<Location>,
+    outgoing: Vec<Vec<Edge>>,
+    incoming: Vec<Vec<Edge>>,
+    index: RTree<GeomWithData<[f64; 3], usize>>,
+    // A*의 heuristic이 실제보다 커지지 않게 가장 빠른 도로의 속도로 나눈다
+    max_speed_mps: f64,
+    fallback: HeuristicEta,
+}
+
+impl RoadGraph {
+    pub fn from_overpass_json(json: &str) -> Result<Self, RoadGraphError> {
+        let extract: Extract = serde_json::from_str(json)?;
+        let mut locations = HashMap::new();
+        let mut ways = Vec::new();
+        for element in extract.elements {
+            match element {
+                Element::Node { id, lat, lon } => {
+                    locations.insert(id, Location::new(lat, lon));
+                }
+                Element::Way { nodes, tags } => ways.push((nodes, tags)),
+                Element::Other => {}
+            }
+        }
+
+        let mut graph = Self {
+            nodes: Vec::new(),
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+            index: RTree::new(),
+            max_speed_mps: 0.0,
+            fallback: HeuristicEta::default(),
+        };
+        let mut ids = HashMap::new();
+        for (nodes, tags) in ways {
+            let default_kph = match tags.get("highway").and_then(|highway| default_speed_kph(highway)) {
+                Some(kph) => kph,
+                None => continue,
+            };
+            if tags.get("access").map(String::as_str) == Some("no") {
+                continue;
+            }
+            let kph = tags.get("maxspeed").and_then(|maxspeed| parse_maxspeed(maxspeed)).unwrap_or(default_kph);
+            let speed_mps = kph / 3.6;
+            graph.max_speed_mps = graph.max_speed_mps.max(speed_mps);
+            let direction = direction(&tags);
+
+            // 파일에 없는 node는 잘린 것이라 건너뛴다
+            let path: Vec<usize> = nodes
+                .iter()
+                .filter_map(|id| {
+                    let location = locations.get(id)?;
+                    Some(*ids.entry(*id).or_insert_with(|| graph.add_node(location.clone())))
+                })
+                .collect();
+            for pair in path.windows(2) {
+                let (a, b) = (pair[0], pair[1]);
+                let meters = distance_meters(&graph.nodes[a], &graph.nodes[b]);
+                let seconds = meters / speed_mps;
+                if direction != Direction::Backward {
+                    graph.add_edge(a, b, meters, seconds);
+                }
+                if direction != Direction::Forward {
+                    graph.add_edge(b, a, meters, seconds);
+                }
+            }
+        }
+        if graph.nodes.is_empty() {
+            return Err(RoadGraphError::NoRoads);
+        }
+
+        let points = graph.nodes.iter().enumerate().map(|(i, node)| GeomWithData::new(to_unit_vector(node), i));
+        graph.index = RTree::bulk_load(points.collect());
+        Ok(graph)
+    }
+
+    /// 도로망 밖에서 쓰는 어림값. 기본은 `HeuristicEta::default()`.
+    pub fn with_fallback(mut self, fallback: HeuristicEta) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn add_node(&mut self, location: Location) -> usize {
+        self.nodes.push(location);
+        self.outgoing.push(Vec::new());
+        self.incoming.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, meters: f64, seconds: f64) {
+        self.outgoing[from].push(Edge { to, meters, seconds });
+        self.incoming[to].push(Edge { to: from, meters, seconds });
+    }
+
+    // 가장 가까운 node와 거기까지의 거리
+    fn snap(&self, location: &Location) -> Option<(usize, f64)> {
+        let point = to_unit_vector(location);
+        let (nearest, distance_2) = self.index.nearest_neighbor_iter_with_distance_2(&point).next()?;
+        let meters = chord_to_meters(distance_2.sqrt());
+        (meters <= MAX_SNAP_METERS).then_some((nearest.data, meters))
+    }
+
+    // `start`에서 `targets`까지 `edges`를 따라간 가장 빠른 길. 다 찾으면 멈춘다.
+    // `goal`이 있으면 target 하나로 가는 A*, 없으면 Dijkstra
+    fn search(
+        &self,
+        start: usize,
+        edges: &[Vec<Edge>],
+        targets: &[usize],
+        goal: Option<&Location>,
+    ) -> HashMap<usize, Route> {
+        let heuristic =
+            |node: usize| goal.map_or(0.0, |goal| distance_meters(&self.nodes[node], goal) / self.max_speed_mps);
+        let mut best: HashMap<usize, Route> = HashMap::new();
+        let mut found = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        best.insert(start, Route { meters: 0.0, seconds: 0.0 });
+        queue.push(Queued { priority: heuristic(start), node: start });
+
+        while let Some(Queued { priority, node }) = queue.pop() {
+            let route = best[&node];
+            // 더 빠른 길로 이미 꺼낸 node
+            if priority > route.seconds + heuristic(node) || found.contains_key(&node) {
+                continue;
+            }
+            if targets.contains(&node) {
+                found.insert(node, route);
+                if found.len() == targets.len() {
+                    break;
+                }
+            }
+            for edge in &edges[node] {
+                let next = Route { meters: route.meters + edge.meters, seconds: route.seconds + edge.seconds };
+                if best.get(&edge.to).is_none_or(|known| next.seconds < known.seconds) {
+                    best.insert(edge.to, next);
+                    queue.push(Queued { priority: next.seconds + heuristic(edge.to), node: edge.to });
+                }
+            }
+        }
+        found
+    }
+
+    // 도로에 붙이기 전후의 거리는 속도를 모르는 cab처럼 간다
+    fn on_roads(&self, cab: &Location, pickup: &Location, route: Route, snapped_meters: f64) -> Estimate {
+        let duration = Duration::from_secs_f64(route.seconds)
+            + self.fallback.travel_time(snapped_meters)
+            + self.fallback.turn_penalty(cab, pickup);
+        Estimate { route_meters: route.meters + snapped_meters, duration }
+    }
+}
+
+impl EtaEstimator for RoadGraph {
+    /// A*.
+    fn estimate(&self, cab: &Location, pickup: &Location) -> Estimate {
+        let (from, to) = match (self.snap(cab), self.snap(pickup)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return self.fallback.estimate(cab, pickup),
+        };
+        let goal = &self.nodes[to.0];
+        match self.search(from.0, &self.outgoing, &[to.0], Some(goal)).get(&to.0) {
+            Some(route) => self.on_roads(cab, pickup, *route, from.1 + to.1),
+            None => self.fallback.estimate(cab, pickup),
+        }
+    }
+
+    /// `pickup`에서 거꾸로 Dijkstra 한 번.
+    fn estimate_many(&self, cabs: &[Location], pickup: &Location) -> Vec<Estimate> {
+        let to = match self.snap(pickup) {
+            Some(to) => to,
+            None => return cabs.iter().map(|cab| self.fallback.estimate(cab, pickup)).collect(),
+        };
+        let snapped: Vec<_> = cabs.iter().map(|cab| self.snap(cab)).collect();
+        let mut targets: Vec<usize> = snapped.iter().flatten().map(|(node, _)| *node).collect();
+        targets.sort_unstable();
+        targets.dedup();
+        let routes = self.search(to.0, &self.incoming, &targets, None);
+
+        cabs.iter()
+            .zip(snapped)
+            .map(|(cab, from)| match from.and_then(|(node, meters)| Some((*routes.get(&node)?, meters))) {
+                Some((route, meters)) => self.on_roads(cab, pickup, route, meters + to.1),
+                None => self.fallback.estimate(cab, pickup),
+            })
+            .collect()
+    }
+}
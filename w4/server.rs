@@ -0,0 +1,211 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tonic::codec::CompressionEncoding;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Endpoint, Server};
+use tonic_health::ServingStatus;
+
+use learning_grpc::auth::{AuthConfig, AuthInterceptor};
+use learning_grpc::cab_page::CabPager;
+use learning_grpc::dispatch::DispatchService;
+use learning_grpc::eta::{EtaEstimator, HeuristicEta};
+use learning_grpc::geofence::{Geofences, OutsidePolicy};
+use learning_grpc::health;
+use learning_grpc::hello::cab_service_server::CabServiceServer;
+use learning_grpc::hello::dispatch_service_server::DispatchServiceServer;
+use learning_grpc::legacy::LegacyRoutes;
+use learning_grpc::reflection;
+use learning_grpc::registry::{self, DEFAULT_CAB_TTL};
+use learning_grpc::road_graph::RoadGraph;
+use learning_grpc::service::CabService;
+use learning_grpc::sqlite::SqliteStore;
+use learning_grpc::store::{self, Store, DEFAULT_HISTORY_RETENTION};
+use learning_grpc::unknown_methods::{Fallback, UnknownMethodStats, UnknownMethods, UnknownServicesLayer};
+
+// JWT_HS256_SECRET(공유 secret)이나 JWT_RS256_PUBLIC_KEY(PEM 파일 경로) 중 하나.
+// 둘 다 없으면 인증 없이 띄운다
+fn auth_from_env() -> Result<AuthInterceptor, Box<dyn Error>> {
+    if let Ok(secret) = env::var("JWT_HS256_SECRET") {
+        return Ok(AuthInterceptor::new(AuthConfig::hs256(secret.as_bytes())));
+    }
+    if let Ok(path) = env::var("JWT_RS256_PUBLIC_KEY") {
+        let pem = fs::read(&path)?;
+        return Ok(AuthInterceptor::new(AuthConfig::rs256_pem(&pem)?));
+    }
+    println!("WARNING: no JWT key configured, all requests are allowed");
+    Ok(AuthInterceptor::allow_all())
+}
+
+// CAB_DB(SQLite 파일 경로)가 있으면 거기에 저장한다
+fn store_from_env() -> Result<Option<Arc<dyn Store>>, Box<dyn Error>> {
+    match env::var("CAB_DB") {
+        Ok(path) => Ok(Some(Arc::new(SqliteStore::open(&path)?))),
+        Err(_) => {
+            println!("WARNING: CAB_DB is not set, cab locations are kept in memory only");
+            Ok(None)
+        }
+    }
+}
+
+// GEOFENCES(GeoJSON 파일 경로)가 있으면 그 zone을 쓴다.
+// 서비스 지역 밖의 위치는 GEOFENCE_OUTSIDE가 reject(기본)면 거절하고 flag면 표시만 한다
+fn geofences_from_env() -> Result<Geofences, Box<dyn Error>> {
+    let path = match env::var("GEOFENCES") {
+        Ok(path) => path,
+        Err(_) => return Ok(Geofences::default()),
+    };
+    let outside = match env::var("GEOFENCE_OUTSIDE").as_deref() {
+        Ok("reject") | Err(_) => OutsidePolicy::Reject,
+        Ok("flag") => OutsidePolicy::Flag,
+        Ok(other) => return Err(format!("GEOFENCE_OUTSIDE must be reject or flag, not {:?}", other).into()),
+    };
+    let geofences = Geofences::from_geojson(&fs::read_to_string(&path)?)?.with_outside_policy(outside);
+    println!("Loaded {} zone(s) from {}", geofences.zones().len(), path);
+    Ok(geofences)
+}
+
+// ROAD_GRAPH(Overpass JSON 파일 경로)가 있으면 도로망으로 도착 시간을 계산한다
+fn eta_from_env() -> Result<Arc<dyn EtaEstimator>, Box<dyn Error>> {
+    match env::var("ROAD_GRAPH") {
+        Ok(path) => {
+            let graph = RoadGraph::from_overpass_json(&fs::read_to_string(&path)?)?;
+            println!("Loaded {} road node(s) from {}", graph.len(), path);
+            Ok(Arc::new(graph))
+        }
+        Err(_) => Ok(Arc::new(HeuristicEta::default())),
+    }
+}
+
+// 서버를 여러 대 띄우면 PAGE_TOKEN_SECRET을 같게 줘야 서로 GetCabs page token을 받는다
+fn pager_from_env() -> CabPager {
+    match env::var("PAGE_TOKEN_SECRET") {
+        Ok(secret) => CabPager::new(secret.as_bytes()),
+        Err(_) => CabPager::default(),
+    }
+}
+
+// 위치 기록은 CAB_HISTORY_RETENTION_HOURS만큼 남긴다
+fn history_retention_from_env() -> Result<Duration, Box<dyn Error>> {
+    match env::var("CAB_HISTORY_RETENTION_HOURS") {
+        Ok(hours) => match hours.parse::<u64>()?.checked_mul(60 * 60) {
+            Some(seconds) => Ok(Duration::from_secs(seconds)),
+            None => Err(format!("CAB_HISTORY_RETENTION_HOURS is too large: {}", hours).into()),
+        },
+        Err(_) => Ok(DEFAULT_HISTORY_RETENTION),
+    }
+}
+
+// UNKNOWN_METHOD_FALLBACK(예전 서버 주소)가 있으면 모르는 method를 그 서버에 넘긴다
+fn fallback_from_env() -> Result<Fallback, Box<dyn Error>> {
+    match env::var("UNKNOWN_METHOD_FALLBACK") {
+        Ok(url) => {
+            let channel = Endpoint::from_shared(url.clone())?.connect_lazy();
+            println!("Unknown methods are forwarded to {}", url);
+            Ok(Fallback::Forward(channel))
+        }
+        Err(_) => Ok(Fallback::Unimplemented),
+    }
+}
+
+// Ctrl-C나 SIGTERM
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut terminate = signal(SignalKind::terminate()).expect("SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let addr = env::args().nth(1).unwrap_or_else(|| "0.0.0.0:50051".to_string()).parse()?;
+    // 위치가 이만큼(초) 안 들어온 cab은 지운다
+    let cab_ttl = match env::args().nth(2) {
+        Some(seconds) => Duration::from_secs(seconds.parse()?),
+        None => DEFAULT_CAB_TTL,
+    };
+    if cab_ttl.is_zero() {
+        return Err("cab TTL must be at least 1 second".into());
+    }
+
+    let auth = auth_from_env()?;
+
+    // registry가 준비될 때까지는 NOT_SERVING
+    let (mut health, health_server) = tonic_health::server::health_reporter();
+    health::set_all(&mut health, ServingStatus::NotServing).await;
+    let reflection = reflection::builder().build()?;
+
+    let mut cab_service = CabService::default()
+        .with_geofences(Arc::new(geofences_from_env()?))
+        .with_eta(eta_from_env()?)
+        .with_pager(Arc::new(pager_from_env()));
+    let registry = cab_service.registry().clone();
+    let mut dispatch_service = DispatchService::new(registry.clone());
+    if let Some(store) = store_from_env()? {
+        let restored = store::restore(&registry, store.as_ref())?;
+        println!("Restored {} cab(s)", restored);
+        store::spawn_writer(registry.clone(), store.clone());
+        store::spawn_retention(store.clone(), history_retention_from_env()?);
+        cab_service = cab_service.with_store(store.clone());
+        dispatch_service = dispatch_service.with_store(store);
+    }
+    let expiry = registry::spawn_expiry(registry, cab_ttl);
+    health::set_all(&mut health, ServingStatus::Serving).await;
+    // 오래된 cab을 지우는 task가 죽으면 registry를 믿을 수 없다
+    tokio::spawn(async move {
+        let _ = expiry.await;
+        println!("ERROR: cab expiry stopped");
+        health::set_all(&mut health, ServingStatus::NotServing).await;
+    });
+
+    let gzip = CompressionEncoding::Gzip;
+    let cab_server = CabServiceServer::new(cab_service).accept_compressed(gzip).send_compressed(gzip);
+    // 두 서비스를 같이 센다
+    let unknown = UnknownMethodStats::default();
+    let fallback = fallback_from_env()?;
+    let cab_server = UnknownMethods::new(cab_server).with_fallback(fallback.clone()).with_stats(unknown.clone());
+    let dispatch_server = DispatchServiceServer::new(dispatch_service).accept_compressed(gzip).send_compressed(gzip);
+    let dispatch_server =
+        UnknownMethods::new(dispatch_server).with_fallback(fallback.clone()).with_stats(unknown.clone());
+    // with_interceptor는 서버를 새로 만들어서 gzip 설정이 빠지므로 직접 감싼다
+    let legacy_cab_server = InterceptedService::new(LegacyRoutes::new(cab_server.clone()), auth.clone());
+    let legacy_dispatch_server = InterceptedService::new(LegacyRoutes::new(dispatch_server.clone()), auth.clone());
+    let cab_server = InterceptedService::new(cab_server, auth.clone());
+    let dispatch_server = InterceptedService::new(dispatch_server, auth);
+    // 없는 서비스로 온 요청도 같이 센다
+    let unknown_services = UnknownServicesLayer::default()
+        .known(&cab_server)
+        .known(&dispatch_server)
+        .known(&legacy_cab_server)
+        .known(&legacy_dispatch_server)
+        .known(&health_server)
+        .known(&reflection)
+        .with_fallback(fallback)
+        .with_stats(unknown.clone());
+    println!("Listening on: {}", addr);
+    Server::builder()
+        .layer(unknown_services)
+        .add_service(cab_server)
+        .add_service(dispatch_server)
+        // 예전 Hello.Hello, Hello.Dispatch path. 클라이언트가 다 옮겨가면 뺀다
+        .add_service(legacy_cab_server)
+        .add_service(legacy_dispatch_server)
+        // probe와 grpcurl은 token 없이 부른다
+        .add_service(health_server)
+        .add_service(reflection)
+        .serve_with_shutdown(addr, shutdown_signal())
+        .await?;
+    println!("Unknown method calls: {}", unknown);
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::auth::{self, Role};
+use crate::cab_page::{self, CabPager, Continuation};
+use crate::eta::{self, EtaEstimator, HeuristicEta};
+use crate::geofence::{Admission, Geofences};
+use crate::hello::cab_service_server::CabService as CabServiceRpc;
+use crate::hello::{
+    CabLocationRequest, CabLocationResponse, GetCabRequest, GetCabResponse, GetCabTrackRequest, GetCabTrackResponse,
+    HelloRequest, HelloResponse, LookupZonesRequest, LookupZonesResponse, RejectReason, WatchCabsRequest, Zone,
+};
+use crate::ingest::{self, IngestStream};
+use crate::query::{self, CabQuery};
+use crate::registry::{CabAttributes, CabEntry, CabRegistry};
+use crate::store::Store;
+use crate::track::TrackQuery;
+use crate::validate;
+use crate::watch::{self, WatchStream};
+
+/// `cabs.v1.CabService` 구현. cab 위치는 `CabRegistry`에 들고 있고, 지난 위치는 `store`에서 읽는다.
+#[derive(Debug, Clone)]
+pub struct CabService {
+    registry: Arc<CabRegistry>,
+    store: Option<Arc<dyn Store>>,
+    geofences: Arc<Geofences>,
+    eta: Arc<dyn EtaEstimator>,
+    pager: Arc<CabPager>,
+}
+
+impl Default for CabService {
+    fn default() -> Self {
+        Self {
+            registry: Arc::default(),
+            store: None,
+            geofences: Arc::default(),
+            eta: Arc::new(HeuristicEta::default()),
+            pager: Arc::default(),
+        }
+    }
+}
+
+impl CabService {
+    pub fn new(registry: Arc<CabRegistry>) -> Self {
+        Self { registry, ..Self::default() }
+    }
+
+    /// 위치를 받을 곳과 `get_cabs`에서 보여줄 cab을 zone으로 정한다. 없으면 어디든 받는다.
+    pub fn with_geofences(mut self, geofences: Arc<Geofences>) -> Self {
+        self.geofences = geofences;
+        self
+    }
+
+    /// `get_cab_track`이 읽을 곳. 쓰는 것은 `store::spawn_writer`가 한다.
+    pub fn with_store(mut self, store: Arc<dyn Store>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// `get_cabs`가 도착 시간을 계산하는 방법. 기본은 `HeuristicEta`.
+    pub fn with_eta(mut self, eta: Arc<dyn EtaEstimator>) -> Self {
+        self.eta = eta;
+        self
+    }
+
+    /// `get_cabs`의 page token에 서명하는 것. 서버 여러 대가 서로의 token을 받으려면 같은 secret을 쓴다.
+    pub fn with_pager(mut self, pager: Arc<CabPager>) -> Self {
+        self.pager = pager;
+        self
+    }
+
+    pub fn registry(&self) -> &Arc<CabRegistry> {
+        &self.registry
+    }
+}
+
+#[tonic::async_trait]
+impl CabServiceRpc for CabService {
+    async fn hello_world(&self, _: Request<HelloRequest>) -> Result<Response<HelloResponse>, Status> {
+        let response = HelloResponse { message: "Hello, World!".to_string() };
+        Ok(Response::new(response))
+    }
+
+    // 잘못된 요청은 reject_reason 대신 INVALID_ARGUMENT로 돌려준다. field violation을 같이 줄 수 있어서
+    async fn record_cab_location(&self, req: Request<CabLocationRequest>) -> Result<Response<CabLocationResponse>, Status> {
+        auth::authorize_driver(&req, &req.get_ref().name)?;
+        let req = req.into_inner();
+        let location = validate::cab_location_request(&req)?;
+
+        let admission = self.geofences.admit(&location);
+        if admission == Admission::Rejected {
+            let reject_reason = RejectReason::OutsideServiceArea as i32;
+            let response = CabLocationResponse { accepted: false, reject_reason, outside_service_area: true };
+            return Ok(Response::new(response));
+        }
+        self.registry.record(&req.name, location, CabAttributes::from_request(&req));
+        let response = CabLocationResponse {
+            accepted: true,
+            reject_reason: RejectReason::Unspecified as i32,
+            outside_service_area: admission == Admission::Flagged,
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn get_cabs(&self, req: Request<GetCabRequest>) -> Result<Response<GetCabResponse>, Status> {
+        let req = req.into_inner();
+        let origin = validate::location("location", req.location.as_ref())?;
+
+        let now = SystemTime::now();
+        let continuation = self.pager.open(&req, now)?;
+
+        let query = CabQuery::from_request(&req);
+        let accept = |_: &str, entry: &CabEntry| self.geofences.allows_pickup(&origin, &entry.location);
+        let (mut found, searched_at) = match continuation {
+            None => (self.registry.search_by(&origin, &query, accept), now),
+            Some(Continuation { searched_at, remaining }) => {
+                (self.registry.search_among(&origin, &query, &remaining, accept), searched_at)
+            }
+        };
+        let rest = match cab_page::page_size(&req) {
+            Some(size) if found.len() > size => found.split_off(size),
+            _ => Vec::new(),
+        };
+        let remaining = rest.into_iter().map(|(cab, _)| cab.name).collect();
+        let next_page_token = self.pager.seal(&req, Continuation { searched_at, remaining });
+
+        let estimator = self.eta.clone();
+        let cabs = tokio::task::spawn_blocking(move || eta::fill_estimates(estimator.as_ref(), &origin, found))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(GetCabResponse { cabs, next_page_token }))
+    }
+
+    type WatchCabsStream = WatchStream;
+
+    async fn watch_cabs(&self, req: Request<WatchCabsRequest>) -> Result<Response<Self::WatchCabsStream>, Status> {
+        let req = req.into_inner();
+        let origin = validate::location("location", req.location.as_ref())?;
+
+        let radius_meters = query::radius_or_default(req.radius_meters);
+        Ok(Response::new(watch::watch(self.registry.clone(), origin, radius_meters)))
+    }
+
+    type StreamCabLocationsStream = IngestStream;
+
+    async fn stream_cab_locations(
+        &self,
+        req: Request<Streaming<CabLocationRequest>>,
+    ) -> Result<Response<Self::StreamCabLocationsStream>, Status> {
+        // 기사 token이면 그 cab의 위치만 받는다
+        let owner = match auth::claims(&req) {
+            Some(claims) if claims.role == Role::Driver && claims.cab.is_some() => claims.cab.clone(),
+            Some(_) => return Err(Status::permission_denied("only drivers with a cab may stream locations")),
+            None => None,
+        };
+        let (registry, geofences) = (self.registry.clone(), self.geofences.clone());
+        Ok(Response::new(ingest::ingest(registry, geofences, owner, req.into_inner())))
+    }
+
+    async fn get_cab_track(&self, req: Request<GetCabTrackRequest>) -> Result<Response<GetCabTrackResponse>, Status> {
+        auth::authorize_operator_or_driver(&req, &req.get_ref().cab_name)?;
+        let store = self.store.clone().ok_or_else(|| Status::failed_precondition("location history is not stored"))?;
+        let query = TrackQuery::from_request(req.get_ref(), SystemTime::now())?;
+
+        let page = tokio::task::spawn_blocking(move || query.read_page(store.as_ref()))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))??;
+        Ok(Response::new(page))
+    }
+
+    async fn lookup_zones(&self, req: Request<LookupZonesRequest>) -> Result<Response<LookupZonesResponse>, Status> {
+        let req = req.into_inner();
+        let location = validate::location("location", req.location.as_ref())?;
+
+        let zones = self
+            .geofences
+            .zones_at(&location)
+            .map(|zone| Zone { name: zone.name.clone(), kind: zone.kind as i32 })
+            .collect();
+        let in_service_area = self.geofences.in_service_area(&location);
+        Ok(Response::new(LookupZonesResponse { zones, in_service_area }))
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use rstar::{PointDistance, RTree, RTreeObject, AABB};
+
+use crate::distance::{chord_to_meters, meters_to_chord, to_unit_vector};
+use crate::hello::Location;
+
+#[derive(Debug, Clone, PartialEq)]
+struct IndexedCab {
+    name: String,
+    point: [f64; 3],
+}
+
+impl RTreeObject for IndexedCab {
+    type Envelope = AABB<[f64; 3]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_point(self.point)
+    }
+}
+
+impl PointDistance for IndexedCab {
+    fn distance_2(&self, point: &[f64; 3]) -> f64 {
+        self.point.iter().zip(point).map(|(a, b)| (a - b) * (a - b)).sum()
+    }
+}
+
+/// cab 위치로 가까운 cab을 찾는 R-tree.
+///
+/// 위경도를 단위 구 위의 3차원 점으로 바꿔서 넣기 때문에 극지방이나 날짜변경선 근처에서도
+/// 거리 순서가 정확하다. 위치 갱신은 지우고 다시 넣는 것이라 O(log n).
+#[derive(Debug, Default)]
+pub struct SpatialIndex {
+    tree: RTree<IndexedCab>,
+    points: HashMap<String, [f64; 3]>,
+}
+
+impl SpatialIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 여러 cab을 한 번에 넣을 때는 하나씩 넣는 것보다 훨씬 빠르다.
+    pub fn bulk_load<'a>(cabs: impl IntoIterator<Item = (&'a str, &'a Location)>) -> Self {
+        let points: HashMap<String, [f64; 3]> = cabs
+            .into_iter()
+            .map(|(name, location)| (name.to_string(), to_unit_vector(location)))
+            .collect();
+        let entries = points
+            .iter()
+            .map(|(name, point)| IndexedCab { name: name.clone(), point: *point })
+            .collect();
+        Self { tree: RTree::bulk_load(entries), points }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn upsert(&mut self, name: &str, location: &Location) {
+        let point = to_unit_vector(location);
+        if let Some(old) = self.points.insert(name.to_string(), point) {
+            self.tree.remove(&IndexedCab { name: name.to_string(), point: old });
+        }
+        self.tree.insert(IndexedCab { name: name.to_string(), point });
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.points.remove(name) {
+            Some(point) => self.tree.remove(&IndexedCab { name: name.to_string(), point }).is_some(),
+            None => false,
+        }
+    }
+
+    /// 가까운 순서로 최대 `k`개. (이름, 미터)
+    pub fn nearest(&self, origin: &Location, k: usize) -> Vec<(&str, f64)> {
+        self.nearest_iter(origin).take(k).collect()
+    }
+
+    /// 가까운 순서로 하나씩 꺼낸다. 조건을 걸러가며 필요한 만큼만 볼 때 쓴다.
+    pub fn nearest_iter(&self, origin: &Location) -> impl Iterator<Item = (&str, f64)> {
+        let origin = to_unit_vector(origin);
+        self.tree
+            .nearest_neighbor_iter_with_distance_2(&origin)
+            .map(|(cab, distance_2)| (cab.name.as_str(), chord_to_meters(distance_2.sqrt())))
+    }
+
+    /// 반경 안의 cab을 가까운 순서로. (이름, 미터)
+    pub fn within(&self, origin: &Location, radius_meters: f64) -> Vec<(&str, f64)> {
+        let origin = to_unit_vector(origin);
+        let chord = meters_to_chord(radius_meters);
+        let mut found: Vec<(&str, f64)> = self
+            .tree
+            .locate_within_distance(origin, chord * chord)
+            .map(|cab| (cab.name.as_str(), chord_to_meters(cab.distance_2(&origin).sqrt())))
+            .collect();
+        found.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+        found
+    }
+}
@@ -0,0 +1,206 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use prost::Message;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::hello::{CabStatus, CabType, Location, Trip};
+use crate::registry::{CabChange, CabEntry};
+use crate::store::{from_unix_millis, unix_millis, Store, StoreError, TrackPoint};
+
+// Location은 heading 같은 optional 필드가 있어서 proto 그대로 넣고, 범위로 찾을 값만 column으로 뺀다
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cabs (
+    name TEXT PRIMARY KEY,
+    cab_type INTEGER NOT NULL,
+    capacity INTEGER NOT NULL,
+    status INTEGER NOT NULL,
+    updated_at_ms INTEGER NOT NULL,
+    location BLOB NOT NULL
+);
+CREATE TABLE IF NOT EXISTS cab_locations (
+    name TEXT NOT NULL,
+    recorded_at_ms INTEGER NOT NULL,
+    latitude REAL NOT NULL,
+    longitude REAL NOT NULL,
+    location BLOB NOT NULL,
+    PRIMARY KEY (name, recorded_at_ms)
+) WITHOUT ROWID;
+CREATE INDEX IF NOT EXISTS cab_locations_recorded_at ON cab_locations (recorded_at_ms);
+CREATE TABLE IF NOT EXISTS trips (
+    trip_id TEXT PRIMARY KEY,
+    rider_id TEXT NOT NULL,
+    cab_name TEXT NOT NULL,
+    state INTEGER NOT NULL,
+    updated_at_ms INTEGER NOT NULL,
+    trip BLOB NOT NULL
+);
+";
+
+/// 파일 하나에 저장하는 `Store`. 연결 하나를 같이 쓴다.
+#[derive(Debug)]
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// 파일이 없으면 만든다.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StoreError> {
+        let conn = Connection::open(path)?;
+        // 쓰는 동안에도 읽을 수 있고, 매번 fsync하지 않는다
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
+        Self::with_connection(conn)
+    }
+
+    /// 닫으면 사라진다.
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, StoreError> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+}
+
+impl Store for SqliteStore {
+    fn save_cabs(&self, changes: &[CabChange]) -> Result<(), StoreError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        {
+            let mut save_cab = tx.prepare_cached(
+                "INSERT INTO cabs (name, cab_type, capacity, status, updated_at_ms, location)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (name) DO UPDATE SET cab_type = excluded.cab_type, capacity = excluded.capacity,
+                     status = excluded.status, updated_at_ms = excluded.updated_at_ms, location = excluded.location",
+            )?;
+            // 상태만 바뀐 것은 기록한 시각이 같아서 무시된다
+            let mut add_location = tx.prepare_cached(
+                "INSERT OR IGNORE INTO cab_locations (name, recorded_at_ms, latitude, longitude, location)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for CabChange { name, entry } in changes {
+                let updated_at_ms = unix_millis(entry.updated_at);
+                let location = entry.location.encode_to_vec();
+                save_cab.execute(params![
+                    name,
+                    entry.cab_type as i32,
+                    entry.capacity,
+                    entry.status as i32,
+                    updated_at_ms,
+                    location
+                ])?;
+                add_location.execute(params![
+                    name,
+                    updated_at_ms,
+                    entry.location.latitude,
+                    entry.location.longitude,
+                    location
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_cabs(&self) -> Result<Vec<(String, CabEntry)>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT name, cab_type, capacity, status, updated_at_ms, location FROM cabs WHERE status != ?1")?;
+        let rows = stmt.query_map(params![CabStatus::Offline as i32], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i32>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, i32>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, Vec<u8>>(5)?,
+            ))
+        })?;
+
+        let mut cabs = Vec::new();
+        for row in rows {
+            let (name, cab_type, capacity, status, updated_at_ms, location) = row?;
+            let entry = CabEntry {
+                location: Location::decode(location.as_slice())?,
+                cab_type: CabType::from_i32(cab_type).unwrap_or(CabType::Unspecified),
+                capacity,
+                status: CabStatus::from_i32(status).unwrap_or(CabStatus::Available),
+                updated_at: from_unix_millis(updated_at_ms),
+            };
+            cabs.push((name, entry));
+        }
+        Ok(cabs)
+    }
+
+    fn cab_history(
+        &self,
+        name: &str,
+        from: SystemTime,
+        to: SystemTime,
+        limit: usize,
+    ) -> Result<Vec<TrackPoint>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT recorded_at_ms, location FROM cab_locations
+             WHERE name = ?1 AND recorded_at_ms >= ?2 AND recorded_at_ms < ?3
+             ORDER BY recorded_at_ms LIMIT ?4",
+        )?;
+        let limit = limit.min(i64::MAX as usize) as i64;
+        let rows = stmt.query_map(params![name, unix_millis(from), unix_millis(to), limit], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+        })?;
+
+        let mut track = Vec::new();
+        for row in rows {
+            let (recorded_at_ms, location) = row?;
+            track.push(TrackPoint {
+                location: Location::decode(location.as_slice())?,
+                recorded_at: from_unix_millis(recorded_at_ms),
+            });
+        }
+        Ok(track)
+    }
+
+    fn save_trip(&self, trip: &Trip) -> Result<(), StoreError> {
+        let conn = self.conn.lock().unwrap();
+        conn.prepare_cached(
+            "INSERT INTO trips (trip_id, rider_id, cab_name, state, updated_at_ms, trip)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (trip_id) DO UPDATE SET rider_id = excluded.rider_id, cab_name = excluded.cab_name,
+                 state = excluded.state, updated_at_ms = excluded.updated_at_ms, trip = excluded.trip",
+        )?
+        .execute(params![
+            trip.trip_id,
+            trip.rider_id,
+            trip.cab_name,
+            trip.state,
+            unix_millis(SystemTime::now()),
+            trip.encode_to_vec()
+        ])?;
+        Ok(())
+    }
+
+    fn load_trip(&self, trip_id: &str) -> Result<Option<Trip>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let trip: Option<Vec<u8>> = conn
+            .prepare_cached("SELECT trip FROM trips WHERE trip_id = ?1")?
+            .query_row(params![trip_id], |row| row.get(0))
+            .optional()?;
+        Ok(trip.map(|trip| Trip::decode(trip.as_slice())).transpose()?)
+    }
+
+    fn prune(&self, before: SystemTime) -> Result<usize, StoreError> {
+        let before = unix_millis(before);
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let pruned = tx.execute("DELETE FROM cab_locations WHERE recorded_at_ms < ?1", params![before])?;
+        tx.execute(
+            "DELETE FROM cabs WHERE status = ?1 AND updated_at_ms < ?2",
+            params![CabStatus::Offline as i32, before],
+        )?;
+        tx.commit()?;
+        Ok(pruned)
+    }
+}
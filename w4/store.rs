@@ -0,0 +1,169 @@
+//! 서버를 다시 띄워도 남아 있어야 하는 것.
+//!
+//! `CabRegistry`는 메모리에 있고, 바뀐 것은 `spawn_writer`가 알림을 받아서 `Store`에 모아 쓴다.
+//! 시작할 때 `restore`로 마지막 위치를 registry에 다시 채운다.
+//! trip은 `DispatchService`가 바뀔 때마다 쓰고, 응답하기 전에 다 쓸 때까지 기다린다.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::task::JoinHandle;
+
+use crate::hello::{CabStatus, Location, Trip};
+use crate::registry::{CabChange, CabEntry, CabRegistry};
+
+// 한 transaction에 쓰는 최대 변경 수
+const WRITE_BATCH: usize = 256;
+
+const RETENTION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+pub const DEFAULT_HISTORY_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// 기록해 둔 위치 하나.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackPoint {
+    pub location: Location,
+    pub recorded_at: SystemTime,
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Sqlite(rusqlite::Error),
+    /// 저장해 둔 proto를 읽을 수 없다.
+    Decode(prost::DecodeError),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Sqlite(e) => write!(f, "sqlite: {}", e),
+            StoreError::Decode(e) => write!(f, "stored message is broken: {}", e),
+        }
+    }
+}
+
+impl Error for StoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StoreError::Sqlite(e) => Some(e),
+            StoreError::Decode(e) => Some(e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        StoreError::Sqlite(e)
+    }
+}
+
+impl From<prost::DecodeError> for StoreError {
+    fn from(e: prost::DecodeError) -> Self {
+        StoreError::Decode(e)
+    }
+}
+
+/// cab 상태, 위치 기록, trip을 저장하는 곳. 호출은 막힐 수 있으니 async 코드에서는 `spawn_blocking`으로 부른다.
+pub trait Store: Send + Sync + fmt::Debug {
+    /// cab마다 마지막 상태를 덮어쓰고 위치 기록에 더한다.
+    /// 상태만 바뀐 것처럼 `updated_at`이 이미 기록된 것과 같으면 위치 기록에는 더하지 않는다.
+    fn save_cabs(&self, changes: &[CabChange]) -> Result<(), StoreError>;
+
+    /// offline이 아닌 cab의 마지막 상태.
+    fn load_cabs(&self) -> Result<Vec<(String, CabEntry)>, StoreError>;
+
+    /// `from` 이상 `to` 미만에 기록된 위치를 시간 순서로 최대 `limit`개.
+    fn cab_history(
+        &self,
+        name: &str,
+        from: SystemTime,
+        to: SystemTime,
+        limit: usize,
+    ) -> Result<Vec<TrackPoint>, StoreError>;
+
+    /// trip의 지금 상태를 덮어쓴다.
+    fn save_trip(&self, trip: &Trip) -> Result<(), StoreError>;
+
+    fn load_trip(&self, trip_id: &str) -> Result<Option<Trip>, StoreError>;
+
+    /// `before`보다 전의 위치 기록과, 그 전에 offline이 된 cab을 지우고 지운 위치 기록 수를 돌려준다.
+    fn prune(&self, before: SystemTime) -> Result<usize, StoreError>;
+}
+
+pub fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as i64)
+}
+
+pub fn from_unix_millis(millis: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64)
+}
+
+/// 저장해 둔 cab을 registry에 다시 채우고 몇 대인지 돌려준다. `spawn_writer`보다 먼저 부른다.
+/// 진행 중이던 trip은 이어서 진행하지 않으므로 ON_TRIP이던 cab은 AVAILABLE로 돌린다.
+pub fn restore(registry: &CabRegistry, store: &dyn Store) -> Result<usize, StoreError> {
+    let mut cabs = store.load_cabs()?;
+    for (_, entry) in &mut cabs {
+        if entry.status == CabStatus::OnTrip {
+            entry.status = CabStatus::Available;
+        }
+    }
+    let restored = cabs.len();
+    registry.restore(cabs);
+    Ok(restored)
+}
+
+/// registry가 바뀔 때마다 `store`에 쓴다. 밀린 만큼 모아서 한 번에 쓴다.
+pub fn spawn_writer(registry: Arc<CabRegistry>, store: Arc<dyn Store>) -> JoinHandle<()> {
+    // task가 돌기 전에 들어온 것도 놓치지 않게 먼저 구독한다
+    let mut changes = registry.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let mut batch = match changes.recv().await {
+                Ok(change) => vec![change],
+                Err(RecvError::Lagged(missed)) => resync(&registry, missed),
+                Err(RecvError::Closed) => return,
+            };
+            while batch.len() < WRITE_BATCH {
+                match changes.try_recv() {
+                    Ok(change) => batch.push(change),
+                    Err(TryRecvError::Lagged(missed)) => batch.extend(resync(&registry, missed)),
+                    Err(_) => break,
+                }
+            }
+
+            let store = store.clone();
+            match tokio::task::spawn_blocking(move || store.save_cabs(&batch)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => println!("ERROR: failed to save cabs: {}", e),
+                Err(e) => println!("ERROR: cab writer panicked: {}", e),
+            }
+        }
+    })
+}
+
+// 알림을 놓치면 그 사이의 위치 기록은 잃지만 마지막 상태는 registry에서 다시 가져온다
+fn resync(registry: &CabRegistry, missed: u64) -> Vec<CabChange> {
+    println!("WARNING: cab writer missed {} change(s), saving current state", missed);
+    registry.entries().into_iter().map(|(name, entry)| CabChange { name, entry }).collect()
+}
+
+/// `keep`보다 오래된 위치 기록을 주기적으로 지운다.
+pub fn spawn_retention(store: Arc<dyn Store>, keep: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(RETENTION_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let before = SystemTime::now() - keep;
+            let store = store.clone();
+            match tokio::task::spawn_blocking(move || store.prune(before)).await {
+                Ok(Ok(0)) => {}
+                Ok(Ok(pruned)) => println!("pruned {} location(s) older than {:?}", pruned, keep),
+                Ok(Err(e)) => println!("ERROR: failed to prune history: {}", e),
+                Err(e) => println!("ERROR: history pruning panicked: {}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use tonic::service::Interceptor;
+use tonic::transport::{Channel, Server};
+use tonic::{Code, Request};
+
+use learning_grpc::auth::{AuthConfig, AuthInterceptor, BearerToken, Claims, Forbidden, Role, AUTHORIZATION};
+use learning_grpc::hello::cab_service_client::CabServiceClient;
+use learning_grpc::hello::cab_service_server::CabServiceServer;
+use learning_grpc::service::CabService;
+
+// 테스트에서만 쓰는 key
+const RS256_PRIVATE_KEY: &[u8] = include_bytes!("fixtures/rs256_private.pem");
+const RS256_PUBLIC_KEY: &[u8] = include_bytes!("fixtures/rs256_public.pem");
+const SECRET: &[u8] = b"test secret";
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn claims(role: Role, sub: &str, cab: Option<&str>) -> Claims {
+    Claims { sub: sub.to_string(), role, cab: cab.map(str::to_string), exp: now() + 3600 }
+}
+
+fn driver(cab: &str) -> Claims {
+    claims(Role::Driver, "driver-1", Some(cab))
+}
+
+fn hs256(claims: &Claims) -> String {
+    jsonwebtoken::encode(&Header::new(Algorithm::HS256), claims, &EncodingKey::from_secret(SECRET)).unwrap()
+}
+
+fn rs256(claims: &Claims) -> String {
+    let key = EncodingKey::from_rsa_pem(RS256_PRIVATE_KEY).unwrap();
+    jsonwebtoken::encode(&Header::new(Algorithm::RS256), claims, &key).unwrap()
+}
+
+fn with_header(value: &str) -> Request<()> {
+    let mut req = Request::new(());
+    req.metadata_mut().insert(AUTHORIZATION, value.parse().unwrap());
+    req
+}
+
+fn check(interceptor: &mut AuthInterceptor, token: &str) -> Result<Claims, Code> {
+    match interceptor.call(with_header(&format!("Bearer {}", token))) {
+        Ok(req) => Ok(req.extensions().get::<Claims>().cloned().unwrap()),
+        Err(status) => Err(status.code()),
+    }
+}
+
+#[test]
+fn hs256_and_rs256_tokens_are_accepted() {
+    let mut hs = AuthInterceptor::new(AuthConfig::hs256(SECRET));
+    assert_eq!(check(&mut hs, &hs256(&driver("cab-1"))), Ok(driver("cab-1")));
+
+    let mut rs = AuthInterceptor::new(AuthConfig::rs256_pem(RS256_PUBLIC_KEY).unwrap());
+    let rider = claims(Role::Rider, "rider-1", None);
+    assert_eq!(check(&mut rs, &rs256(&rider)), Ok(rider));
+
+    // 알고리즘이 다르면 받지 않는다
+    assert_eq!(check(&mut rs, &hs256(&driver("cab-1"))), Err(Code::Unauthenticated));
+    assert_eq!(check(&mut hs, &rs256(&driver("cab-1"))), Err(Code::Unauthenticated));
+    assert!(AuthConfig::rs256_pem(b"not a key").is_err());
+}
+
+#[test]
+fn bad_tokens_are_unauthenticated() {
+    let mut interceptor = AuthInterceptor::new(AuthConfig::hs256(SECRET));
+    // 기본 leeway(60초)보다 오래 지났다
+    let expired = Claims { exp: now() - 3600, ..driver("cab-1") };
+    assert_eq!(check(&mut interceptor, &hs256(&expired)), Err(Code::Unauthenticated));
+    assert_eq!(check(&mut interceptor, "garbage"), Err(Code::Unauthenticated));
+    let other_secret = jsonwebtoken::encode(
+        &Header::new(Algorithm::HS256),
+        &driver("cab-1"),
+        &EncodingKey::from_secret(b"someone else"),
+    )
+    .unwrap();
+    assert_eq!(check(&mut interceptor, &other_secret), Err(Code::Unauthenticated));
+
+    let missing = interceptor.call(Request::new(())).unwrap_err();
+    assert_eq!((missing.code(), missing.message()), (Code::Unauthenticated, "missing bearer token"));
+    let basic = interceptor.call(with_header("Basic dXNlcjpwYXNz")).unwrap_err();
+    assert_eq!(basic.code(), Code::Unauthenticated);
+
+    // key가 없으면 아무것도 보지 않는다
+    let req = AuthInterceptor::allow_all().call(Request::new(())).unwrap();
+    assert!(req.extensions().get::<Claims>().is_none());
+}
+
+#[test]
+fn roles_decide_what_is_allowed() {
+    let rider = claims(Role::Rider, "rider-1", None);
+    let operator = claims(Role::Operator, "ops", None);
+
+    assert!(driver("cab-1").check_driver_of("cab-1").is_ok());
+    assert!(driver("cab-1").check_driver_of("cab-2").is_err());
+    assert!(claims(Role::Driver, "driver-1", None).check_driver_of("cab-1").is_err());
+    assert!(rider.check_driver_of("cab-1").is_err());
+    assert!(operator.check_driver_of("cab-1").is_err());
+
+    assert!(rider.check_rider("rider-1").is_ok());
+    assert!(rider.check_rider("rider-2").is_err());
+    assert!(driver("cab-1").check_rider("driver-1").is_err());
+
+    assert!(operator.check_operator_or_driver_of("cab-9").is_ok());
+    assert!(driver("cab-1").check_operator_or_driver_of("cab-1").is_ok());
+    assert!(driver("cab-1").check_operator_or_driver_of("cab-9").is_err());
+    assert!(rider.check_operator_or_driver_of("cab-1").is_err());
+
+    let status = tonic::Status::from(Forbidden("no".to_string()));
+    assert_eq!(status.code(), Code::PermissionDenied);
+}
+
+async fn start_server() -> Channel {
+    let interceptor = AuthInterceptor::new(AuthConfig::hs256(SECRET));
+    let server = CabServiceServer::with_interceptor(CabService::default(), interceptor);
+    common::connect(common::serve(Server::builder().add_service(server)).await).await
+}
+
+async fn record(channel: &Channel, token: Option<&Claims>, cab: &str) -> Result<(), Code> {
+    let req = common::located(cab, common::origin());
+    let result = match token {
+        Some(claims) => {
+            let mut client =
+                CabServiceClient::with_interceptor(channel.clone(), BearerToken::new(&hs256(claims)).unwrap());
+            client.record_cab_location(req).await
+        }
+        None => CabServiceClient::new(channel.clone()).record_cab_location(req).await,
+    };
+    result.map(|_| ()).map_err(|status| status.code())
+}
+
+#[tokio::test]
+async fn drivers_may_only_write_their_own_cab() {
+    let channel = start_server().await;
+    assert_eq!(record(&channel, Some(&driver("cab-1")), "cab-1").await, Ok(()));
+    assert_eq!(record(&channel, Some(&driver("cab-1")), "cab-2").await, Err(Code::PermissionDenied));
+    let rider = claims(Role::Rider, "rider-1", None);
+    assert_eq!(record(&channel, Some(&rider), "cab-1").await, Err(Code::PermissionDenied));
+    assert_eq!(record(&channel, None, "cab-1").await, Err(Code::Unauthenticated));
+    let expired = Claims { exp: now() - 3600, ..driver("cab-1") };
+    assert_eq!(record(&channel, Some(&expired), "cab-1").await, Err(Code::Unauthenticated));
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use tonic::{Code, Request, Status};
+
+use learning_grpc::cab_page::{CabPager, Continuation, PageTokenError, PAGE_TOKEN_TTL};
+use learning_grpc::hello::cab_service_server::CabService as _;
+use learning_grpc::hello::{CabStatus, GetCabRequest, GetCabResponse, Location};
+use learning_grpc::service::CabService;
+
+use common::{located, origin};
+
+// cab-00이 가장 가깝고 번호가 클수록 북쪽으로 멀다
+fn cab_at(i: usize) -> Location {
+    Location::new(37.5 + (i + 1) as f64 * 1e-4, 127.0)
+}
+
+async fn record(service: &CabService, i: usize, location: Location) {
+    common::record(service, located(&format!("cab-{:02}", i), location)).await;
+}
+
+async fn service_with_cabs(count: usize) -> CabService {
+    let service = CabService::default();
+    for i in 0..count {
+        record(&service, i, cab_at(i)).await;
+    }
+    service
+}
+
+fn request(max_results: u32, page_size: u32, page_token: &str) -> GetCabRequest {
+    GetCabRequest {
+        location: Some(origin()),
+        max_results,
+        page_size,
+        page_token: page_token.to_string(),
+        ..GetCabRequest::default()
+    }
+}
+
+async fn get(service: &CabService, req: GetCabRequest) -> Result<GetCabResponse, Status> {
+    service.get_cabs(Request::new(req)).await.map(|response| response.into_inner())
+}
+
+fn names(response: &GetCabResponse) -> Vec<String> {
+    response.cabs.iter().map(|cab| cab.name.clone()).collect()
+}
+
+fn cab_names(range: std::ops::Range<usize>) -> Vec<String> {
+    range.map(|i| format!("cab-{:02}", i)).collect()
+}
+
+#[tokio::test]
+async fn pages_through_every_cab_once() {
+    let service = service_with_cabs(150).await;
+
+    // 예전처럼 page_size가 없으면 한 번에 다 준다. 상한은 100
+    let all = get(&service, request(150, 0, "")).await.unwrap();
+    assert_eq!(names(&all), cab_names(0..100));
+    assert!(all.next_page_token.is_empty());
+
+    // page로 받으면 100보다 많이 받을 수 있다
+    let mut seen = Vec::new();
+    let mut token = String::new();
+    let mut pages = 0;
+    loop {
+        let page = get(&service, request(150, 40, &token)).await.unwrap();
+        assert!(page.cabs.len() <= 40);
+        seen.extend(names(&page));
+        pages += 1;
+        if page.next_page_token.is_empty() {
+            break;
+        }
+        token = page.next_page_token;
+    }
+    assert_eq!(pages, 4);
+    assert_eq!(seen, cab_names(0..150));
+}
+
+#[tokio::test]
+async fn order_is_kept_while_cabs_move() {
+    let service = service_with_cabs(10).await;
+    let first = get(&service, request(10, 4, "")).await.unwrap();
+    assert_eq!(names(&first), cab_names(0..4));
+
+    // 두 번째 page의 cab이 가장 가까워지고, 첫 page의 cab이 가장 멀어진다
+    record(&service, 7, origin()).await;
+    record(&service, 1, cab_at(20)).await;
+    // 남은 cab 하나는 손님을 태웠다
+    service.registry().set_status("cab-05", CabStatus::OnTrip);
+
+    let second = get(&service, request(10, 4, &first.next_page_token)).await.unwrap();
+    assert_eq!(names(&second), ["cab-04", "cab-06", "cab-07", "cab-08"]);
+    // 거리는 지금 위치로 잰다
+    assert!(second.cabs[2].distance_meters.unwrap() < 1.0);
+
+    let third = get(&service, request(10, 4, &second.next_page_token)).await.unwrap();
+    assert_eq!(names(&third), ["cab-09"]);
+    assert!(third.next_page_token.is_empty());
+}
+
+#[tokio::test]
+async fn rejects_changed_tokens_and_requests() {
+    let service = service_with_cabs(10).await;
+    let token = get(&service, request(10, 4, "")).await.unwrap().next_page_token;
+
+    let mut tampered = token.clone().into_bytes();
+    let last = tampered.len() - 1;
+    tampered[last] = if tampered[last] == b'A' { b'B' } else { b'A' };
+    let tampered = String::from_utf8(tampered).unwrap();
+
+    let other_radius = GetCabRequest { radius_meters: 100.0, ..request(10, 4, &token) };
+    let other_server = CabService::new(service.registry().clone()).with_pager(Arc::new(CabPager::new(b"other")));
+    for (service, req) in [
+        (&service, request(10, 4, &tampered)),
+        (&service, request(10, 4, "not a token")),
+        (&service, other_radius),
+        (&service, request(9, 4, &token)),
+        (&other_server, request(10, 4, &token)),
+    ] {
+        assert_eq!(get(service, req).await.unwrap_err().code(), Code::InvalidArgument);
+    }
+
+    // page 크기는 바꿔도 된다
+    assert_eq!(names(&get(&service, request(10, 10, &token)).await.unwrap()), cab_names(4..10));
+}
+
+#[test]
+fn tokens_expire() {
+    let pager = CabPager::new(b"secret");
+    // token에는 밀리초까지 들어간다
+    let searched_at = SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+    let req = request(10, 4, "");
+    let token = pager.seal(&req, Continuation { searched_at, remaining: cab_names(4..10) });
+    let req = request(10, 4, &token);
+
+    let opened = pager.open(&req, searched_at + PAGE_TOKEN_TTL).unwrap().unwrap();
+    assert_eq!(opened.remaining, cab_names(4..10));
+    let later = searched_at + PAGE_TOKEN_TTL + Duration::from_secs(1);
+    assert_eq!(pager.open(&req, later), Err(PageTokenError::Expired));
+
+    // 남은 cab이 없으면 token도 없다
+    assert!(pager.seal(&req, Continuation { searched_at, remaining: Vec::new() }).is_empty());
+}
@@ -0,0 +1,221 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tokio::time::Instant;
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::Server;
+use tonic::{Code, Request, Status};
+
+use learning_grpc::client::{BreakerPolicy, CabClient, RetryPolicy};
+use learning_grpc::hello::cab_service_server::CabServiceServer;
+use learning_grpc::hello::{CabLocationRequest, GetCabRequest, HelloRequest};
+use learning_grpc::service::CabService;
+
+use common::{seoul, url};
+
+// 받은 요청 수를 세고, `fail`이면 UNAVAILABLE로 답한다
+#[derive(Clone, Default)]
+struct Counter {
+    calls: Arc<AtomicUsize>,
+    fail: bool,
+}
+
+impl Counter {
+    fn failing() -> Self {
+        Self { fail: true, ..Self::default() }
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+impl Interceptor for Counter {
+    fn call(&mut self, req: Request<()>) -> Result<Request<()>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.fail {
+            return Err(Status::unavailable("draining"));
+        }
+        Ok(req)
+    }
+}
+
+async fn start_server(counter: Counter) -> String {
+    let server = InterceptedService::new(CabServiceServer::new(CabService::default()), counter);
+    url(common::serve(Server::builder().add_service(server)).await)
+}
+
+async fn closed_port() -> String {
+    url(common::closed_port().await)
+}
+
+// 연결은 받지만 답하지 않는다
+async fn black_hole() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            held.push(socket);
+        }
+    });
+    url(addr)
+}
+
+fn fast_retry(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy { max_attempts, initial_backoff: Duration::from_millis(1), ..RetryPolicy::default() }
+}
+
+fn get_cabs() -> GetCabRequest {
+    GetCabRequest { location: Some(seoul()), ..GetCabRequest::default() }
+}
+
+#[tokio::test]
+async fn balances_across_endpoints() {
+    let (a, b) = (Counter::default(), Counter::default());
+    let client = CabClient::builder()
+        .endpoint(start_server(a.clone()).await)
+        .endpoint(start_server(b.clone()).await)
+        .build()
+        .unwrap();
+
+    for _ in 0..10 {
+        client.hello_world(HelloRequest::default()).await.unwrap();
+    }
+    assert_eq!((a.calls(), b.calls()), (5, 5));
+}
+
+#[tokio::test]
+async fn unreachable_endpoints_are_skipped_for_every_rpc() {
+    let live = Counter::default();
+    let client = CabClient::builder()
+        .endpoints(vec![closed_port().await, start_server(live.clone()).await])
+        .retry(fast_retry(2))
+        .build()
+        .unwrap();
+
+    // 기록은 다시 보내지 않는 RPC지만, 닿지 못한 것은 나가지 않았으니 다시 보낸다
+    for i in 0..4 {
+        let req =
+            CabLocationRequest { name: format!("cab-{}", i), location: get_cabs().location, ..Default::default() };
+        assert!(client.record_cab_location(req).await.unwrap().get_ref().accepted);
+    }
+    assert_eq!(client.get_cabs(get_cabs()).await.unwrap().get_ref().cabs.len(), 4);
+    assert_eq!(live.calls(), 5);
+}
+
+#[tokio::test]
+async fn only_idempotent_rpcs_are_retried_on_errors() {
+    let sick = Counter::failing();
+    let client = CabClient::builder()
+        .endpoint(start_server(sick.clone()).await)
+        .retry(fast_retry(3))
+        .circuit_breaker(BreakerPolicy { failure_threshold: 100, ..BreakerPolicy::default() })
+        .build()
+        .unwrap();
+
+    let err = client.get_cabs(get_cabs()).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable);
+    assert_eq!(sick.calls(), 3);
+
+    let req = CabLocationRequest { name: "cab-1".to_string(), location: get_cabs().location, ..Default::default() };
+    assert_eq!(client.record_cab_location(req).await.unwrap_err().code(), Code::Unavailable);
+    assert_eq!(sick.calls(), 4);
+}
+
+#[tokio::test]
+async fn circuit_breaker_takes_failing_endpoints_out() {
+    let (sick, healthy) = (Counter::failing(), Counter::default());
+    let breaker = BreakerPolicy { failure_threshold: 2, open_for: Duration::from_millis(300) };
+    let client = CabClient::builder()
+        .endpoints(vec![start_server(sick.clone()).await, start_server(healthy.clone()).await])
+        .retry(fast_retry(2))
+        .circuit_breaker(breaker)
+        .build()
+        .unwrap();
+
+    for _ in 0..10 {
+        client.hello_world(HelloRequest::default()).await.unwrap();
+    }
+    assert_eq!(sick.calls(), 2);
+    assert_eq!(healthy.calls(), 10);
+
+    // 시간이 지나면 하나만 보내 보고, 또 실패하면 다시 뺀다
+    tokio::time::sleep(breaker.open_for).await;
+    for _ in 0..10 {
+        client.hello_world(HelloRequest::default()).await.unwrap();
+    }
+    assert_eq!(sick.calls(), 3);
+
+    // 다 빠지면 바로 실패한다
+    let only_sick = CabClient::builder()
+        .endpoint(start_server(Counter::failing()).await)
+        .retry(fast_retry(1))
+        .circuit_breaker(BreakerPolicy { failure_threshold: 1, ..BreakerPolicy::default() })
+        .build()
+        .unwrap();
+    assert_eq!(only_sick.hello_world(HelloRequest::default()).await.unwrap_err().code(), Code::Unavailable);
+    let err = only_sick.hello_world(HelloRequest::default()).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable);
+    assert!(err.message().contains("failing"), "{}", err.message());
+}
+
+#[tokio::test]
+async fn deadline_covers_the_whole_call() {
+    let client = CabClient::builder()
+        .endpoint(black_hole().await)
+        .deadline(Duration::from_secs(60))
+        .retry(fast_retry(5))
+        .build()
+        .unwrap()
+        .with_deadline(Duration::from_millis(200));
+
+    let started = Instant::now();
+    let err = client.get_cabs(get_cabs()).await.unwrap_err();
+    assert_eq!(err.code(), Code::DeadlineExceeded, "{:?}", err);
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn deadlines_do_not_trip_the_breaker() {
+    let client = CabClient::builder()
+        .endpoint(black_hole().await)
+        .retry(fast_retry(1))
+        .circuit_breaker(BreakerPolicy { failure_threshold: 1, ..BreakerPolicy::default() })
+        .deadline(Duration::from_millis(100))
+        .build()
+        .unwrap();
+
+    // 서버가 빠졌으면 두 번째는 바로 UNAVAILABLE이다
+    for _ in 0..2 {
+        let err = client.get_cabs(get_cabs()).await.unwrap_err();
+        assert_eq!(err.code(), Code::DeadlineExceeded, "{:?}", err);
+    }
+}
+
+#[test]
+fn backoff_grows_with_jitter() {
+    let retry = RetryPolicy {
+        max_attempts: 10,
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(500),
+        multiplier: 2.0,
+    };
+    for (attempt, ceiling) in [(1, 100), (2, 200), (3, 400), (4, 500), (10, 500)] {
+        let backoffs: Vec<_> = (0..50).map(|_| retry.backoff(attempt)).collect();
+        assert!(backoffs.iter().all(|backoff| *backoff <= Duration::from_millis(ceiling)));
+        // 매번 같은 값이 아니다
+        assert!(backoffs.iter().any(|backoff| *backoff != backoffs[0]));
+    }
+}
+
+#[test]
+fn needs_valid_endpoints() {
+    assert!(CabClient::builder().build().is_err());
+    assert!(CabClient::builder().endpoint("not a uri").build().is_err());
+}
@@ -0,0 +1,93 @@
+// 여러 테스트 파일에서 같이 쓰는 도우미. 파일마다 쓰는 것만 골라 쓴다.
+#![allow(dead_code)]
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::client::Grpc;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::server::Router;
+use tonic::transport::Channel;
+use tonic::{Request, Status};
+
+use learning_grpc::hello::cab_service_server::CabService as _;
+use learning_grpc::hello::{CabLocationRequest, Location};
+use learning_grpc::registry::{CabAttributes, CabRegistry};
+use learning_grpc::service::CabService;
+
+pub fn origin() -> Location {
+    Location::new(37.5, 127.0)
+}
+
+// 북쪽으로 `meters`쯤 떨어진 곳
+pub fn north(meters: f64) -> Location {
+    Location::new(37.5 + meters / 111_195.0, 127.0)
+}
+
+pub fn seoul() -> Location {
+    Location::new(37.5665, 126.978)
+}
+
+pub fn located(name: &str, location: Location) -> CabLocationRequest {
+    CabLocationRequest { name: name.to_string(), location: Some(location), ..CabLocationRequest::default() }
+}
+
+pub async fn record(service: &CabService, req: CabLocationRequest) {
+    service.record_cab_location(Request::new(req)).await.unwrap();
+}
+
+pub fn record_in(registry: &CabRegistry, name: &str, location: Location) {
+    registry.record(name, location, CabAttributes::default());
+}
+
+// cab-1이 제일 가깝고 번호 순으로 멀어진다
+pub fn registry(cabs: usize) -> Arc<CabRegistry> {
+    let registry = Arc::new(CabRegistry::new());
+    for i in 1..=cabs {
+        record_in(&registry, &format!("cab-{}", i), north(100.0 * i as f64));
+    }
+    registry
+}
+
+// 빈 port. layer를 붙인 router처럼 `serve`로 못 띄우는 것은 직접 띄운다
+pub async fn incoming() -> (TcpListenerStream, SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    (TcpListenerStream::new(listener), addr)
+}
+
+// 빈 port에 띄운다
+pub async fn serve(router: Router) -> SocketAddr {
+    let (incoming, addr) = incoming().await;
+    tokio::spawn(router.serve_with_incoming(incoming));
+    addr
+}
+
+pub fn url(addr: SocketAddr) -> String {
+    format!("http://{}", addr)
+}
+
+pub async fn connect(addr: SocketAddr) -> Channel {
+    Channel::from_shared(url(addr)).unwrap().connect().await.unwrap()
+}
+
+// 아무도 받지 않는 주소
+pub async fn closed_port() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap()
+}
+
+// 생성된 client 없이 path를 직접 적어서 부른다
+pub async fn call_path<M, R>(channel: Channel, path: &'static str, message: M) -> Result<R, Status>
+where
+    M: prost::Message + 'static,
+    R: prost::Message + Default + 'static,
+{
+    let mut grpc = Grpc::new(channel);
+    grpc.ready().await.unwrap();
+    let response = grpc.unary(Request::new(message), PathAndQuery::from_static(path), ProstCodec::default()).await?;
+    Ok(response.into_inner())
+}
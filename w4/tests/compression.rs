@@ -0,0 +1,118 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tonic::codec::CompressionEncoding;
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::{Channel, Server};
+use tonic::{Code, Request, Status};
+
+use learning_grpc::hello::cab_service_client::CabServiceClient;
+use learning_grpc::hello::cab_service_server::CabServiceServer;
+use learning_grpc::hello::watch_cabs_response::Event;
+use learning_grpc::hello::{CabLocationRequest, GetCabRequest, WatchCabsRequest};
+use learning_grpc::service::CabService;
+
+use common::{located, seoul};
+
+// 서버가 받은 요청의 grpc-encoding을 기록한다
+#[derive(Clone, Default)]
+struct Encodings(Arc<Mutex<Vec<Option<String>>>>);
+
+impl Encodings {
+    fn seen(&self) -> Vec<Option<String>> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Interceptor for Encodings {
+    fn call(&mut self, req: Request<()>) -> Result<Request<()>, Status> {
+        let encoding = req.metadata().get("grpc-encoding").map(|v| v.to_str().unwrap().to_string());
+        self.0.lock().unwrap().push(encoding);
+        Ok(req)
+    }
+}
+
+async fn start_server(server: CabServiceServer<CabService>) -> (SocketAddr, Encodings) {
+    let encodings = Encodings::default();
+    let addr = common::serve(Server::builder().add_service(InterceptedService::new(server, encodings.clone()))).await;
+    (addr, encodings)
+}
+
+async fn connect(addr: SocketAddr) -> CabServiceClient<Channel> {
+    CabServiceClient::new(common::connect(addr).await)
+}
+
+fn record(name: &str) -> CabLocationRequest {
+    located(name, seoul())
+}
+
+#[tokio::test]
+async fn gzip_client_round_trips_against_gzip_server() {
+    let (addr, encodings) = start_server(
+        CabServiceServer::new(CabService::default())
+            .accept_compressed(CompressionEncoding::Gzip)
+            .send_compressed(CompressionEncoding::Gzip),
+    )
+    .await;
+    let mut client =
+        connect(addr).await.send_compressed(CompressionEncoding::Gzip).accept_compressed(CompressionEncoding::Gzip);
+
+    let response = client.record_cab_location(record("cab-1")).await.unwrap();
+    assert!(response.get_ref().accepted);
+    assert_eq!(response.metadata().get("grpc-encoding").unwrap(), "gzip");
+
+    let response =
+        client.get_cabs(GetCabRequest { location: Some(seoul()), ..GetCabRequest::default() }).await.unwrap();
+    assert_eq!(response.metadata().get("grpc-encoding").unwrap(), "gzip");
+    let names: Vec<_> = response.get_ref().cabs.iter().map(|cab| cab.name.as_str()).collect();
+    assert_eq!(names, ["cab-1"]);
+
+    assert_eq!(encodings.seen(), [Some("gzip".to_string()), Some("gzip".to_string())]);
+}
+
+#[tokio::test]
+async fn gzip_streaming_response() {
+    let (addr, _) =
+        start_server(CabServiceServer::new(CabService::default()).send_compressed(CompressionEncoding::Gzip)).await;
+    let mut client = connect(addr).await.accept_compressed(CompressionEncoding::Gzip);
+    client.record_cab_location(record("cab-1")).await.unwrap();
+
+    let request = WatchCabsRequest { location: Some(seoul()), radius_meters: 0.0 };
+    let response = client.watch_cabs(request).await.unwrap();
+    assert_eq!(response.metadata().get("grpc-encoding").unwrap(), "gzip");
+
+    let mut stream = response.into_inner();
+    match stream.message().await.unwrap().unwrap().event {
+        Some(Event::Snapshot(snapshot)) => assert_eq!(snapshot.cabs.len(), 1),
+        other => panic!("expected a snapshot, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn plain_client_still_works_against_gzip_server() {
+    let (addr, encodings) = start_server(
+        CabServiceServer::new(CabService::default())
+            .accept_compressed(CompressionEncoding::Gzip)
+            .send_compressed(CompressionEncoding::Gzip),
+    )
+    .await;
+    let mut client = connect(addr).await;
+
+    // 클라이언트가 gzip을 받겠다고 하지 않았으면 압축하지 않는다
+    let response = client.record_cab_location(record("cab-1")).await.unwrap();
+    assert!(response.get_ref().accepted);
+    assert!(response.metadata().get("grpc-encoding").is_none());
+    assert_eq!(encodings.seen(), [None]);
+}
+
+#[tokio::test]
+async fn gzip_request_is_rejected_unless_server_accepts_it() {
+    let (addr, _) = start_server(CabServiceServer::new(CabService::default())).await;
+    let mut client = connect(addr).await.send_compressed(CompressionEncoding::Gzip);
+
+    let status = client.record_cab_location(record("cab-1")).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
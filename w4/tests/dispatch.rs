@@ -0,0 +1,299 @@
+mod common;
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::mpsc as tokio_mpsc;
+use tonic::{Code, Request};
+
+use learning_grpc::dispatch::DispatchService;
+use learning_grpc::hello::dispatch_service_server::DispatchService as _;
+use learning_grpc::hello::{
+    CabStatus, CabTripRequest, CancelTripRequest, GetTripRequest, RideRequest, Trip, TripAction, TripParty, TripState,
+};
+use learning_grpc::registry::{CabChange, CabEntry, CabRegistry};
+use learning_grpc::sqlite::SqliteStore;
+use learning_grpc::store::{Store, StoreError, TrackPoint};
+use learning_grpc::trip::{self, TripEvent};
+
+use common::{origin, registry};
+
+async fn request_ride(dispatch: &DispatchService) -> Trip {
+    let ride = RideRequest { rider_id: "rider-1".to_string(), pickup: Some(origin()), ..RideRequest::default() };
+    dispatch.request_ride(Request::new(ride)).await.unwrap().into_inner()
+}
+
+fn action(trip: &Trip, cab_name: &str) -> Request<TripAction> {
+    Request::new(TripAction { trip_id: trip.trip_id.clone(), cab_name: cab_name.to_string() })
+}
+
+async fn get_trip(dispatch: &DispatchService, trip_id: &str) -> Result<Trip, tonic::Status> {
+    let req = GetTripRequest { trip_id: trip_id.to_string() };
+    dispatch.get_trip(Request::new(req)).await.map(|response| response.into_inner())
+}
+
+fn state(trip: &Trip) -> TripState {
+    TripState::from_i32(trip.state).unwrap()
+}
+
+fn cab_status(registry: &CabRegistry, name: &str) -> CabStatus {
+    registry.get(name).unwrap().status
+}
+
+#[test]
+fn transitions_follow_the_diagram() {
+    use TripEvent::*;
+    use TripState::*;
+
+    let allowed = [
+        (Searching, Offer, Offered),
+        (Searching, NoCab, NoCabAvailable),
+        (Offered, Accept, Accepted),
+        (Offered, Decline, Searching),
+        (Accepted, Start, InProgress),
+        (InProgress, Complete, Completed),
+        (Searching, Cancel(TripParty::Rider), Cancelled),
+        (Offered, Cancel(TripParty::Driver), Cancelled),
+        (Accepted, Cancel(TripParty::Rider), Cancelled),
+    ];
+    for (from, event, to) in allowed {
+        assert_eq!(trip::transition(from, event), Ok(to), "{:?} {:?}", from, event);
+    }
+
+    let refused = [
+        (Searching, Accept),
+        (Offered, Start),
+        (Accepted, Decline),
+        (InProgress, Cancel(TripParty::Rider)),
+        (Completed, Complete),
+        (Cancelled, Offer),
+        (Accepted, Cancel(TripParty::Unspecified)),
+    ];
+    for (from, event) in refused {
+        let e = trip::transition(from, event).unwrap_err();
+        assert_eq!((e.from, e.event), (from, event));
+    }
+
+    assert!(trip::is_finished(Completed) && trip::is_finished(Cancelled) && trip::is_finished(NoCabAvailable));
+    assert!(!trip::is_finished(Offered) && !trip::is_finished(InProgress));
+}
+
+#[tokio::test]
+async fn offer_accept_start_complete() {
+    let registry = registry(2);
+    let dispatch = DispatchService::new(registry.clone());
+
+    let trip = request_ride(&dispatch).await;
+    assert_eq!(state(&trip), TripState::Offered);
+    assert_eq!(trip.cab_name, "cab-1");
+    assert!(trip.offer_expires_at_ms > 0);
+    let offered = dispatch.get_cab_trip(Request::new(CabTripRequest { cab_name: "cab-1".to_string() })).await;
+    assert_eq!(offered.unwrap().into_inner().trip_id, trip.trip_id);
+
+    // 다른 cab은 건드릴 수 없고, 수락 전에는 출발할 수 없다
+    let status = dispatch.accept_trip(action(&trip, "cab-2")).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    let status = dispatch.start_trip(action(&trip, "cab-1")).await.unwrap_err();
+    assert_eq!(status.code(), Code::FailedPrecondition);
+
+    let accepted = dispatch.accept_trip(action(&trip, "cab-1")).await.unwrap().into_inner();
+    assert_eq!((state(&accepted), accepted.offer_expires_at_ms), (TripState::Accepted, 0));
+    assert_eq!(cab_status(&registry, "cab-1"), CabStatus::OnTrip);
+
+    // 배차된 cab에게는 다음 손님을 제안하지 않는다
+    assert_eq!(request_ride(&dispatch).await.cab_name, "cab-2");
+
+    dispatch.start_trip(action(&trip, "cab-1")).await.unwrap();
+    let completed = dispatch.complete_trip(action(&trip, "cab-1")).await.unwrap().into_inner();
+    assert_eq!(state(&completed), TripState::Completed);
+    assert_eq!(cab_status(&registry, "cab-1"), CabStatus::Available);
+    let status = dispatch.get_cab_trip(Request::new(CabTripRequest { cab_name: "cab-1".to_string() })).await;
+    assert_eq!(status.unwrap_err().code(), Code::NotFound);
+    assert_eq!(state(&get_trip(&dispatch, &trip.trip_id).await.unwrap()), TripState::Completed);
+}
+
+#[tokio::test]
+async fn decline_offers_the_next_cab_until_none_is_left() {
+    let registry = registry(2);
+    let dispatch = DispatchService::new(registry.clone());
+    let trip = request_ride(&dispatch).await;
+
+    let next = dispatch.decline_trip(action(&trip, "cab-1")).await.unwrap().into_inner();
+    assert_eq!((state(&next), next.cab_name.as_str()), (TripState::Offered, "cab-2"));
+    assert_eq!(next.declined_cabs, ["cab-1"]);
+
+    let last = dispatch.decline_trip(action(&trip, "cab-2")).await.unwrap().into_inner();
+    assert_eq!(state(&last), TripState::NoCabAvailable);
+    assert_eq!(last.declined_cabs, ["cab-1", "cab-2"]);
+    assert!(last.cab_name.is_empty());
+    assert_eq!(cab_status(&registry, "cab-1"), CabStatus::Available);
+}
+
+#[tokio::test(start_paused = true)]
+async fn unanswered_offers_time_out() {
+    let dispatch = DispatchService::new(registry(2)).with_offer_timeout(Duration::from_millis(100));
+    let trip = request_ride(&dispatch).await;
+    assert_eq!(trip.cab_name, "cab-1");
+
+    // 만료 task가 sleep을 걸고, 시간이 지나면 깨어나서 돌 기회를 준다
+    let advance = |millis| async move {
+        tokio::task::yield_now().await;
+        tokio::time::advance(Duration::from_millis(millis)).await;
+        tokio::task::yield_now().await;
+    };
+    advance(99).await;
+    assert_eq!(get_trip(&dispatch, &trip.trip_id).await.unwrap().cab_name, "cab-1");
+
+    advance(1).await;
+    let next = get_trip(&dispatch, &trip.trip_id).await.unwrap();
+    assert_eq!((state(&next), next.cab_name.as_str()), (TripState::Offered, "cab-2"));
+    // 시간이 지난 제안은 수락할 수 없다
+    let status = dispatch.accept_trip(action(&trip, "cab-1")).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+
+    advance(100).await;
+    let last = get_trip(&dispatch, &trip.trip_id).await.unwrap();
+    assert_eq!(state(&last), TripState::NoCabAvailable);
+}
+
+#[tokio::test]
+async fn rider_cancel_frees_the_cab() {
+    let registry = registry(1);
+    let dispatch = DispatchService::new(registry.clone());
+    let trip = request_ride(&dispatch).await;
+    dispatch.accept_trip(action(&trip, "cab-1")).await.unwrap();
+
+    let cancel = CancelTripRequest {
+        trip_id: trip.trip_id.clone(),
+        cancelled_by: TripParty::Rider as i32,
+        ..CancelTripRequest::default()
+    };
+    let cancelled = dispatch.cancel_trip(Request::new(cancel.clone())).await.unwrap().into_inner();
+    assert_eq!(state(&cancelled), TripState::Cancelled);
+    assert_eq!(cancelled.cancelled_by, TripParty::Rider as i32);
+    assert_eq!(cab_status(&registry, "cab-1"), CabStatus::Available);
+    assert_eq!(dispatch.cancel_trip(Request::new(cancel)).await.unwrap_err().code(), Code::FailedPrecondition);
+}
+
+#[tokio::test]
+async fn finished_trips_are_evicted_from_memory() {
+    // cab이 없어서 바로 NO_CAB_AVAILABLE로 끝난다
+    let dispatch = DispatchService::new(registry(0)).with_finished_trips_kept(2);
+    let trips = [request_ride(&dispatch).await, request_ride(&dispatch).await, request_ride(&dispatch).await];
+    assert!(trips.iter().all(|trip| state(trip) == TripState::NoCabAvailable));
+
+    assert_eq!(get_trip(&dispatch, &trips[0].trip_id).await.unwrap_err().code(), Code::NotFound);
+    assert!(get_trip(&dispatch, &trips[1].trip_id).await.is_ok());
+    assert!(get_trip(&dispatch, &trips[2].trip_id).await.is_ok());
+
+    // store가 있으면 거기서 읽는다
+    let store: Arc<dyn Store> = Arc::new(SqliteStore::open_in_memory().unwrap());
+    let dispatch = DispatchService::new(registry(0)).with_store(store).with_finished_trips_kept(1);
+    let first = request_ride(&dispatch).await;
+    request_ride(&dispatch).await;
+    assert_eq!(state(&get_trip(&dispatch, &first.trip_id).await.unwrap()), TripState::NoCabAvailable);
+}
+
+// 테스트가 열어줄 때까지 trip을 쓰지 못하는 store
+#[derive(Debug)]
+struct GatedStore {
+    inner: SqliteStore,
+    open: Mutex<bool>,
+    opened: Condvar,
+    // save_trip에 들어올 때마다 알린다
+    saving: tokio_mpsc::UnboundedSender<()>,
+}
+
+impl GatedStore {
+    fn new() -> (Self, tokio_mpsc::UnboundedReceiver<()>) {
+        let (saving, saves) = tokio_mpsc::unbounded_channel();
+        let store = Self {
+            inner: SqliteStore::open_in_memory().unwrap(),
+            open: Mutex::new(true),
+            opened: Condvar::new(),
+            saving,
+        };
+        (store, saves)
+    }
+
+    fn set_open(&self, open: bool) {
+        *self.open.lock().unwrap() = open;
+        self.opened.notify_all();
+    }
+}
+
+// 테스트가 중간에 실패해도 막힌 blocking thread가 runtime 종료를 붙잡지 않게 연다
+struct OpenOnDrop(Arc<GatedStore>);
+
+impl Drop for OpenOnDrop {
+    fn drop(&mut self) {
+        self.0.set_open(true);
+    }
+}
+
+impl Store for GatedStore {
+    fn save_cabs(&self, changes: &[CabChange]) -> Result<(), StoreError> {
+        self.inner.save_cabs(changes)
+    }
+
+    fn load_cabs(&self) -> Result<Vec<(String, CabEntry)>, StoreError> {
+        self.inner.load_cabs()
+    }
+
+    fn cab_history(
+        &self,
+        name: &str,
+        from: SystemTime,
+        to: SystemTime,
+        limit: usize,
+    ) -> Result<Vec<TrackPoint>, StoreError> {
+        self.inner.cab_history(name, from, to, limit)
+    }
+
+    fn save_trip(&self, trip: &Trip) -> Result<(), StoreError> {
+        let _ = self.saving.send(());
+        let _open = self.opened.wait_while(self.open.lock().unwrap(), |open| !*open).unwrap();
+        self.inner.save_trip(trip)
+    }
+
+    fn load_trip(&self, trip_id: &str) -> Result<Option<Trip>, StoreError> {
+        self.inner.load_trip(trip_id)
+    }
+
+    fn prune(&self, before: SystemTime) -> Result<usize, StoreError> {
+        self.inner.prune(before)
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn saving_does_not_hold_the_trips_lock() {
+    let (store, mut saves) = GatedStore::new();
+    let store = Arc::new(store);
+    let dispatch = DispatchService::new(registry(1)).with_store(store.clone());
+    let trip = request_ride(&dispatch).await;
+    // 응답이 오면 이미 써 있다
+    assert_eq!(store.load_trip(&trip.trip_id).unwrap().unwrap().state, TripState::Offered as i32);
+    while saves.try_recv().is_ok() {}
+
+    store.set_open(false);
+    let _open_on_drop = OpenOnDrop(store.clone());
+    let accepting = tokio::spawn({
+        let dispatch = dispatch.clone();
+        let trip = trip.clone();
+        async move { dispatch.accept_trip(action(&trip, "cab-1")).await.unwrap() }
+    });
+    saves.recv().await.unwrap();
+
+    // 쓰는 동안에도 메모리에 있는 것은 읽는다. lock을 쥐고 있으면 여기서 멈춘다
+    let accepted = tokio::time::timeout(Duration::from_secs(10), get_trip(&dispatch, &trip.trip_id))
+        .await
+        .expect("get_trip waited for the store")
+        .unwrap();
+    assert_eq!(state(&accepted), TripState::Accepted);
+    // 수락은 다 써야 응답한다
+    assert!(!accepting.is_finished());
+
+    store.set_open(true);
+    accepting.await.unwrap();
+    assert_eq!(store.load_trip(&trip.trip_id).unwrap().unwrap().state, TripState::Accepted as i32);
+}
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tonic::Request;
+
+use learning_grpc::distance::distance_meters;
+use learning_grpc::eta::{EtaEstimator, HeuristicEta};
+use learning_grpc::hello::cab_service_server::CabService as _;
+use learning_grpc::hello::{CabLocationRequest, GetCabRequest, Location};
+use learning_grpc::road_graph::RoadGraph;
+use learning_grpc::service::CabService;
+
+// 정사각형 블록 하나. 아래쪽(a -> b)은 서쪽에서 동쪽으로만 가는 큰길이고 대각선은 보행로다
+const ROADS: &str = r#"{
+    "version": 0.6,
+    "elements": [
+        {"type": "node", "id": 1, "lat": 37.50, "lon": 127.00},
+        {"type": "node", "id": 2, "lat": 37.50, "lon": 127.01},
+        {"type": "node", "id": 3, "lat": 37.51, "lon": 127.01},
+        {"type": "node", "id": 4, "lat": 37.51, "lon": 127.00},
+        {"type": "way", "id": 10, "nodes": [1, 2], "tags": {"highway": "primary", "oneway": "yes", "maxspeed": "60"}},
+        {"type": "way", "id": 11, "nodes": [2, 3, 4, 1], "tags": {"highway": "residential"}},
+        {"type": "way", "id": 12, "nodes": [1, 3], "tags": {"highway": "footway"}},
+        {"type": "relation", "id": 20, "members": []}
+    ]
+}"#;
+
+fn a() -> Location {
+    Location::new(37.50, 127.00)
+}
+
+fn b() -> Location {
+    Location::new(37.50, 127.01)
+}
+
+fn moving(mut location: Location, heading_degrees: f64, speed_mps: f64) -> Location {
+    location.heading_degrees = Some(heading_degrees);
+    location.speed_mps = Some(speed_mps);
+    location
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-6 * expected.max(1.0), "{} != {}", actual, expected);
+}
+
+#[test]
+fn heuristic_uses_speed_and_heading() {
+    let eta = HeuristicEta::default();
+    let (cab, pickup) = (Location::new(37.49, 127.0), Location::new(37.5, 127.0));
+    let route_meters = distance_meters(&cab, &pickup) * eta.detour_factor();
+
+    // 서 있으면 기본 속도
+    let still = eta.estimate(&cab, &pickup);
+    assert_close(still.route_meters, route_meters);
+    assert_close(still.duration.as_secs_f64(), route_meters / eta.default_speed_mps());
+
+    // 북쪽으로 오고 있으면 그 속도, 남쪽으로 가고 있으면 돌아와야 한다
+    let coming = eta.estimate(&moving(cab.clone(), 0.0, 12.0), &pickup);
+    assert_close(coming.duration.as_secs_f64(), route_meters / 12.0);
+    let leaving = eta.estimate(&moving(cab.clone(), 180.0, 12.0), &pickup);
+    assert_close(leaving.duration.as_secs_f64(), route_meters / 12.0 + eta.turn_around().as_secs_f64());
+    let sideways = eta.estimate(&moving(cab.clone(), 90.0, 12.0), &pickup);
+    assert!(coming.duration < sideways.duration && sideways.duration < leaving.duration);
+
+    // 너무 빠른 속도는 믿지 않는다
+    let racing = eta.estimate(&moving(cab, 0.0, 60.0), &pickup);
+    assert_close(racing.duration.as_secs_f64(), route_meters / eta.max_speed_mps());
+}
+
+#[test]
+fn heuristic_rejects_speeds_it_cannot_divide_by() {
+    let turn_around = Duration::from_secs(90);
+    for speed in [0.0, -8.0, f64::NAN, f64::INFINITY] {
+        assert!(HeuristicEta::new(1.3, speed, 16.7, turn_around).is_err(), "{}", speed);
+        assert!(HeuristicEta::new(1.3, 8.0, speed, turn_around).is_err(), "{}", speed);
+        assert!(HeuristicEta::new(speed, 8.0, 16.7, turn_around).is_err(), "{}", speed);
+    }
+    let eta = HeuristicEta::new(1.3, 8.0, 16.7, turn_around).unwrap();
+    assert_eq!(eta, HeuristicEta::default());
+}
+
+#[test]
+fn road_graph_follows_one_way_streets() {
+    let graph = RoadGraph::from_overpass_json(ROADS).unwrap();
+    assert_eq!(graph.len(), 4);
+    let side = distance_meters(&a(), &b());
+
+    // a -> b는 큰길 하나
+    let forward = graph.estimate(&a(), &b());
+    assert_close(forward.route_meters, side);
+    assert_close(forward.duration.as_secs_f64(), side / (60.0 / 3.6));
+
+    // b -> a는 나머지 세 변을 돌아야 하고, 보행로는 쓰지 않는다
+    let backward = graph.estimate(&b(), &a());
+    let around = distance_meters(&b(), &Location::new(37.51, 127.01))
+        + distance_meters(&Location::new(37.51, 127.01), &Location::new(37.51, 127.00))
+        + distance_meters(&Location::new(37.51, 127.00), &a());
+    assert_close(backward.route_meters, around);
+    assert_close(backward.duration.as_secs_f64(), around / (30.0 / 3.6));
+
+    // 여러 대를 한 번에 계산해도 같다
+    let cabs = [a(), b(), Location::new(37.51, 127.005)];
+    let many = graph.estimate_many(&cabs, &a());
+    let one_by_one: Vec<_> = cabs.iter().map(|cab| graph.estimate(cab, &a())).collect();
+    for (many, one) in many.iter().zip(&one_by_one) {
+        assert_close(many.route_meters, one.route_meters);
+        assert_close(many.duration.as_secs_f64(), one.duration.as_secs_f64());
+    }
+    assert_eq!(many[0].duration, Duration::ZERO);
+}
+
+#[test]
+fn road_graph_falls_back_off_the_map() {
+    let graph = RoadGraph::from_overpass_json(ROADS).unwrap();
+    let far = Location::new(37.6, 127.0);
+    assert_eq!(graph.estimate(&far, &a()), HeuristicEta::default().estimate(&far, &a()));
+    assert_eq!(graph.estimate_many(std::slice::from_ref(&far), &a()), [HeuristicEta::default().estimate(&far, &a())]);
+
+    assert!(RoadGraph::from_overpass_json(r#"{"elements": []}"#).is_err());
+    assert!(RoadGraph::from_overpass_json("[]").is_err());
+}
+
+#[tokio::test]
+async fn get_cabs_carries_distance_and_eta() {
+    let graph = RoadGraph::from_overpass_json(ROADS).unwrap();
+    let service = CabService::default().with_eta(Arc::new(graph));
+    for (name, location) in [("west", a()), ("east", b())] {
+        let req = CabLocationRequest { name: name.to_string(), location: Some(location), ..Default::default() };
+        service.record_cab_location(Request::new(req)).await.unwrap();
+    }
+
+    let req = GetCabRequest { location: Some(Location::new(37.5, 127.0001)), ..GetCabRequest::default() };
+    let cabs = service.get_cabs(Request::new(req)).await.unwrap().into_inner().cabs;
+    assert_eq!(cabs.iter().map(|cab| cab.name.as_str()).collect::<Vec<_>>(), ["west", "east"]);
+    let (west, east) = (&cabs[0], &cabs[1]);
+    assert!(west.distance_meters.unwrap() < 10.0);
+    assert!(east.distance_meters.unwrap() > 800.0);
+    // 동쪽 cab은 일방통행 때문에 블록을 돈다
+    assert!(east.route_distance_meters.unwrap() > 3000.0);
+    assert!(west.eta_seconds.unwrap() < east.eta_seconds.unwrap());
+}
@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use tokio::time::Instant;
+use tonic::Request;
+
+use learning_grpc::geofence::{Admission, Geofences, OutsidePolicy};
+use learning_grpc::hello::cab_service_server::CabService as _;
+use learning_grpc::hello::{
+    CabLocationRequest, GetCabRequest, Location, LookupZonesRequest, RejectReason, Zone, ZoneKind,
+};
+use learning_grpc::ingest::Batch;
+use learning_grpc::registry::CabRegistry;
+use learning_grpc::service::CabService;
+
+// 도시 하나에 구멍(공원) 하나, 그 안에 공항 대기열과 승차 금지 구역
+const ZONES: &str = r#"{
+    "type": "FeatureCollection",
+    "features": [
+        {
+            "type": "Feature",
+            "properties": {"name": "city", "kind": "service_area"},
+            "geometry": {"type": "Polygon", "coordinates": [
+                [[126.9, 37.4], [127.1, 37.4], [127.1, 37.6], [126.9, 37.6], [126.9, 37.4]],
+                [[127.05, 37.55], [127.08, 37.55], [127.08, 37.58], [127.05, 37.58], [127.05, 37.55]]
+            ]}
+        },
+        {
+            "type": "Feature",
+            "properties": {"name": "airport", "kind": "airport_queue"},
+            "geometry": {"type": "MultiPolygon", "coordinates": [
+                [[[126.95, 37.45], [126.97, 37.45], [126.97, 37.47], [126.95, 37.47], [126.95, 37.45]]]
+            ]}
+        },
+        {
+            "type": "Feature",
+            "properties": {"name": "station", "kind": "no_pickup"},
+            "geometry": {"type": "Polygon", "coordinates": [
+                [[127.0, 37.5], [127.01, 37.5], [127.01, 37.51], [127.0, 37.51], [127.0, 37.5]]
+            ]}
+        }
+    ]
+}"#;
+
+fn geofences(outside: OutsidePolicy) -> Arc<Geofences> {
+    Arc::new(Geofences::from_geojson(ZONES).unwrap().with_outside_policy(outside))
+}
+
+fn city() -> Location {
+    Location::new(37.53, 127.02)
+}
+
+fn airport() -> Location {
+    Location::new(37.46, 126.96)
+}
+
+fn station() -> Location {
+    Location::new(37.505, 127.005)
+}
+
+fn outside() -> Location {
+    Location::new(35.1, 129.0)
+}
+
+fn record(name: &str, location: Location) -> CabLocationRequest {
+    CabLocationRequest { name: name.to_string(), location: Some(location), ..CabLocationRequest::default() }
+}
+
+fn zone_names(geofences: &Geofences, location: &Location) -> Vec<String> {
+    geofences.zones_at(location).map(|zone| zone.name.clone()).collect()
+}
+
+#[test]
+fn looks_up_zones() {
+    let geofences = geofences(OutsidePolicy::Reject);
+    assert_eq!(geofences.zones().len(), 3);
+    assert_eq!(zone_names(&geofences, &airport()), ["city", "airport"]);
+    assert_eq!(zone_names(&geofences, &station()), ["city", "station"]);
+    // 경계 위는 안이다
+    assert!(geofences.in_service_area(&Location::new(37.5, 126.9)));
+    // 구멍 안은 밖이다
+    assert!(!geofences.in_service_area(&Location::new(37.56, 127.06)));
+    assert!(!geofences.in_service_area(&outside()));
+    assert!(zone_names(&geofences, &outside()).is_empty());
+
+    // zone이 없으면 어디든 서비스 지역이다
+    assert_eq!(Geofences::default().admit(&outside()), Admission::Inside);
+}
+
+#[test]
+fn rejects_unusable_geojson() {
+    let feature = |properties: &str, geometry: &str| {
+        format!(
+            r#"{{"type": "FeatureCollection", "features": [{{"type": "Feature", "properties": {}, "geometry": {}}}]}}"#,
+            properties, geometry
+        )
+    };
+    let square = r#"{"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 0]]]}"#;
+    let valid = r#"{"name": "a", "kind": "no_pickup"}"#;
+    assert!(Geofences::from_geojson(&feature(valid, square)).is_ok());
+
+    let invalid = [
+        feature(valid, r#"{"type": "Point", "coordinates": [0, 0]}"#),
+        feature(r#"{"name": "a"}"#, square),
+        feature(r#"{"name": "a", "kind": "parking"}"#, square),
+        feature("null", square),
+        feature(valid, r#"{"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [0, 0]]]}"#),
+        feature(valid, r#"{"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 91], [0, 0]]]}"#),
+        "{}".to_string(),
+    ];
+    for json in &invalid {
+        assert!(Geofences::from_geojson(json).is_err(), "{}", json);
+    }
+}
+
+#[tokio::test]
+async fn record_outside_service_area_is_rejected_or_flagged() {
+    let rejecting = CabService::default().with_geofences(geofences(OutsidePolicy::Reject));
+    let response = rejecting.record_cab_location(Request::new(record("a", outside()))).await.unwrap().into_inner();
+    assert!(!response.accepted);
+    assert_eq!(response.reject_reason, RejectReason::OutsideServiceArea as i32);
+    assert!(rejecting.registry().get("a").is_none());
+
+    let response = rejecting.record_cab_location(Request::new(record("a", city()))).await.unwrap().into_inner();
+    assert!(response.accepted && !response.outside_service_area);
+
+    let flagging = CabService::default().with_geofences(geofences(OutsidePolicy::Flag));
+    let response = flagging.record_cab_location(Request::new(record("a", outside()))).await.unwrap().into_inner();
+    assert!(response.accepted && response.outside_service_area);
+    assert!(flagging.registry().get("a").is_some());
+}
+
+#[tokio::test]
+async fn get_cabs_follows_zone_rules() {
+    let service = CabService::default().with_geofences(geofences(OutsidePolicy::Flag));
+    for (name, location) in [("city", city()), ("airport", airport()), ("station", station()), ("outside", outside())] {
+        service.record_cab_location(Request::new(record(name, location))).await.unwrap();
+    }
+    let found = |origin: Location| {
+        let service = service.clone();
+        async move {
+            let req = GetCabRequest { location: Some(origin), radius_meters: 50_000.0, ..GetCabRequest::default() };
+            let cabs = service.get_cabs(Request::new(req)).await.unwrap().into_inner().cabs;
+            cabs.into_iter().map(|cab| cab.name).collect::<Vec<_>>()
+        }
+    };
+
+    // 공항 대기열의 cab은 공항 안의 손님에게만, 승차 금지 구역과 서비스 지역 밖의 cab은 아무에게도
+    assert_eq!(found(city()).await, ["city"]);
+    assert_eq!(found(station()).await, ["city"]);
+    assert_eq!(found(airport()).await, ["airport"]);
+    assert!(found(outside()).await.is_empty());
+}
+
+#[tokio::test]
+async fn lookup_zones_lists_every_zone() {
+    let service = CabService::default().with_geofences(geofences(OutsidePolicy::Reject));
+    let lookup = |location: Location| {
+        let service = service.clone();
+        async move {
+            let req = LookupZonesRequest { location: Some(location) };
+            service.lookup_zones(Request::new(req)).await.unwrap().into_inner()
+        }
+    };
+
+    let response = lookup(station()).await;
+    assert!(response.in_service_area);
+    assert_eq!(
+        response.zones,
+        [
+            Zone { name: "city".to_string(), kind: ZoneKind::ServiceArea as i32 },
+            Zone { name: "station".to_string(), kind: ZoneKind::NoPickup as i32 },
+        ]
+    );
+    let response = lookup(outside()).await;
+    assert!(!response.in_service_area && response.zones.is_empty());
+
+    let err = service.lookup_zones(Request::new(LookupZonesRequest { location: None })).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+}
+
+#[test]
+fn stream_applies_outside_policy() {
+    let registry = CabRegistry::new();
+    let now = Instant::now();
+
+    let mut batch = Batch::new(None, geofences(OutsidePolicy::Reject));
+    batch.push(&registry, record("a", outside()), now);
+    batch.push(&registry, record("b", city()), now);
+    let ack = batch.take_ack();
+    assert_eq!((ack.accepted, ack.rejected, ack.outside_service_area), (1, 1, 0));
+    assert_eq!(ack.last_reject_reason, RejectReason::OutsideServiceArea as i32);
+    assert!(registry.get("a").is_none());
+
+    let mut batch = Batch::new(None, geofences(OutsidePolicy::Flag));
+    batch.push(&registry, record("a", outside()), now);
+    let ack = batch.take_ack();
+    assert_eq!((ack.accepted, ack.rejected, ack.outside_service_area), (1, 0, 1));
+    assert!(registry.get("a").is_some());
+}
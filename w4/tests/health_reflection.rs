@@ -0,0 +1,152 @@
+mod common;
+
+use prost::Message;
+use prost_types::FileDescriptorProto;
+use tonic::client::Grpc;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::{Channel, Server};
+use tonic::{Code, Status};
+use tonic_health::proto::health_check_response::ServingStatus;
+use tonic_health::proto::health_client::HealthClient;
+use tonic_health::proto::HealthCheckRequest;
+use tonic_health::server::HealthReporter;
+
+use learning_grpc::health;
+use learning_grpc::reflection;
+
+// tonic-reflection은 client를 내보내지 않으므로 쓰는 message만 옮겨 왔다
+mod pb {
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct ServerReflectionRequest {
+        #[prost(string, tag = "1")]
+        pub host: String,
+        #[prost(oneof = "MessageRequest", tags = "4, 7")]
+        pub message_request: Option<MessageRequest>,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum MessageRequest {
+        #[prost(string, tag = "4")]
+        FileContainingSymbol(String),
+        #[prost(string, tag = "7")]
+        ListServices(String),
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct ServerReflectionResponse {
+        #[prost(oneof = "MessageResponse", tags = "4, 6")]
+        pub message_response: Option<MessageResponse>,
+    }
+
+    // proto의 이름을 그대로 쓴다
+    #[allow(clippy::enum_variant_names)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum MessageResponse {
+        #[prost(message, tag = "4")]
+        FileDescriptorResponse(FileDescriptorResponse),
+        #[prost(message, tag = "6")]
+        ListServicesResponse(ListServiceResponse),
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct FileDescriptorResponse {
+        #[prost(bytes = "vec", repeated, tag = "1")]
+        pub file_descriptor_proto: Vec<Vec<u8>>,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct ListServiceResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub service: Vec<ServiceResponse>,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct ServiceResponse {
+        #[prost(string, tag = "1")]
+        pub name: String,
+    }
+}
+
+use pb::{MessageRequest, MessageResponse, ServerReflectionRequest, ServerReflectionResponse};
+
+async fn start_server() -> (HealthReporter, Channel) {
+    let (health, health_server) = tonic_health::server::health_reporter();
+    let router = Server::builder().add_service(health_server).add_service(reflection::builder().build().unwrap());
+    (health, common::connect(common::serve(router).await).await)
+}
+
+fn check(service: &str) -> HealthCheckRequest {
+    HealthCheckRequest { service: service.to_string() }
+}
+
+#[tokio::test]
+async fn health_follows_reporter() {
+    let (mut health, channel) = start_server().await;
+    health::set_all(&mut health, tonic_health::ServingStatus::NotServing).await;
+    let mut client = HealthClient::new(channel);
+
+    let status = client.check(check(health::SERVER)).await.unwrap().into_inner().status;
+    assert_eq!(status, ServingStatus::NotServing as i32);
+
+    let mut watch = client.watch(check("cabs.v1.CabService")).await.unwrap().into_inner();
+    assert_eq!(watch.message().await.unwrap().unwrap().status, ServingStatus::NotServing as i32);
+
+    health::set_all(&mut health, tonic_health::ServingStatus::Serving).await;
+    assert_eq!(watch.message().await.unwrap().unwrap().status, ServingStatus::Serving as i32);
+    // 예전 이름으로 probe해도 같은 상태
+    for service in ["cabs.v1.DispatchService", "Hello.Hello", "Hello.Dispatch"] {
+        let status = client.check(check(service)).await.unwrap().into_inner().status;
+        assert_eq!(status, ServingStatus::Serving as i32, "{}", service);
+    }
+
+    let status = client.check(check("no.such.Service")).await.unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+    let status = client.watch(check("no.such.Service")).await.unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+}
+
+async fn reflect(client: &mut Grpc<Channel>, request: MessageRequest) -> Result<MessageResponse, Status> {
+    let request = ServerReflectionRequest { host: String::new(), message_request: Some(request) };
+    let path = PathAndQuery::from_static("/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo");
+    client.ready().await.unwrap();
+    let codec = ProstCodec::<ServerReflectionRequest, ServerReflectionResponse>::default();
+    let response = client.streaming(tonic::Request::new(tokio_stream::iter(vec![request])), path, codec).await;
+    let mut responses = response.unwrap().into_inner();
+    responses.message().await.map(|response| response.unwrap().message_response.unwrap())
+}
+
+#[tokio::test]
+async fn reflection_lists_and_describes_services() {
+    let mut client = Grpc::new(start_server().await.1);
+
+    let services = match reflect(&mut client, MessageRequest::ListServices(String::new())).await.unwrap() {
+        MessageResponse::ListServicesResponse(list) => list.service.into_iter().map(|s| s.name).collect::<Vec<_>>(),
+        other => panic!("expected services, got {:?}", other),
+    };
+    assert_eq!(
+        services,
+        [
+            "cabs.v1.CabService",
+            "cabs.v1.DispatchService",
+            "grpc.health.v1.Health",
+            "grpc.reflection.v1alpha.ServerReflection"
+        ]
+    );
+
+    for symbol in ["cabs.v1.CabService", "cabs.v1.CabService.GetCabs", "cabs.v1.WatchCabsResponse", "cabs.v1.CabStatus"]
+    {
+        match reflect(&mut client, MessageRequest::FileContainingSymbol(symbol.to_string())).await.unwrap() {
+            MessageResponse::FileDescriptorResponse(files) => {
+                let file = FileDescriptorProto::decode(files.file_descriptor_proto[0].as_slice()).unwrap();
+                assert_eq!(file.name(), "hello.proto");
+                assert_eq!(file.package(), "cabs.v1");
+            }
+            other => panic!("expected a file for {}, got {:?}", symbol, other),
+        }
+    }
+
+    // tonic-reflection은 ErrorResponse 대신 stream을 NOT_FOUND로 끝낸다
+    let status = reflect(&mut client, MessageRequest::FileContainingSymbol("cabs.v1.Nope".to_string())).await;
+    assert_eq!(status.unwrap_err().code(), Code::NotFound);
+}
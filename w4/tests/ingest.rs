@@ -0,0 +1,106 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::Status;
+
+use learning_grpc::geofence::Geofences;
+use learning_grpc::hello::{CabLocationAck, CabLocationRequest, RejectReason};
+use learning_grpc::ingest::{self, Batch, ACK_BATCH_SIZE, ACK_INTERVAL, MIN_UPDATE_INTERVAL};
+use learning_grpc::registry::CabRegistry;
+
+fn located(name: &str) -> CabLocationRequest {
+    common::located(name, common::origin())
+}
+
+#[test]
+fn batch_counts_accepted_rejected_and_throttled() {
+    let registry = CabRegistry::new();
+    let mut batch = Batch::new(None, Arc::new(Geofences::default()));
+    let start = Instant::now();
+
+    assert!(!batch.push(&registry, located("cab-1"), start));
+    // 같은 cab이 너무 빨리 다시 보냈다
+    assert!(!batch.push(&registry, located("cab-1"), start + MIN_UPDATE_INTERVAL / 2));
+    assert!(!batch.push(&registry, located("cab-2"), start));
+    assert!(!batch.push(&registry, CabLocationRequest { name: "cab-3".to_string(), ..Default::default() }, start));
+    assert_eq!(batch.len(), 4);
+
+    let ack = batch.take_ack();
+    assert_eq!((ack.received, ack.accepted, ack.rejected, ack.throttled), (4, 2, 1, 1));
+    assert_eq!(ack.min_interval_ms, MIN_UPDATE_INTERVAL.as_millis() as u32);
+    assert_eq!(ack.last_reject_reason, RejectReason::MissingLocation as i32);
+    assert_eq!(registry.len(), 2);
+    assert!(batch.is_empty());
+
+    // 간격이 지나면 다시 받는다. received는 stream 전체로 센다
+    assert!(!batch.push(&registry, located("cab-1"), start + MIN_UPDATE_INTERVAL));
+    let ack = batch.take_ack();
+    assert_eq!((ack.received, ack.accepted, ack.throttled, ack.min_interval_ms), (5, 1, 0, 0));
+}
+
+#[test]
+fn batch_is_full_after_ack_batch_size() {
+    let registry = CabRegistry::new();
+    let mut batch = Batch::new(None, Arc::new(Geofences::default()));
+    let now = Instant::now();
+    for i in 1..ACK_BATCH_SIZE {
+        assert!(!batch.push(&registry, located(&format!("cab-{}", i)), now));
+    }
+    assert!(batch.push(&registry, located("last"), now));
+}
+
+#[test]
+fn owner_may_only_send_its_own_cab() {
+    let registry = CabRegistry::new();
+    let mut batch = Batch::new(Some("mine".to_string()), Arc::new(Geofences::default()));
+    batch.push(&registry, located("mine"), Instant::now());
+    batch.push(&registry, located("theirs"), Instant::now());
+
+    let ack = batch.take_ack();
+    assert_eq!((ack.accepted, ack.rejected), (1, 1));
+    assert_eq!(ack.last_reject_reason, RejectReason::NotOwner as i32);
+    assert!(registry.get("theirs").is_none());
+}
+
+#[tokio::test]
+async fn acks_by_batch_size_and_at_the_end() {
+    let registry = Arc::new(CabRegistry::new());
+    let requests: Vec<Result<_, Status>> =
+        (0..ACK_BATCH_SIZE + 3).map(|i| located(&format!("cab-{}", i))).map(Ok).collect();
+    let acks: Vec<CabLocationAck> =
+        ingest::ingest(registry.clone(), Arc::default(), None, tokio_stream::iter(requests))
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+    let counts: Vec<_> = acks.iter().map(|ack| (ack.received, ack.accepted)).collect();
+    assert_eq!(counts, [(u64::from(ACK_BATCH_SIZE), ACK_BATCH_SIZE), (u64::from(ACK_BATCH_SIZE) + 3, 3)]);
+    assert_eq!(registry.len(), ACK_BATCH_SIZE as usize + 3);
+}
+
+#[tokio::test]
+async fn acks_a_partial_batch_after_the_interval() {
+    let (tx, rx) = mpsc::channel(8);
+    let mut acks = ingest::ingest(Arc::new(CabRegistry::new()), Arc::default(), None, ReceiverStream::new(rx));
+    tx.send(Ok(located("cab-1"))).await.unwrap();
+    tx.send(Ok(located("cab-2"))).await.unwrap();
+
+    // stream은 열려 있어도 ACK_INTERVAL이 지나면 ack가 온다
+    let started = Instant::now();
+    let ack = tokio::time::timeout(ACK_INTERVAL * 3, acks.next()).await.unwrap().unwrap().unwrap();
+    assert_eq!((ack.received, ack.accepted), (2, 2));
+    assert!(started.elapsed() >= ACK_INTERVAL / 2);
+
+    // 보낼 것이 없으면 ack도 없다
+    assert!(tokio::time::timeout(ACK_INTERVAL + Duration::from_millis(300), acks.next()).await.is_err());
+    drop(tx);
+    let last = acks.next().await.unwrap().unwrap();
+    assert_eq!((last.received, last.accepted), (2, 0));
+    assert!(acks.next().await.is_none());
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use std::net::SocketAddr;
+
+use tonic::transport::Server;
+use tonic::Code;
+
+use learning_grpc::dispatch::DispatchService;
+use learning_grpc::hello::cab_service_client::CabServiceClient;
+use learning_grpc::hello::cab_service_server::CabServiceServer;
+use learning_grpc::hello::dispatch_service_server::DispatchServiceServer;
+use learning_grpc::hello::{
+    CabLocationRequest, CabLocationResponse, GetCabRequest, HelloRequest, HelloResponse, RideRequest, Trip, TripState,
+};
+use learning_grpc::legacy::LegacyRoutes;
+use learning_grpc::service::CabService;
+
+// 예전 클라이언트가 하던 것처럼 path를 직접 적어서 부른다
+use common::{call_path as call_old, connect, seoul};
+
+async fn start_server() -> SocketAddr {
+    let cab_service = CabService::default();
+    let cab_server = CabServiceServer::new(cab_service.clone());
+    let dispatch_server = DispatchServiceServer::new(DispatchService::new(cab_service.registry().clone()));
+
+    common::serve(
+        Server::builder()
+            .add_service(cab_server.clone())
+            .add_service(dispatch_server.clone())
+            .add_service(LegacyRoutes::new(cab_server))
+            .add_service(LegacyRoutes::new(dispatch_server)),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn old_paths_reach_the_renamed_service() {
+    let addr = start_server().await;
+    let channel = connect(addr).await;
+
+    let reply: HelloResponse = call_old(channel.clone(), "/Hello.Hello/HelloWorld", HelloRequest {}).await.unwrap();
+    assert_eq!(reply.message, "Hello, World!");
+
+    let request =
+        CabLocationRequest { name: "cab-1".to_string(), location: Some(seoul()), ..CabLocationRequest::default() };
+    let reply: CabLocationResponse =
+        call_old(channel.clone(), "/Hello.Hello/record_cab_location", request).await.unwrap();
+    assert!(reply.accepted);
+
+    // 예전 path로 올린 위치가 새 path에서도 보인다
+    let mut client = CabServiceClient::new(channel);
+    let response =
+        client.get_cabs(GetCabRequest { location: Some(seoul()), ..GetCabRequest::default() }).await.unwrap();
+    let names: Vec<_> = response.get_ref().cabs.iter().map(|cab| cab.name.as_str()).collect();
+    assert_eq!(names, ["cab-1"]);
+}
+
+#[tokio::test]
+async fn old_dispatch_paths_are_routed() {
+    let addr = start_server().await;
+    let channel = connect(addr).await;
+
+    let request = RideRequest { rider_id: "rider-1".to_string(), pickup: Some(seoul()), ..RideRequest::default() };
+    let trip: Trip = call_old(channel, "/Hello.Dispatch/request_ride", request).await.unwrap();
+    assert_eq!(trip.state, TripState::NoCabAvailable as i32);
+}
+
+#[tokio::test]
+async fn unknown_old_method_is_unimplemented() {
+    let addr = start_server().await;
+    let channel = connect(addr).await;
+
+    let status =
+        call_old::<_, HelloResponse>(channel, "/Hello.Hello/no_such_method", HelloRequest {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
@@ -0,0 +1,113 @@
+#![allow(deprecated)]
+
+use geo::Point;
+use tonic::Request;
+
+use learning_grpc::distance::{bearing_degrees, distance_meters, EARTH_RADIUS_METERS};
+use learning_grpc::hello::cab_service_server::CabService as _;
+use learning_grpc::hello::{CabLocationRequest, GetCabRequest, Location};
+use learning_grpc::service::CabService;
+use learning_grpc::validate;
+
+fn close(a: f64, b: f64, tolerance: f64) -> bool {
+    (a - b).abs() <= tolerance
+}
+
+fn legacy(latitude: f32, longitude: f32) -> Location {
+    Location { legacy_latitude: latitude, legacy_longitude: longitude, ..Location::default() }
+}
+
+fn violations(location: Location) -> Vec<String> {
+    match validate::location("location", Some(&location)) {
+        Ok(_) => Vec::new(),
+        Err(invalid) => invalid.violations.into_iter().map(|v| v.field).collect(),
+    }
+}
+
+#[test]
+fn legacy_floats_fill_the_doubles() {
+    let location = legacy(37.5, 127.25).normalized();
+    assert_eq!((location.latitude, location.longitude), (37.5, 127.25));
+
+    // double이 있으면 float는 무시하고 double에 맞춰 다시 채운다
+    let both = Location { latitude: 10.123456789, longitude: 20.0, ..legacy(1.0, 2.0) }.normalized();
+    assert_eq!((both.latitude, both.longitude), (10.123456789, 20.0));
+    assert_eq!((both.legacy_latitude, both.legacy_longitude), (10.123456789_f64 as f32, 20.0));
+
+    let new = Location::new(-33.8688, 151.2093);
+    assert_eq!(new.normalized(), new);
+}
+
+#[tokio::test]
+async fn legacy_clients_are_answered_in_both_fields() {
+    let service = CabService::default();
+    let req = CabLocationRequest { name: "old".to_string(), location: Some(legacy(37.5, 127.0)), ..Default::default() };
+    service.record_cab_location(Request::new(req)).await.unwrap();
+
+    let req = GetCabRequest { location: Some(legacy(37.5, 127.0)), ..Default::default() };
+    let cabs = service.get_cabs(Request::new(req)).await.unwrap().into_inner().cabs;
+    assert_eq!(cabs.len(), 1);
+    let location = cabs[0].location.clone().unwrap();
+    assert_eq!((location.latitude, location.longitude), (37.5, 127.0));
+    assert_eq!((location.legacy_latitude, location.legacy_longitude), (37.5, 127.0));
+}
+
+#[test]
+fn heading_speed_and_accuracy_bounds() {
+    let with = |heading, speed, accuracy| Location {
+        heading_degrees: heading,
+        speed_mps: speed,
+        accuracy_meters: accuracy,
+        ..Location::new(37.5, 127.0)
+    };
+    assert!(violations(with(None, None, None)).is_empty());
+    assert!(violations(with(Some(0.0), Some(0.0), Some(0.0))).is_empty());
+    assert!(violations(with(Some(359.9), Some(30.0), Some(5.0))).is_empty());
+
+    assert_eq!(violations(with(Some(360.0), None, None)), ["location.heading_degrees"]);
+    assert_eq!(violations(with(Some(-1.0), None, None)), ["location.heading_degrees"]);
+    assert_eq!(violations(with(Some(f64::NAN), None, None)), ["location.heading_degrees"]);
+    assert_eq!(violations(with(None, Some(-0.1), None)), ["location.speed_mps"]);
+    assert_eq!(violations(with(None, Some(f64::INFINITY), None)), ["location.speed_mps"]);
+    assert_eq!(violations(with(None, Some(f64::NAN), Some(-5.0))), ["location.speed_mps", "location.accuracy_meters"]);
+}
+
+#[test]
+fn converts_to_and_from_geo_points() {
+    let location = Location { heading_degrees: Some(90.0), ..Location::new(37.5, 127.25) };
+    let point: Point<f64> = (&location).into();
+    // x가 경도
+    assert_eq!((point.x(), point.y()), (127.25, 37.5));
+    assert_eq!(Point::from(location), point);
+
+    let back = Location::from(point);
+    assert_eq!(back, Location::new(37.5, 127.25));
+    assert_eq!(back.heading_degrees, None);
+}
+
+#[test]
+fn distance_between_known_points() {
+    let seoul = Location::new(37.5665, 126.978);
+    let busan = Location::new(35.1796, 129.0756);
+    assert!(close(distance_meters(&seoul, &busan), 325_000.0, 2_000.0));
+    assert_eq!(distance_meters(&seoul, &busan), distance_meters(&busan, &seoul));
+    assert_eq!(distance_meters(&seoul, &seoul), 0.0);
+
+    // 적도에서 경도 1도, 극에서 극은 반 바퀴
+    let degree = EARTH_RADIUS_METERS * std::f64::consts::PI / 180.0;
+    assert!(close(distance_meters(&Location::new(0.0, 0.0), &Location::new(0.0, 1.0)), degree, 0.01));
+    assert!(close(distance_meters(&Location::new(0.0, 179.5), &Location::new(0.0, -179.5)), degree, 0.01));
+    let half = EARTH_RADIUS_METERS * std::f64::consts::PI;
+    assert!(close(distance_meters(&Location::new(90.0, 0.0), &Location::new(-90.0, 0.0)), half, 0.01));
+}
+
+#[test]
+fn bearing_is_clockwise_from_north() {
+    let origin = Location::new(0.0, 0.0);
+    assert!(close(bearing_degrees(&origin, &Location::new(1.0, 0.0)), 0.0, 1e-9));
+    assert!(close(bearing_degrees(&origin, &Location::new(0.0, 1.0)), 90.0, 1e-9));
+    assert!(close(bearing_degrees(&origin, &Location::new(-1.0, 0.0)), 180.0, 1e-9));
+    assert!(close(bearing_degrees(&origin, &Location::new(0.0, -1.0)), 270.0, 1e-9));
+    // 날짜변경선을 넘으면 동쪽
+    assert!(close(bearing_degrees(&Location::new(0.0, 179.5), &Location::new(0.0, -179.5)), 90.0, 1e-9));
+}
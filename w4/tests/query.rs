@@ -0,0 +1,126 @@
+mod common;
+
+use std::time::{Duration, SystemTime};
+
+use tonic::Request;
+
+use learning_grpc::hello::cab_service_server::CabService as _;
+use learning_grpc::hello::{
+    CabFilter, CabLocationRequest, CabStatus, CabType, GetCabRequest, GetCabResponse, Location,
+};
+use learning_grpc::query::{
+    CabQuery, DEFAULT_MAX_RESULTS, DEFAULT_RADIUS_METERS, MAX_RADIUS_METERS, MAX_RESULTS_LIMIT,
+};
+use learning_grpc::registry::{CabAttributes, CabEntry};
+use learning_grpc::service::CabService;
+
+use common::{located, north, origin};
+
+async fn record(service: &CabService, name: &str, location: Location, cab_type: CabType, capacity: u32) {
+    let req = CabLocationRequest { cab_type: cab_type as i32, capacity, ..located(name, location) };
+    common::record(service, req).await;
+}
+
+async fn get(service: &CabService, req: GetCabRequest) -> GetCabResponse {
+    service.get_cabs(Request::new(req)).await.unwrap().into_inner()
+}
+
+fn names(response: &GetCabResponse) -> Vec<&str> {
+    response.cabs.iter().map(|cab| cab.name.as_str()).collect()
+}
+
+fn entry(cab_type: CabType, capacity: u32, status: CabStatus, updated_at: SystemTime) -> CabEntry {
+    CabEntry { location: origin(), cab_type, capacity, status, updated_at }
+}
+
+#[tokio::test]
+async fn radius_and_max_results_limit_the_answer() {
+    let service = CabService::default();
+    for (i, meters) in [100.0, 900.0, 1_900.0, 4_000.0, 8_000.0].iter().enumerate() {
+        record(&service, &format!("cab-{}", i), north(*meters), CabType::Standard, 4).await;
+    }
+
+    // 반경을 안 주면 기본 5km
+    let all = get(&service, GetCabRequest { location: Some(origin()), ..GetCabRequest::default() }).await;
+    assert_eq!(names(&all), ["cab-0", "cab-1", "cab-2", "cab-3"]);
+
+    let near = GetCabRequest { location: Some(origin()), radius_meters: 1_000.0, ..GetCabRequest::default() };
+    assert_eq!(names(&get(&service, near).await), ["cab-0", "cab-1"]);
+
+    let two = GetCabRequest { location: Some(origin()), max_results: 2, ..GetCabRequest::default() };
+    assert_eq!(names(&get(&service, two).await), ["cab-0", "cab-1"]);
+}
+
+#[tokio::test]
+async fn filters_by_type_and_capacity() {
+    let service = CabService::default();
+    record(&service, "standard", north(100.0), CabType::Standard, 4).await;
+    record(&service, "xl", north(200.0), CabType::Xl, 6).await;
+    record(&service, "premium", north(300.0), CabType::Premium, 4).await;
+
+    let filtered = |cab_types: Vec<CabType>, min_capacity| GetCabRequest {
+        location: Some(origin()),
+        filter: Some(CabFilter { cab_types: cab_types.into_iter().map(|t| t as i32).collect(), min_capacity }),
+        ..GetCabRequest::default()
+    };
+    assert_eq!(names(&get(&service, filtered(vec![], 0)).await), ["standard", "xl", "premium"]);
+    assert_eq!(names(&get(&service, filtered(vec![CabType::Xl, CabType::Premium], 0)).await), ["xl", "premium"]);
+    assert_eq!(names(&get(&service, filtered(vec![], 5)).await), ["xl"]);
+    assert!(get(&service, filtered(vec![CabType::Standard], 5)).await.cabs.is_empty());
+}
+
+#[tokio::test]
+async fn position_only_updates_keep_type_and_capacity() {
+    let service = CabService::default();
+    record(&service, "xl", north(100.0), CabType::Xl, 6).await;
+    // stream_cab_locations처럼 위치만 보낸다
+    record(&service, "xl", north(150.0), CabType::Unspecified, 0).await;
+
+    let entry = service.registry().get("xl").unwrap();
+    assert_eq!((entry.cab_type, entry.capacity), (CabType::Xl, 6));
+    let req = GetCabRequest {
+        location: Some(origin()),
+        filter: Some(CabFilter { cab_types: vec![CabType::Xl as i32], min_capacity: 6 }),
+        ..GetCabRequest::default()
+    };
+    assert_eq!(names(&get(&service, req).await), ["xl"]);
+
+    // 값을 주면 바뀐다
+    service.registry().record("xl", north(150.0), CabAttributes { capacity: 4, ..CabAttributes::default() });
+    let entry = service.registry().get("xl").unwrap();
+    assert_eq!((entry.cab_type, entry.capacity), (CabType::Xl, 4));
+}
+
+#[test]
+fn query_defaults_and_limits() {
+    let query = CabQuery::from_request(&GetCabRequest::default());
+    assert_eq!(query, CabQuery::default());
+    assert_eq!(query.radius_meters, DEFAULT_RADIUS_METERS);
+    assert_eq!(query.max_results, DEFAULT_MAX_RESULTS);
+
+    let query =
+        CabQuery::from_request(&GetCabRequest { radius_meters: 1e9, max_results: 10_000, ..GetCabRequest::default() });
+    assert_eq!(query.radius_meters, MAX_RADIUS_METERS);
+    assert_eq!(query.max_results, MAX_RESULTS_LIMIT);
+
+    let query = CabQuery::from_request(&GetCabRequest { radius_meters: f64::NAN, ..GetCabRequest::default() });
+    assert_eq!(query.radius_meters, DEFAULT_RADIUS_METERS);
+}
+
+#[test]
+fn matches_status_and_staleness() {
+    let now = SystemTime::now();
+    let query = CabQuery { max_staleness: Duration::from_secs(60), ..CabQuery::default() };
+
+    assert!(query.matches(&entry(CabType::Standard, 4, CabStatus::Available, now), now));
+    // 기본은 AVAILABLE만
+    assert!(!query.matches(&entry(CabType::Standard, 4, CabStatus::OnTrip, now), now));
+    let on_trip = CabQuery { statuses: vec![CabStatus::OnTrip as i32], ..query.clone() };
+    assert!(on_trip.matches(&entry(CabType::Standard, 4, CabStatus::OnTrip, now), now));
+
+    let old = now - Duration::from_secs(61);
+    assert!(!query.matches(&entry(CabType::Standard, 4, CabStatus::Available, old), now));
+    // 시계가 거꾸로 가면 방금 들어온 것으로 본다
+    let future = now + Duration::from_secs(10);
+    assert!(query.matches(&entry(CabType::Standard, 4, CabStatus::Available, future), now));
+}
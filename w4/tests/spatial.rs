@@ -0,0 +1,103 @@
+use learning_grpc::distance::distance_meters;
+use learning_grpc::hello::Location;
+use learning_grpc::spatial::SpatialIndex;
+
+// 테스트마다 같은 점이 나오도록 간단한 LCG를 쓴다
+struct Lcg(u64);
+
+impl Lcg {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // 서울 근처 ±0.1도
+    fn near_seoul(&mut self) -> Location {
+        Location::new(37.5665 + (self.next_f64() - 0.5) * 0.2, 126.978 + (self.next_f64() - 0.5) * 0.2)
+    }
+}
+
+fn seoul() -> Location {
+    Location::new(37.5665, 126.978)
+}
+
+fn random_cabs(count: usize) -> Vec<(String, Location)> {
+    let mut rng = Lcg(42);
+    (0..count).map(|i| (format!("cab-{}", i), rng.near_seoul())).collect()
+}
+
+// 전체를 보고 거리순으로 정렬한 것
+fn linear_scan(cabs: &[(String, Location)], origin: &Location) -> Vec<(String, f64)> {
+    let mut all: Vec<_> =
+        cabs.iter().map(|(name, location)| (name.clone(), distance_meters(origin, location))).collect();
+    all.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    all
+}
+
+fn names(found: &[(&str, f64)]) -> Vec<String> {
+    found.iter().map(|(name, _)| name.to_string()).collect()
+}
+
+#[test]
+fn nearest_and_within_match_a_linear_scan() {
+    let cabs = random_cabs(2_000);
+    let index = SpatialIndex::bulk_load(cabs.iter().map(|(name, location)| (name.as_str(), location)));
+    assert_eq!(index.len(), cabs.len());
+
+    let expected = linear_scan(&cabs, &seoul());
+    let nearest = index.nearest(&seoul(), 10);
+    let expected_names: Vec<_> = expected.iter().take(10).map(|(name, _)| name.clone()).collect();
+    assert_eq!(names(&nearest), expected_names);
+    for ((_, meters), (_, expected)) in nearest.iter().zip(&expected) {
+        assert!((meters - expected).abs() < 0.01, "{} != {}", meters, expected);
+    }
+
+    let within = index.within(&seoul(), 2_000.0);
+    let expected_names: Vec<_> =
+        expected.iter().take_while(|(_, meters)| *meters <= 2_000.0).map(|(name, _)| name.clone()).collect();
+    assert!(!expected_names.is_empty());
+    assert_eq!(names(&within), expected_names);
+}
+
+#[test]
+fn upsert_moves_and_remove_forgets() {
+    let mut index = SpatialIndex::new();
+    assert!(index.is_empty());
+    index.upsert("near", &Location::new(37.5666, 126.978));
+    index.upsert("far", &Location::new(37.6, 126.978));
+    assert_eq!(names(&index.nearest(&seoul(), 1)), ["near"]);
+
+    // 같은 이름은 하나만 남고 새 위치로 옮겨진다
+    index.upsert("far", &seoul());
+    assert_eq!(index.len(), 2);
+    assert_eq!(names(&index.nearest(&seoul(), 2)), ["far", "near"]);
+    assert!(index.nearest(&seoul(), 1)[0].1 < 0.01);
+
+    assert!(index.remove("far"));
+    assert!(!index.remove("far"));
+    assert_eq!(names(&index.nearest(&seoul(), 5)), ["near"]);
+    assert!(index.within(&Location::new(37.6, 126.978), 100.0).is_empty());
+}
+
+#[test]
+fn distances_hold_across_the_antimeridian() {
+    let mut index = SpatialIndex::new();
+    // 날짜변경선 건너편이 같은 쪽의 먼 점보다 가깝다
+    index.upsert("across", &Location::new(0.0, -179.99));
+    index.upsert("same-side", &Location::new(0.0, 179.0));
+    let origin = Location::new(0.0, 179.99);
+
+    let found = index.nearest(&origin, 2);
+    assert_eq!(names(&found), ["across", "same-side"]);
+    assert!((found[0].1 - distance_meters(&origin, &Location::new(0.0, -179.99))).abs() < 0.01);
+    assert_eq!(names(&index.within(&origin, 5_000.0)), ["across"]);
+}
+
+#[test]
+fn nearest_iter_is_lazy_and_ordered() {
+    let cabs = random_cabs(500);
+    let index = SpatialIndex::bulk_load(cabs.iter().map(|(name, location)| (name.as_str(), location)));
+    let distances: Vec<f64> = index.nearest_iter(&seoul()).map(|(_, meters)| meters).collect();
+    assert_eq!(distances.len(), 500);
+    assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+}
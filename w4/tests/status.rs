@@ -0,0 +1,127 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use tonic::Request;
+
+use learning_grpc::hello::cab_service_server::CabService as _;
+use learning_grpc::hello::{CabLocationRequest, CabStatus, CabType, GetCabRequest};
+use learning_grpc::registry::{self, CabAttributes, CabEntry, CabRegistry};
+use learning_grpc::service::CabService;
+
+use common::origin;
+
+fn with_status(status: CabStatus) -> CabAttributes {
+    CabAttributes { status, ..CabAttributes::default() }
+}
+
+#[test]
+fn new_cabs_are_available_and_keep_their_status() {
+    let registry = CabRegistry::new();
+    registry.record("a", origin(), CabAttributes::default());
+    assert_eq!(registry.get("a").unwrap().status, CabStatus::Available);
+
+    registry.record("a", origin(), with_status(CabStatus::OnTrip));
+    // 위치만 보내면 상태는 그대로
+    registry.record("a", origin(), CabAttributes::default());
+    assert_eq!(registry.get("a").unwrap().status, CabStatus::OnTrip);
+
+    registry.record("b", origin(), with_status(CabStatus::Offline));
+    assert_eq!(registry.get("b").unwrap().status, CabStatus::Offline);
+}
+
+#[test]
+fn set_status_keeps_the_location_and_notifies() {
+    let registry = CabRegistry::new();
+    let updated_at = registry.record("a", origin(), CabAttributes::default());
+    let mut changes = registry.subscribe();
+
+    assert!(registry.set_status("a", CabStatus::OnTrip));
+    let change = changes.try_recv().unwrap();
+    assert_eq!((change.name.as_str(), change.entry.status), ("a", CabStatus::OnTrip));
+    assert_eq!((change.entry.location, change.entry.updated_at), (origin(), updated_at));
+
+    assert!(!registry.set_status("nobody", CabStatus::Available));
+    assert!(changes.try_recv().is_err());
+}
+
+fn entry(updated_at: SystemTime) -> CabEntry {
+    CabEntry { location: origin(), cab_type: CabType::Standard, capacity: 4, status: CabStatus::Available, updated_at }
+}
+
+#[test]
+fn expire_removes_cabs_older_than_the_ttl() {
+    let registry = CabRegistry::new();
+    let ttl = Duration::from_secs(60);
+    let now = SystemTime::now();
+    registry.restore(vec![
+        ("old".to_string(), entry(now - ttl - Duration::from_secs(1))),
+        // 딱 ttl만큼 지난 것은 남긴다
+        ("edge".to_string(), entry(now - ttl)),
+        // 시계가 거꾸로 가도 지우지 않는다
+        ("future".to_string(), entry(now + Duration::from_secs(10))),
+    ]);
+    let mut changes = registry.subscribe();
+
+    assert_eq!(registry.expire(ttl, now), ["old"]);
+    assert!(registry.get("old").is_none());
+    assert_eq!(registry.len(), 2);
+    let mut names: Vec<_> = registry.nearest(&origin(), 10).into_iter().map(|(cab, _)| cab.name).collect();
+    names.sort();
+    assert_eq!(names, ["edge", "future"]);
+
+    // 지워진 cab은 offline으로 알린다
+    let change = changes.try_recv().unwrap();
+    assert_eq!((change.name.as_str(), change.entry.status), ("old", CabStatus::Offline));
+    assert!(changes.try_recv().is_err());
+    assert!(registry.expire(ttl, now).is_empty());
+}
+
+#[tokio::test]
+async fn expiry_task_removes_silent_cabs() {
+    let registry = Arc::new(CabRegistry::new());
+    registry.record("silent", origin(), CabAttributes::default());
+    let expiry = registry::spawn_expiry(registry.clone(), Duration::from_millis(50));
+
+    for _ in 0..50 {
+        if registry.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(registry.is_empty());
+    expiry.abort();
+}
+
+#[tokio::test]
+async fn get_cabs_returns_only_requested_statuses() {
+    let service = CabService::default();
+    for (name, status) in [("free", CabStatus::Available), ("busy", CabStatus::OnTrip), ("off", CabStatus::Offline)] {
+        let req = CabLocationRequest {
+            name: name.to_string(),
+            location: Some(origin()),
+            status: status as i32,
+            ..CabLocationRequest::default()
+        };
+        service.record_cab_location(Request::new(req)).await.unwrap();
+    }
+
+    let names = |statuses: Vec<CabStatus>| {
+        let req = GetCabRequest {
+            location: Some(origin()),
+            statuses: statuses.into_iter().map(|status| status as i32).collect(),
+            ..GetCabRequest::default()
+        };
+        let service = &service;
+        async move {
+            let cabs = service.get_cabs(Request::new(req)).await.unwrap().into_inner().cabs;
+            let mut names: Vec<_> = cabs.into_iter().map(|cab| cab.name).collect();
+            names.sort();
+            names
+        }
+    };
+    assert_eq!(names(vec![]).await, ["free"]);
+    assert_eq!(names(vec![CabStatus::OnTrip]).await, ["busy"]);
+    assert_eq!(names(vec![CabStatus::Available, CabStatus::Offline]).await, ["free", "off"]);
+}
@@ -0,0 +1,186 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use tonic::Request;
+
+use learning_grpc::dispatch::DispatchService;
+use learning_grpc::hello::dispatch_service_server::DispatchService as _;
+use learning_grpc::hello::{CabStatus, CabType, GetTripRequest, Location, RideRequest, TripAction, TripState};
+use learning_grpc::registry::{CabAttributes, CabChange, CabEntry, CabRegistry};
+use learning_grpc::sqlite::SqliteStore;
+use learning_grpc::store::{self, Store};
+
+fn db_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("cabs-{}-{}.db", name, std::process::id()));
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+    path
+}
+
+fn at(seconds: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + seconds)
+}
+
+fn change(name: &str, latitude: f64, status: CabStatus, updated_at: SystemTime) -> CabChange {
+    let mut location = Location::new(latitude, 126.978);
+    location.heading_degrees = Some(90.0);
+    CabChange {
+        name: name.to_string(),
+        entry: CabEntry { location, cab_type: CabType::Xl, capacity: 6, status, updated_at },
+    }
+}
+
+#[test]
+fn latest_state_survives_reopen() {
+    let path = db_path("reopen");
+    {
+        let store = SqliteStore::open(&path).unwrap();
+        store.save_cabs(&[change("a", 37.50, CabStatus::Available, at(0))]).unwrap();
+        store
+            .save_cabs(&[change("a", 37.51, CabStatus::OnTrip, at(1)), change("b", 37.52, CabStatus::Available, at(1))])
+            .unwrap();
+        // 지워진 cab은 다시 띄울 때 넣지 않는다
+        store.save_cabs(&[change("b", 37.52, CabStatus::Offline, at(1))]).unwrap();
+    }
+
+    let store = SqliteStore::open(&path).unwrap();
+    let registry = CabRegistry::new();
+    assert_eq!(store.load_cabs().unwrap(), [("a".to_string(), change("a", 37.51, CabStatus::OnTrip, at(1)).entry)]);
+    assert_eq!(store::restore(&registry, &store).unwrap(), 1);
+    // trip은 이어지지 않으므로 AVAILABLE로 돌아온다
+    assert_eq!(registry.get("a"), Some(change("a", 37.51, CabStatus::Available, at(1)).entry));
+    assert_eq!(registry.get("b"), None);
+}
+
+#[test]
+fn history_is_appended_once_per_location() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    store.save_cabs(&[change("a", 37.50, CabStatus::Available, at(0))]).unwrap();
+    store.save_cabs(&[change("a", 37.51, CabStatus::Available, at(10))]).unwrap();
+    // 상태만 바뀌었다
+    store.save_cabs(&[change("a", 37.51, CabStatus::OnTrip, at(10))]).unwrap();
+    store
+        .save_cabs(&[change("a", 37.52, CabStatus::OnTrip, at(20)), change("b", 37.0, CabStatus::Available, at(20))])
+        .unwrap();
+
+    let track = store.cab_history("a", at(0), at(20), 100).unwrap();
+    let points: Vec<_> = track.iter().map(|p| (p.location.latitude, p.recorded_at)).collect();
+    assert_eq!(points, [(37.50, at(0)), (37.51, at(10))]);
+    assert_eq!(track[0].location.heading_degrees, Some(90.0));
+    assert_eq!(store.cab_history("a", at(0), at(21), 100).unwrap().len(), 3);
+    assert_eq!(store.cab_history("a", at(5), at(21), 1).unwrap()[0].recorded_at, at(10));
+}
+
+#[test]
+fn prune_drops_old_history_and_offline_cabs() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    store
+        .save_cabs(&[change("a", 37.50, CabStatus::Available, at(0)), change("b", 37.0, CabStatus::Available, at(0))])
+        .unwrap();
+    store
+        .save_cabs(&[change("a", 37.51, CabStatus::Available, at(100)), change("b", 37.0, CabStatus::Offline, at(0))])
+        .unwrap();
+
+    assert_eq!(store.prune(at(50)).unwrap(), 2);
+    let track = store.cab_history("a", at(0), at(1000), 100).unwrap();
+    assert_eq!(track.iter().map(|p| p.recorded_at).collect::<Vec<_>>(), [at(100)]);
+    let names: Vec<_> = store.load_cabs().unwrap().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["a"]);
+}
+
+#[tokio::test]
+async fn writer_saves_registry_changes() {
+    let registry = Arc::new(CabRegistry::new());
+    let store: Arc<dyn Store> = Arc::new(SqliteStore::open_in_memory().unwrap());
+    store::spawn_writer(registry.clone(), store.clone());
+
+    let recorded_at = registry.record("a", Location::new(37.5, 127.0), CabAttributes::default());
+    registry.set_status("a", CabStatus::OnTrip);
+    registry.expire(Duration::ZERO, recorded_at + Duration::from_secs(1));
+
+    let mut saved = Vec::new();
+    for _ in 0..100 {
+        saved = store.cab_history("a", recorded_at, recorded_at + Duration::from_millis(1), 100).unwrap();
+        if !saved.is_empty() && store.load_cabs().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(saved.len(), 1);
+    // 마지막 알림은 offline이므로 다시 띄울 때 넣지 않는다
+    assert!(store.load_cabs().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn finished_trips_are_readable_after_restart() {
+    let store: Arc<dyn Store> = Arc::new(SqliteStore::open_in_memory().unwrap());
+    let registry = Arc::new(CabRegistry::new());
+    registry.record("cab-1", Location::new(37.5, 127.0), CabAttributes::default());
+    let dispatch = DispatchService::new(registry.clone()).with_store(store.clone());
+
+    let ride = RideRequest {
+        rider_id: "rider-1".to_string(),
+        pickup: Some(Location::new(37.5, 127.0)),
+        ..RideRequest::default()
+    };
+    let trip = dispatch.request_ride(Request::new(ride)).await.unwrap().into_inner();
+    assert_eq!(store.load_trip(&trip.trip_id).unwrap().unwrap().state, TripState::Offered as i32);
+
+    let action = TripAction { trip_id: trip.trip_id.clone(), cab_name: "cab-1".to_string() };
+    dispatch.accept_trip(Request::new(action.clone())).await.unwrap();
+    dispatch.start_trip(Request::new(action.clone())).await.unwrap();
+    dispatch.complete_trip(Request::new(action)).await.unwrap();
+
+    let restarted = DispatchService::new(Arc::new(CabRegistry::new())).with_store(store);
+    let get = GetTripRequest { trip_id: trip.trip_id.clone() };
+    let stored = restarted.get_trip(Request::new(get)).await.unwrap().into_inner();
+    assert_eq!(stored.state, TripState::Completed as i32);
+    assert_eq!(stored.cab_name, "cab-1");
+}
+
+#[tokio::test]
+async fn trip_ids_are_not_reused_after_restart() {
+    let store: Arc<dyn Store> = Arc::new(SqliteStore::open_in_memory().unwrap());
+    let ride = RideRequest {
+        rider_id: "rider-1".to_string(),
+        pickup: Some(Location::new(37.5, 127.0)),
+        ..RideRequest::default()
+    };
+    // cab이 없으니 바로 NO_CAB_AVAILABLE로 끝난다
+    let dispatch = DispatchService::new(Arc::new(CabRegistry::new())).with_store(store.clone());
+    let before = dispatch.request_ride(Request::new(ride.clone())).await.unwrap().into_inner();
+
+    let registry = Arc::new(CabRegistry::new());
+    registry.record("cab-1", Location::new(37.5, 127.0), CabAttributes::default());
+    let restarted = DispatchService::new(registry).with_store(store.clone());
+    let after = restarted.request_ride(Request::new(ride)).await.unwrap().into_inner();
+    assert_ne!(after.trip_id, before.trip_id);
+
+    // 지난 trip은 덮어쓰지 않았다
+    let get = GetTripRequest { trip_id: before.trip_id.clone() };
+    let stored = restarted.get_trip(Request::new(get)).await.unwrap().into_inner();
+    assert_eq!(stored.state, TripState::NoCabAvailable as i32);
+    assert_eq!(store.load_trip(&after.trip_id).unwrap().unwrap().state, TripState::Offered as i32);
+}
+
+#[test]
+fn restore_frees_cabs_that_were_on_trip() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    let now = SystemTime::now();
+    store
+        .save_cabs(&[
+            change("busy", 37.50, CabStatus::OnTrip, now),
+            change("free", 37.51, CabStatus::Available, now),
+            change("off", 37.52, CabStatus::Offline, now),
+        ])
+        .unwrap();
+
+    let registry = CabRegistry::new();
+    assert_eq!(store::restore(&registry, &store).unwrap(), 2);
+    // 진행 중이던 trip은 다시 띄우면 이어지지 않는다
+    assert_eq!(registry.get("busy").unwrap().status, CabStatus::Available);
+    assert_eq!(registry.get("free").unwrap().status, CabStatus::Available);
+    assert!(registry.get("off").is_none());
+}
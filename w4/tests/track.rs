@@ -0,0 +1,149 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use tonic::{Code, Request};
+
+use learning_grpc::auth::{Claims, Role};
+use learning_grpc::hello::cab_service_server::CabService as _;
+use learning_grpc::hello::track_downsampling::Method;
+use learning_grpc::hello::{CabStatus, CabType, GetCabTrackRequest, Location, TrackDownsampling};
+use learning_grpc::registry::{CabChange, CabEntry, CabRegistry};
+use learning_grpc::service::CabService;
+use learning_grpc::sqlite::SqliteStore;
+use learning_grpc::store::{unix_millis, Store};
+
+const START_MS: i64 = 1_700_000_000_000;
+
+fn at(seconds: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis(START_MS as u64) + Duration::from_secs(seconds)
+}
+
+fn change(location: Location, updated_at: SystemTime) -> CabChange {
+    let entry =
+        CabEntry { location, cab_type: CabType::Standard, capacity: 4, status: CabStatus::Available, updated_at };
+    CabChange { name: "cab-1".to_string(), entry }
+}
+
+// 1초마다 북쪽으로 조금씩
+fn service_with_track(seconds: u64) -> (CabService, Arc<dyn Store>) {
+    let store: Arc<dyn Store> = Arc::new(SqliteStore::open_in_memory().unwrap());
+    let changes: Vec<_> = (0..seconds).map(|s| change(Location::new(37.5 + s as f64 * 1e-4, 127.0), at(s))).collect();
+    store.save_cabs(&changes).unwrap();
+    (CabService::new(Arc::new(CabRegistry::new())).with_store(store.clone()), store)
+}
+
+fn request(page_size: u32, downsampling: Option<Method>) -> GetCabTrackRequest {
+    GetCabTrackRequest {
+        cab_name: "cab-1".to_string(),
+        start_time_ms: START_MS,
+        end_time_ms: 0,
+        downsampling: downsampling.map(|method| TrackDownsampling { method: Some(method) }),
+        page_size,
+        page_token: String::new(),
+    }
+}
+
+// 모든 page를 읽고 (점의 시각(초), page 수)
+async fn read_all(service: &CabService, mut req: GetCabTrackRequest) -> (Vec<u64>, usize) {
+    let mut seconds = Vec::new();
+    let mut pages = 0;
+    loop {
+        let page = service.get_cab_track(Request::new(req.clone())).await.unwrap().into_inner();
+        pages += 1;
+        seconds.extend(page.points.iter().map(|p| ((p.recorded_at_ms - START_MS) / 1000) as u64));
+        if page.next_page_token.is_empty() {
+            return (seconds, pages);
+        }
+        req.page_token = page.next_page_token;
+    }
+}
+
+#[tokio::test]
+async fn pages_cover_the_range_in_order() {
+    let (service, store) = service_with_track(25);
+    let mut req = request(10, None);
+
+    let first = service.get_cab_track(Request::new(req.clone())).await.unwrap().into_inner();
+    assert_eq!(first.points.len(), 10);
+    assert_eq!(first.points[0].location.as_ref().unwrap().latitude, 37.5);
+    // 첫 요청 뒤에 들어온 위치는 나오지 않는다
+    store.save_cabs(&[change(Location::new(38.0, 127.0), SystemTime::now() + Duration::from_secs(60))]).unwrap();
+
+    req.page_token = first.next_page_token;
+    let (rest, pages) = read_all(&service, req).await;
+    assert_eq!(rest, (10..25).collect::<Vec<_>>());
+    assert_eq!(pages, 2);
+}
+
+#[tokio::test]
+async fn min_interval_keeps_spacing_across_pages() {
+    let (service, _) = service_with_track(61);
+    let (seconds, pages) = read_all(&service, request(3, Some(Method::MinIntervalMs(10_000)))).await;
+    assert_eq!(seconds, [0, 10, 20, 30, 40, 50, 60]);
+    assert_eq!(pages, 3);
+}
+
+#[tokio::test]
+async fn douglas_peucker_drops_points_on_a_straight_line() {
+    let (service, store) = service_with_track(30);
+    // 선에서 100m 넘게 벗어난 점 하나
+    store.save_cabs(&[change(Location::new(37.5 + 30e-4, 127.002), at(30))]).unwrap();
+    store.save_cabs(&[change(Location::new(37.5 + 31e-4, 127.0), at(31))]).unwrap();
+
+    let (seconds, _) = read_all(&service, request(0, Some(Method::ToleranceMeters(5.0)))).await;
+    assert_eq!(seconds, [0, 29, 30, 31]);
+    let (seconds, pages) = read_all(&service, request(2, Some(Method::ToleranceMeters(5.0)))).await;
+    assert_eq!((seconds, pages), (vec![0, 29, 30, 31], 2));
+}
+
+#[tokio::test]
+async fn bad_requests_are_rejected() {
+    let (service, _) = service_with_track(5);
+    let first = service.get_cab_track(Request::new(request(2, None))).await.unwrap().into_inner();
+
+    // 다른 cab의 token
+    let other = GetCabTrackRequest {
+        cab_name: "cab-2".to_string(),
+        page_token: first.next_page_token.clone(),
+        ..request(2, None)
+    };
+    let status = service.get_cab_track(Request::new(other)).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let broken = GetCabTrackRequest { page_token: "not a token".to_string(), ..request(2, None) };
+    let status = service.get_cab_track(Request::new(broken)).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let backwards = GetCabTrackRequest { end_time_ms: START_MS - 1, ..request(2, None) };
+    let status = service.get_cab_track(Request::new(backwards)).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let memory_only = CabService::default();
+    let status = memory_only.get_cab_track(Request::new(request(2, None))).await.unwrap_err();
+    assert_eq!(status.code(), Code::FailedPrecondition);
+}
+
+#[tokio::test]
+async fn only_operators_and_the_cab_driver_see_the_track() {
+    let (service, _) = service_with_track(3);
+    let claims = |role, cab: Option<&str>| Claims {
+        sub: "someone".to_string(),
+        role,
+        cab: cab.map(str::to_string),
+        exp: unix_millis(SystemTime::now()) as u64 / 1000 + 60,
+    };
+    for (claims, allowed) in [
+        (claims(Role::Operator, None), true),
+        (claims(Role::Driver, Some("cab-1")), true),
+        (claims(Role::Driver, Some("cab-2")), false),
+        (claims(Role::Rider, None), false),
+    ] {
+        let mut req = Request::new(request(10, None));
+        req.extensions_mut().insert(claims.clone());
+        let result = service.get_cab_track(req).await;
+        assert_eq!(result.is_ok(), allowed, "{:?}", claims);
+        if let Err(status) = result {
+            assert_eq!(status.code(), Code::PermissionDenied);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use tonic::body::BoxBody;
+use tonic::codegen::http::{Request as HttpRequest, Response as HttpResponse};
+use tonic::codegen::{BoxFuture, Context, Poll, Service};
+use tonic::transport::{Endpoint, NamedService, Server};
+use tonic::{Code, Status};
+
+use learning_grpc::hello::cab_service_server::CabServiceServer;
+use learning_grpc::hello::{HelloRequest, HelloResponse};
+use learning_grpc::legacy::LegacyRoutes;
+use learning_grpc::reflection::FILE_DESCRIPTOR_SET;
+use learning_grpc::service::CabService;
+use learning_grpc::unknown_methods::{self, method_paths, UnknownMethodStats, UnknownMethods, UnknownServicesLayer};
+
+use common::{call_path, closed_port, connect, incoming, url};
+
+// 받은 path를 적어서 NOT_FOUND로 답한다
+#[derive(Clone)]
+struct Fallback;
+
+impl<B> Service<HttpRequest<B>> for Fallback {
+    type Response = HttpResponse<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: HttpRequest<B>) -> Self::Future {
+        let status = Status::not_found(format!("fallback got {}", req.uri().path()));
+        Box::pin(async move { Ok(status.to_http()) })
+    }
+}
+
+async fn serve<S>(service: S) -> SocketAddr
+where
+    S: Service<
+            HttpRequest<tonic::transport::Body>,
+            Response = HttpResponse<BoxBody>,
+            Error = Infallible,
+            Future = BoxFuture<HttpResponse<BoxBody>, Infallible>,
+        > + NamedService
+        + Clone
+        + Send
+        + 'static,
+{
+    common::serve(Server::builder().add_service(service)).await
+}
+
+async fn call(addr: SocketAddr, path: &'static str) -> Result<HelloResponse, Status> {
+    call_path(connect(addr).await, path, HelloRequest {}).await
+}
+
+#[tokio::test]
+async fn unknown_methods_are_named_and_counted() {
+    let server = UnknownMethods::new(CabServiceServer::new(CabService::default()));
+    let stats = server.stats().clone();
+    let addr = serve(server).await;
+
+    assert_eq!(call(addr, "/cabs.v1.CabService/HelloWorld").await.unwrap().message, "Hello, World!");
+    for _ in 0..3 {
+        let status = call(addr, "/cabs.v1.CabService/NoSuchMethod").await.unwrap_err();
+        assert_eq!(status.code(), Code::Unimplemented);
+        assert_eq!(status.message(), "unknown method /cabs.v1.CabService/NoSuchMethod");
+    }
+    call(addr, "/cabs.v1.CabService/AnotherOne").await.unwrap_err();
+
+    assert_eq!(stats.count("/cabs.v1.CabService/NoSuchMethod"), 3);
+    assert_eq!(stats.count("/cabs.v1.CabService/HelloWorld"), 0);
+    assert_eq!(stats.total(), 4);
+}
+
+#[tokio::test]
+async fn unknown_methods_go_to_the_fallback() {
+    let stats = UnknownMethodStats::default();
+    let server = UnknownMethods::new(CabServiceServer::new(CabService::default()))
+        .with_fallback(Fallback)
+        .with_stats(stats.clone());
+    let addr = serve(server).await;
+
+    assert!(call(addr, "/cabs.v1.CabService/HelloWorld").await.is_ok());
+    let status = call(addr, "/cabs.v1.CabService/NoSuchMethod").await.unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+    assert_eq!(status.message(), "fallback got /cabs.v1.CabService/NoSuchMethod");
+    assert_eq!(stats.total(), 1);
+}
+
+#[tokio::test]
+async fn unknown_methods_are_forwarded_to_the_configured_server() {
+    let upstream = UnknownMethods::new(CabServiceServer::new(CabService::default()));
+    let upstream_stats = upstream.stats().clone();
+    let upstream_addr = serve(upstream).await;
+
+    let channel = Endpoint::from_shared(url(upstream_addr)).unwrap().connect_lazy();
+    let server = UnknownMethods::new(CabServiceServer::new(CabService::default()))
+        .with_fallback(unknown_methods::Fallback::Forward(channel));
+    let stats = server.stats().clone();
+    let addr = serve(LegacyRoutes::new(server)).await;
+
+    // 예전 path도 바뀐 path로 넘긴다
+    let status = call(addr, "/Hello.Hello/no_such_method").await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+    assert_eq!(status.message(), "unknown method /cabs.v1.CabService/no_such_method");
+    assert_eq!(stats.count("/cabs.v1.CabService/no_such_method"), 1);
+    assert_eq!(upstream_stats.count("/cabs.v1.CabService/no_such_method"), 1);
+}
+
+#[tokio::test]
+async fn unreachable_fallback_is_unavailable() {
+    let channel = Endpoint::from_shared(url(closed_port().await)).unwrap().connect_lazy();
+    let server = UnknownMethods::new(CabServiceServer::new(CabService::default()))
+        .with_fallback(unknown_methods::Fallback::Forward(channel));
+    let addr = serve(server).await;
+
+    let status = call(addr, "/cabs.v1.CabService/NoSuchMethod").await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    // 있는 method는 그대로 받는다
+    assert!(call(addr, "/cabs.v1.CabService/HelloWorld").await.is_ok());
+}
+
+#[tokio::test]
+async fn stats_are_shared_and_printable() {
+    let stats = UnknownMethodStats::default();
+    assert_eq!(stats.to_string(), "0");
+    let cab = UnknownMethods::new(CabServiceServer::new(CabService::default())).with_stats(stats.clone());
+    let legacy = UnknownMethods::new(CabServiceServer::new(CabService::default())).with_stats(stats.clone());
+    let cab_addr = serve(cab).await;
+    let legacy_addr = serve(LegacyRoutes::new(legacy)).await;
+
+    call(cab_addr, "/cabs.v1.CabService/B").await.unwrap_err();
+    call(legacy_addr, "/Hello.Hello/B").await.unwrap_err();
+    call(cab_addr, "/cabs.v1.CabService/A").await.unwrap_err();
+    assert_eq!(stats.to_string(), "3 (/cabs.v1.CabService/B: 2, /cabs.v1.CabService/A: 1)");
+}
+
+#[tokio::test]
+async fn unknown_services_are_counted_and_go_to_the_fallback() {
+    let stats = UnknownMethodStats::default();
+    let server = UnknownMethods::new(CabServiceServer::new(CabService::default())).with_stats(stats.clone());
+    let layer = UnknownServicesLayer::default().known(&server).with_fallback(Fallback).with_stats(stats.clone());
+    let (incoming, addr) = incoming().await;
+    tokio::spawn(Server::builder().layer(layer).add_service(server).serve_with_incoming(incoming));
+
+    assert!(call(addr, "/cabs.v1.CabService/HelloWorld").await.is_ok());
+    for _ in 0..2 {
+        let status = call(addr, "/cabs.v2.CabService/HelloWorld").await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "fallback got /cabs.v2.CabService/HelloWorld");
+    }
+    // 있는 서비스의 모르는 method는 전처럼 UnknownMethods가 받는다
+    assert_eq!(call(addr, "/cabs.v1.CabService/NoSuchMethod").await.unwrap_err().code(), Code::Unimplemented);
+
+    assert_eq!(stats.count("/cabs.v2.CabService/HelloWorld"), 2);
+    assert_eq!(stats.total(), 3);
+}
+
+#[tokio::test]
+async fn unknown_services_are_unimplemented_by_default() {
+    let server = CabServiceServer::new(CabService::default());
+    let stats = UnknownMethodStats::default();
+    let layer = UnknownServicesLayer::default().known(&server).with_stats(stats.clone());
+    let (incoming, addr) = incoming().await;
+    tokio::spawn(Server::builder().layer(layer).add_service(server).serve_with_incoming(incoming));
+
+    let status = call(addr, "/NoSuchService/HelloWorld").await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+    assert_eq!(status.message(), "unknown method /NoSuchService/HelloWorld");
+    assert_eq!(stats.total(), 1);
+}
+
+#[tokio::test]
+async fn old_paths_are_checked_after_renaming() {
+    let server = UnknownMethods::new(CabServiceServer::new(CabService::default()));
+    let stats = server.stats().clone();
+    let addr = serve(LegacyRoutes::new(server)).await;
+
+    assert!(call(addr, "/Hello.Hello/HelloWorld").await.is_ok());
+    let status = call(addr, "/Hello.Hello/no_such_method").await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+    assert_eq!(stats.count("/cabs.v1.CabService/no_such_method"), 1);
+}
+
+#[test]
+fn methods_come_from_the_descriptor() {
+    let paths = method_paths(FILE_DESCRIPTOR_SET, "cabs.v1.CabService").unwrap().unwrap();
+    assert!(paths.contains("/cabs.v1.CabService/LookupZones"));
+    assert!(!paths.contains("/cabs.v1.CabService/RequestRide"));
+    assert!(method_paths(FILE_DESCRIPTOR_SET, "Hello.Hello").unwrap().is_none());
+}
@@ -0,0 +1,78 @@
+use tonic::{Code, Request};
+
+use learning_grpc::hello::cab_service_server::CabService as _;
+use learning_grpc::hello::{CabLocationRequest, Location, RejectReason};
+use learning_grpc::service::CabService;
+use learning_grpc::validate::{self, field_violations, MAX_NAME_LEN};
+
+fn request(name: &str, location: Option<Location>) -> CabLocationRequest {
+    CabLocationRequest { name: name.to_string(), location, ..CabLocationRequest::default() }
+}
+
+fn fields(req: &CabLocationRequest) -> Vec<String> {
+    match validate::cab_location_request(req) {
+        Ok(_) => Vec::new(),
+        Err(invalid) => invalid.violations.into_iter().map(|v| v.field).collect(),
+    }
+}
+
+#[test]
+fn name_must_be_present_and_short() {
+    let here = Some(Location::new(37.5, 127.0));
+    assert!(fields(&request("cab-1", here.clone())).is_empty());
+    assert_eq!(fields(&request("", here.clone())), ["name"]);
+
+    // 바이트가 아니라 글자 수로 센다
+    let longest: String = "택".repeat(MAX_NAME_LEN);
+    assert!(fields(&request(&longest, here.clone())).is_empty());
+    let too_long = format!("{}x", longest);
+    let invalid = validate::cab_location_request(&request(&too_long, here)).unwrap_err();
+    assert_eq!(invalid.reason, RejectReason::InvalidName);
+}
+
+#[test]
+fn latitude_and_longitude_bounds() {
+    let at = |latitude, longitude| fields(&request("cab", Some(Location::new(latitude, longitude))));
+    assert!(at(90.0, 180.0).is_empty());
+    assert!(at(-90.0, -180.0).is_empty());
+    assert_eq!(at(90.5, 0.1), ["location.latitude"]);
+    assert_eq!(at(0.1, -180.5), ["location.longitude"]);
+    assert_eq!(at(f64::NAN, f64::INFINITY), ["location.latitude", "location.longitude"]);
+}
+
+#[test]
+fn reason_follows_the_first_violation() {
+    let invalid = validate::cab_location_request(&request("", None)).unwrap_err();
+    assert_eq!(invalid.reason, RejectReason::InvalidName);
+    let fields: Vec<_> = invalid.violations.iter().map(|v| v.field.as_str()).collect();
+    assert_eq!(fields, ["name", "location"]);
+
+    let invalid = validate::cab_location_request(&request("cab", None)).unwrap_err();
+    assert_eq!(invalid.reason, RejectReason::MissingLocation);
+    let invalid = validate::location("origin", Some(&Location::new(100.0, 0.0))).unwrap_err();
+    assert_eq!(invalid.reason, RejectReason::InvalidLocation);
+    assert_eq!(invalid.violations[0].field, "origin.latitude");
+}
+
+#[tokio::test]
+async fn unary_record_returns_invalid_argument_with_details() {
+    let service = CabService::default();
+    let status =
+        service.record_cab_location(Request::new(request("", Some(Location::new(95.0, 0.0))))).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "name: must not be empty; location.latitude: must be between -90 and 90");
+    let fields: Vec<_> = field_violations(&status).into_iter().map(|v| v.field).collect();
+    assert_eq!(fields, ["name", "location.latitude"]);
+    assert!(service.registry().get("").is_none());
+
+    // 통과하면 reject_reason은 비어 있다
+    let ok = service.record_cab_location(Request::new(request("cab-1", Some(Location::new(37.5, 127.0))))).await;
+    let ok = ok.unwrap().into_inner();
+    assert!(ok.accepted);
+    assert_eq!(ok.reject_reason, RejectReason::Unspecified as i32);
+}
+
+#[test]
+fn statuses_without_details_have_no_violations() {
+    assert!(field_violations(&tonic::Status::invalid_argument("plain")).is_empty());
+}
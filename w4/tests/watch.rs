@@ -0,0 +1,103 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio_stream::StreamExt;
+
+use learning_grpc::hello::watch_cabs_response::Event;
+use learning_grpc::hello::{CabSnapshot, CabStatus, CabUpdate, CabUpdateKind};
+use learning_grpc::registry::{CabAttributes, CabRegistry};
+use learning_grpc::watch::{self, WatchStream};
+
+use common::{north, origin, record_in as record};
+
+async fn next(stream: &mut WatchStream) -> Event {
+    let next = tokio::time::timeout(Duration::from_secs(3), stream.next()).await.expect("no event");
+    next.unwrap().unwrap().event.unwrap()
+}
+
+async fn next_snapshot(stream: &mut WatchStream) -> CabSnapshot {
+    match next(stream).await {
+        Event::Snapshot(snapshot) => snapshot,
+        other => panic!("expected a snapshot, got {:?}", other),
+    }
+}
+
+async fn next_update(stream: &mut WatchStream) -> (CabUpdateKind, String) {
+    match next(stream).await {
+        Event::Update(CabUpdate { kind, cab }) => (CabUpdateKind::from_i32(kind).unwrap(), cab.unwrap().name),
+        other => panic!("expected an update, got {:?}", other),
+    }
+}
+
+fn update(kind: CabUpdateKind, name: &str) -> (CabUpdateKind, String) {
+    (kind, name.to_string())
+}
+
+#[tokio::test]
+async fn snapshot_then_enter_move_leave() {
+    let registry = Arc::new(CabRegistry::new());
+    record(&registry, "inside", north(100.0));
+    record(&registry, "outside", north(5_000.0));
+    registry.record("offline", north(200.0), CabAttributes { status: CabStatus::Offline, ..Default::default() });
+
+    let mut stream = watch::watch(registry.clone(), origin(), 1_000.0);
+    let snapshot = next_snapshot(&mut stream).await;
+    let names: Vec<_> = snapshot.cabs.iter().map(|cab| cab.name.as_str()).collect();
+    assert_eq!(names, ["inside"]);
+
+    // 밖에서 밖으로 움직인 것은 오지 않는다
+    record(&registry, "outside", north(4_000.0));
+    record(&registry, "outside", north(500.0));
+    assert_eq!(next_update(&mut stream).await, update(CabUpdateKind::Enter, "outside"));
+    record(&registry, "inside", north(150.0));
+    assert_eq!(next_update(&mut stream).await, update(CabUpdateKind::Move, "inside"));
+    record(&registry, "inside", north(2_000.0));
+    assert_eq!(next_update(&mut stream).await, update(CabUpdateKind::Leave, "inside"));
+    record(&registry, "new", north(10.0));
+    assert_eq!(next_update(&mut stream).await, update(CabUpdateKind::Enter, "new"));
+}
+
+#[tokio::test]
+async fn offline_and_expired_cabs_leave() {
+    let registry = Arc::new(CabRegistry::new());
+    record(&registry, "resting", north(100.0));
+    record(&registry, "silent", north(200.0));
+    let mut stream = watch::watch(registry.clone(), origin(), 1_000.0);
+    assert_eq!(next_snapshot(&mut stream).await.cabs.len(), 2);
+
+    registry.set_status("resting", CabStatus::Offline);
+    assert_eq!(next_update(&mut stream).await, update(CabUpdateKind::Leave, "resting"));
+
+    // 다시 보내기 시작하면 들어온다
+    registry.record("resting", north(100.0), CabAttributes { status: CabStatus::Available, ..Default::default() });
+    assert_eq!(next_update(&mut stream).await, update(CabUpdateKind::Enter, "resting"));
+
+    let later = std::time::SystemTime::now() + Duration::from_secs(120);
+    assert_eq!(registry.expire(Duration::from_secs(60), later).len(), 2);
+    let mut left = vec![next_update(&mut stream).await, next_update(&mut stream).await];
+    left.sort();
+    assert_eq!(left, [update(CabUpdateKind::Leave, "resting"), update(CabUpdateKind::Leave, "silent")]);
+}
+
+#[tokio::test]
+async fn lagging_watcher_gets_a_fresh_snapshot() {
+    let registry = Arc::new(CabRegistry::new());
+    let mut stream = watch::watch(registry.clone(), origin(), 1_000.0);
+    assert!(next_snapshot(&mut stream).await.cabs.is_empty());
+
+    // 읽지 않는 동안 알림 buffer보다 많이 바뀐다
+    for i in 0..3_000 {
+        record(&registry, &format!("cab-{}", i % 10), north((i % 7) as f64 * 10.0));
+    }
+    let mut got_snapshot = false;
+    for _ in 0..100 {
+        if let Event::Snapshot(snapshot) = next(&mut stream).await {
+            assert_eq!(snapshot.cabs.len(), 10);
+            got_snapshot = true;
+            break;
+        }
+    }
+    assert!(got_snapshot);
+}
@@ -0,0 +1,242 @@
+//! `GetCabTrack`. 저장해 둔 위치 기록을 줄이고 page로 나눈다.
+//!
+//! page token에는 첫 요청 때 정한 끝 시각과 마지막으로 준 점의 시각이 들어 있어서,
+//! 그 사이에 위치가 더 들어와도 page가 밀리거나 겹치지 않는다.
+
+use std::fmt;
+use std::time::SystemTime;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use geo::{Coord, LineString, SimplifyIdx};
+use prost::Message;
+use tonic::Status;
+
+use crate::distance::EARTH_RADIUS_METERS;
+use crate::hello::track_downsampling::Method;
+use crate::hello::{CabTrackPoint, GetCabTrackRequest, GetCabTrackResponse, Location, TrackDownsampling};
+use crate::store::{from_unix_millis, unix_millis, Store, StoreError, TrackPoint};
+
+pub const DEFAULT_PAGE_SIZE: usize = 500;
+pub const MAX_PAGE_SIZE: usize = 5000;
+/// Douglas-Peucker는 범위 전체를 한 번에 봐야 한다. 이보다 많으면 범위를 줄여 달라고 한다.
+pub const MAX_SIMPLIFY_POINTS: usize = 100_000;
+
+// 간격으로 줄이거나 줄이지 않을 때 저장소에서 한 번에 읽는 수
+const READ_CHUNK: usize = 1000;
+
+// 클라이언트에게는 base64 문자열로만 보인다
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct PageToken {
+    #[prost(string, tag = "1")]
+    cab_name: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    start_time_ms: i64,
+    #[prost(int64, tag = "3")]
+    end_time_ms: i64,
+    #[prost(message, optional, tag = "4")]
+    downsampling: ::core::option::Option<TrackDownsampling>,
+    /// 앞 page의 마지막 점. 이 뒤부터 준다.
+    #[prost(int64, tag = "5")]
+    after_ms: i64,
+}
+
+#[derive(Debug)]
+pub enum TrackError {
+    /// 요청이 잘못됐다. `INVALID_ARGUMENT`.
+    Invalid(String),
+    /// Douglas-Peucker로 줄이기에는 점이 너무 많다.
+    TooManyPoints,
+    Store(StoreError),
+}
+
+impl fmt::Display for TrackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackError::Invalid(message) => f.write_str(message),
+            TrackError::TooManyPoints => write!(
+                f,
+                "time range has more than {} points, narrow it or use min_interval_ms",
+                MAX_SIMPLIFY_POINTS
+            ),
+            TrackError::Store(e) => write!(f, "failed to read location history: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TrackError {}
+
+impl From<StoreError> for TrackError {
+    fn from(e: StoreError) -> Self {
+        TrackError::Store(e)
+    }
+}
+
+impl From<TrackError> for Status {
+    fn from(e: TrackError) -> Self {
+        match e {
+            TrackError::Invalid(_) => Status::invalid_argument(e.to_string()),
+            TrackError::TooManyPoints => Status::failed_precondition(e.to_string()),
+            TrackError::Store(_) => Status::internal(e.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Downsampling {
+    None,
+    MinInterval(i64),
+    ToleranceMeters(f64),
+}
+
+/// 검사를 마친 요청. 어디부터 읽을지도 정해져 있다.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackQuery {
+    token: PageToken,
+    downsampling: Downsampling,
+    page_size: usize,
+    // 첫 page면 None
+    after_ms: Option<i64>,
+}
+
+impl TrackQuery {
+    /// `end_time_ms`가 0이면 `now`까지. page token이 있으면 첫 요청과 같은지 본다.
+    pub fn from_request(req: &GetCabTrackRequest, now: SystemTime) -> Result<Self, TrackError> {
+        if req.cab_name.is_empty() {
+            return Err(TrackError::Invalid("cab_name is required".to_string()));
+        }
+        let downsampling = match req.downsampling.as_ref().and_then(|d| d.method.as_ref()) {
+            None => Downsampling::None,
+            Some(Method::MinIntervalMs(ms)) if *ms > 0 => Downsampling::MinInterval(*ms),
+            Some(Method::ToleranceMeters(m)) if m.is_finite() && *m > 0.0 => Downsampling::ToleranceMeters(*m),
+            Some(_) => return Err(TrackError::Invalid("downsampling must be positive".to_string())),
+        };
+        let page_size = match req.page_size as usize {
+            0 => DEFAULT_PAGE_SIZE,
+            size => size.min(MAX_PAGE_SIZE),
+        };
+
+        let (token, after_ms) = if req.page_token.is_empty() {
+            let end_time_ms = if req.end_time_ms == 0 { unix_millis(now) } else { req.end_time_ms };
+            let token = PageToken {
+                cab_name: req.cab_name.clone(),
+                start_time_ms: req.start_time_ms,
+                end_time_ms,
+                downsampling: req.downsampling.clone(),
+                after_ms: 0,
+            };
+            (token, None)
+        } else {
+            let token = decode_token(&req.page_token)?;
+            let same = token.cab_name == req.cab_name
+                && token.start_time_ms == req.start_time_ms
+                && (req.end_time_ms == 0 || token.end_time_ms == req.end_time_ms)
+                && token.downsampling == req.downsampling;
+            if !same {
+                return Err(TrackError::Invalid("page_token does not match the request".to_string()));
+            }
+            let after_ms = token.after_ms;
+            (token, Some(after_ms))
+        };
+        if token.end_time_ms <= token.start_time_ms {
+            return Err(TrackError::Invalid("end_time_ms must be after start_time_ms".to_string()));
+        }
+
+        Ok(Self { token, downsampling, page_size, after_ms })
+    }
+
+    /// 한 page를 읽는다. 저장소를 읽으므로 막힌다.
+    pub fn read_page(&self, store: &dyn Store) -> Result<GetCabTrackResponse, TrackError> {
+        let (points, more) = match self.downsampling {
+            Downsampling::ToleranceMeters(tolerance) => self.read_simplified(store, tolerance)?,
+            Downsampling::None => self.read_every(store, 0)?,
+            Downsampling::MinInterval(interval) => self.read_every(store, interval)?,
+        };
+
+        let next_page_token = match points.last() {
+            Some(last) if more => {
+                let token = PageToken { after_ms: unix_millis(last.recorded_at), ..self.token.clone() };
+                URL_SAFE_NO_PAD.encode(token.encode_to_vec())
+            }
+            _ => String::new(),
+        };
+        let points = points
+            .into_iter()
+            .map(|p| CabTrackPoint { location: Some(p.location), recorded_at_ms: unix_millis(p.recorded_at) })
+            .collect();
+        Ok(GetCabTrackResponse { points, next_page_token })
+    }
+
+    // 앞에 남긴 점에서 `interval`(밀리초) 이상 지난 점만 남긴다. 0이면 다 남긴다
+    fn read_every(&self, store: &dyn Store, interval: i64) -> Result<(Vec<TrackPoint>, bool), TrackError> {
+        let mut points = Vec::new();
+        let mut last_kept = self.after_ms;
+        let mut from = self.after_ms.map_or(self.token.start_time_ms, |after| after + 1);
+        loop {
+            let chunk = self.history(store, from, READ_CHUNK)?;
+            let read_all = chunk.len() < READ_CHUNK;
+            for point in chunk {
+                let at = unix_millis(point.recorded_at);
+                from = at + 1;
+                if last_kept.is_some_and(|kept| at - kept < interval) {
+                    continue;
+                }
+                // 한 page를 채우고도 남는 점이 있으면 다음 page가 있다
+                if points.len() == self.page_size {
+                    return Ok((points, true));
+                }
+                last_kept = Some(at);
+                points.push(point);
+            }
+            if read_all {
+                return Ok((points, false));
+            }
+        }
+    }
+
+    fn read_simplified(&self, store: &dyn Store, tolerance: f64) -> Result<(Vec<TrackPoint>, bool), TrackError> {
+        let all = self.history(store, self.token.start_time_ms, MAX_SIMPLIFY_POINTS + 1)?;
+        if all.len() > MAX_SIMPLIFY_POINTS {
+            return Err(TrackError::TooManyPoints);
+        }
+        let mut kept = simplify(all, tolerance)
+            .into_iter()
+            .filter(|p| self.after_ms.is_none_or(|after| unix_millis(p.recorded_at) > after))
+            .collect::<Vec<_>>();
+        let more = kept.len() > self.page_size;
+        kept.truncate(self.page_size);
+        Ok((kept, more))
+    }
+
+    fn history(&self, store: &dyn Store, from_ms: i64, limit: usize) -> Result<Vec<TrackPoint>, TrackError> {
+        let (from, to) = (from_unix_millis(from_ms), from_unix_millis(self.token.end_time_ms));
+        Ok(store.cab_history(&self.token.cab_name, from, to, limit)?)
+    }
+}
+
+fn decode_token(token: &str) -> Result<PageToken, TrackError> {
+    let invalid = || TrackError::Invalid("page_token is malformed".to_string());
+    let bytes = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+    PageToken::decode(bytes.as_slice()).map_err(|_| invalid())
+}
+
+/// Douglas-Peucker. 처음과 마지막 점은 항상 남는다.
+/// 짧은 구간이라고 보고 첫 점 기준 평면(미터)에 펴서 계산한다.
+pub fn simplify(points: Vec<TrackPoint>, tolerance_meters: f64) -> Vec<TrackPoint> {
+    let origin = match points.first() {
+        Some(first) => first.location.clone(),
+        None => return points,
+    };
+    let line: LineString<f64> = points.iter().map(|p| to_plane(&origin, &p.location)).collect();
+    let kept = line.simplify_idx(&tolerance_meters);
+
+    let mut points = points.into_iter().map(Some).collect::<Vec<_>>();
+    kept.into_iter().filter_map(|i| points[i].take()).collect()
+}
+
+// 경도 1도의 길이는 위도에 따라 줄어든다
+fn to_plane(origin: &Location, location: &Location) -> Coord<f64> {
+    let x = (location.longitude - origin.longitude).to_radians() * origin.latitude.to_radians().cos();
+    let y = (location.latitude - origin.latitude).to_radians();
+    Coord { x: x * EARTH_RADIUS_METERS, y: y * EARTH_RADIUS_METERS }
+}
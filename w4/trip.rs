@@ -0,0 +1,65 @@
+use std::fmt;
+
+use crate::hello::{TripParty, TripState};
+
+/// trip 상태를 바꾸는 일.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TripEvent {
+    /// 다음 cab에게 제안했다.
+    Offer,
+    /// 더 제안할 cab이 없다.
+    NoCab,
+    Accept,
+    /// 기사가 거절했거나 제안 시간이 지났다. 다음 cab을 찾는다.
+    Decline,
+    Start,
+    Complete,
+    Cancel(TripParty),
+}
+
+/// 지금 상태에서 할 수 없는 일.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub from: TripState,
+    pub event: TripEvent,
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot {:?} a trip in state {:?}", self.event, self.from)
+    }
+}
+
+impl std::error::Error for InvalidTransition {}
+
+/// 상태가 바뀌는 길은 여기 있는 것뿐이다.
+///
+/// ```text
+/// SEARCHING --Offer--> OFFERED --Accept--> ACCEPTED --Start--> IN_PROGRESS --Complete--> COMPLETED
+///     |  ^                |
+///     |  +----Decline-----+
+///     +--NoCab--> NO_CAB_AVAILABLE
+///
+/// SEARCHING, OFFERED, ACCEPTED --Cancel--> CANCELLED
+/// ```
+pub fn transition(from: TripState, event: TripEvent) -> Result<TripState, InvalidTransition> {
+    use TripEvent::*;
+    use TripState::*;
+
+    let to = match (from, event) {
+        (Searching, Offer) => Offered,
+        (Searching, NoCab) => NoCabAvailable,
+        (Offered, Accept) => Accepted,
+        (Offered, Decline) => Searching,
+        (Accepted, Start) => InProgress,
+        (InProgress, Complete) => Completed,
+        (Searching | Offered | Accepted, Cancel(TripParty::Rider | TripParty::Driver)) => Cancelled,
+        _ => return Err(InvalidTransition { from, event }),
+    };
+    Ok(to)
+}
+
+/// 더 바뀔 일이 없는 상태.
+pub fn is_finished(state: TripState) -> bool {
+    matches!(state, TripState::Completed | TripState::Cancelled | TripState::NoCabAvailable)
+}
@@ -0,0 +1,339 @@
+//! 서비스에 없는 method로 온 요청.
+//!
+//! 생성된 서버는 모르는 method에 grpc-message도 없이 UNIMPLEMENTED만 주고, 아무것도 남기지 않는다.
+//! `UnknownMethods`로 감싸면 descriptor에 없는 method를 먼저 가로채서 세고 로그를 남긴 뒤
+//! `Unimplemented`로 path를 적어서 답한다. `with_fallback`으로 다른 서비스에 넘길 수도 있어서,
+//! 옮기는 동안 새 구현이 아직 없는 method는 예전 구현이 받게 할 수 있다. 서버에서는 `Fallback`으로 고른다.
+//!
+//! 서버에 아예 없는 서비스(예를 들어 다른 `cabs.vN` package)로 온 요청은 router가 바로 답해 버리므로
+//! `UnknownServicesLayer`를 `Server::builder().layer(...)`로 붙여서 같은 방법으로 세고 넘긴다.
+
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use prost::Message;
+use prost_types::FileDescriptorSet;
+use tonic::body::BoxBody;
+use tonic::codegen::http::{Request, Response};
+use tonic::codegen::{poll_fn, Body as _, BoxFuture, Context, Poll, Service};
+use tonic::transport::{Body, Channel, NamedService};
+use tonic::Status;
+use tower_layer::Layer;
+
+use crate::legacy::Renamed;
+use crate::reflection::FILE_DESCRIPTOR_SET;
+
+// 아무 path나 보내는 클라이언트가 있어도 메모리가 늘지 않게, 이보다 많은 path는 합쳐서 센다
+const MAX_COUNTED_PATHS: usize = 64;
+
+/// 모르는 method로 온 요청 수.
+#[derive(Debug, Clone, Default)]
+pub struct UnknownMethodStats {
+    counts: Arc<Mutex<Counts>>,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    by_path: HashMap<String, u64>,
+    // `by_path`가 가득 찬 뒤에 처음 본 path
+    other: u64,
+}
+
+impl UnknownMethodStats {
+    // 처음 본 path면 로그를 남긴다
+    fn record_and_warn(&self, path: &str) {
+        if self.record(path) {
+            println!("WARNING: unknown method {} called, further calls are only counted", path);
+        }
+    }
+
+    /// 처음 본 path면 true.
+    fn record(&self, path: &str) -> bool {
+        let mut counts = self.counts.lock().unwrap();
+        if let Some(count) = counts.by_path.get_mut(path) {
+            *count += 1;
+            return false;
+        }
+        if counts.by_path.len() >= MAX_COUNTED_PATHS {
+            counts.other += 1;
+            return false;
+        }
+        counts.by_path.insert(path.to_string(), 1);
+        true
+    }
+
+    pub fn total(&self) -> u64 {
+        let counts = self.counts.lock().unwrap();
+        counts.by_path.values().sum::<u64>() + counts.other
+    }
+
+    /// path 하나의 수. 따로 세지 못한 path는 0.
+    pub fn count(&self, path: &str) -> u64 {
+        self.counts.lock().unwrap().by_path.get(path).copied().unwrap_or(0)
+    }
+}
+
+/// 전체 수와, 많이 온 path부터 path마다의 수. 서버를 내릴 때 찍는다.
+impl fmt::Display for UnknownMethodStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = self.counts.lock().unwrap();
+        let mut paths: Vec<_> = counts.by_path.iter().collect();
+        paths.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let total = paths.iter().map(|(_, count)| **count).sum::<u64>() + counts.other;
+        write!(f, "{}", total)?;
+        if total == 0 {
+            return Ok(());
+        }
+        let mut parts: Vec<_> = paths.iter().map(|(path, count)| format!("{}: {}", path, count)).collect();
+        if counts.other > 0 {
+            parts.push(format!("other paths: {}", counts.other));
+        }
+        write!(f, " ({})", parts.join(", "))
+    }
+}
+
+/// `service`(package까지 붙인 이름)의 method path. descriptor에 없는 서비스면 `None`.
+pub fn method_paths(descriptor_set: &[u8], service: &str) -> Result<Option<HashSet<String>>, prost::DecodeError> {
+    let set = FileDescriptorSet::decode(descriptor_set)?;
+    for file in &set.file {
+        for found in &file.service {
+            let name = if file.package().is_empty() {
+                found.name().to_string()
+            } else {
+                format!("{}.{}", file.package(), found.name())
+            };
+            if name == service {
+                let paths = found.method.iter().map(|method| format!("/{}/{}", name, method.name())).collect();
+                return Ok(Some(paths));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// path를 적어서 UNIMPLEMENTED로 답한다. `UnknownMethods`의 기본 fallback.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Unimplemented;
+
+impl<B> Service<Request<B>> for Unimplemented {
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let status = Status::unimplemented(format!("unknown method {}", req.uri().path()));
+        Box::pin(async move { Ok(status.to_http()) })
+    }
+}
+
+/// 설정으로 고르는 fallback.
+#[derive(Debug, Clone)]
+pub enum Fallback {
+    Unimplemented,
+    /// 다른 서버(예전 구현)에 그대로 넘긴다. `LegacyRoutes` 뒤에 있으면 바뀐 path로 넘어간다.
+    Forward(Channel),
+}
+
+impl Service<Request<Body>> for Fallback {
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    // channel은 넘길 때 준비되기를 기다린다
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let mut channel = match self {
+            Fallback::Unimplemented => return Unimplemented.call(req),
+            Fallback::Forward(channel) => channel.clone(),
+        };
+        let req = req.map(|body| body.map_err(body_error).boxed_unsync());
+        Box::pin(async move {
+            let forwarded = async {
+                poll_fn(|cx| channel.poll_ready(cx)).await?;
+                channel.call(req).await
+            };
+            match forwarded.await {
+                Ok(response) => Ok(response.map(|body| body.map_err(body_error).boxed_unsync())),
+                Err(e) => Ok(Status::unavailable(format!("fallback failed: {}", e)).to_http()),
+            }
+        })
+    }
+}
+
+// 넘기는 중에 body가 끊기면 stream 오류로 보인다
+fn body_error(e: hyper::Error) -> Status {
+    Status::unavailable(format!("fallback body: {}", e))
+}
+
+/// `inner`에 없는 method를 세고 `fallback`에 넘긴다.
+#[derive(Debug, Clone)]
+pub struct UnknownMethods<S, F = Unimplemented> {
+    inner: S,
+    fallback: F,
+    methods: Arc<HashSet<String>>,
+    stats: UnknownMethodStats,
+}
+
+impl<S: NamedService> UnknownMethods<S> {
+    /// 어떤 method가 있는지는 `FILE_DESCRIPTOR_SET`에서 찾는다.
+    pub fn new(inner: S) -> Self {
+        let methods = method_paths(FILE_DESCRIPTOR_SET, S::NAME)
+            .expect("descriptor.bin is generated with hello.rs")
+            .unwrap_or_else(|| panic!("{} is not in descriptor.bin", S::NAME));
+        Self { inner, fallback: Unimplemented, methods: Arc::new(methods), stats: UnknownMethodStats::default() }
+    }
+}
+
+impl<S, F> UnknownMethods<S, F> {
+    /// 모르는 method를 UNIMPLEMENTED 대신 `fallback`에 넘긴다.
+    pub fn with_fallback<G>(self, fallback: G) -> UnknownMethods<S, G> {
+        UnknownMethods { inner: self.inner, fallback, methods: self.methods, stats: self.stats }
+    }
+
+    /// 여러 서비스를 같이 세려면 같은 것을 준다.
+    pub fn with_stats(mut self, stats: UnknownMethodStats) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    pub fn stats(&self) -> &UnknownMethodStats {
+        &self.stats
+    }
+}
+
+impl<S: NamedService, F> NamedService for UnknownMethods<S, F> {
+    const NAME: &'static str = S::NAME;
+}
+
+// `LegacyRoutes`로 한 번 더 감쌀 수 있다
+impl<S: Renamed, F> Renamed for UnknownMethods<S, F> {
+    const OLD_NAME: &'static str = S::OLD_NAME;
+    const OLD_METHODS: &'static [(&'static str, &'static str)] = S::OLD_METHODS;
+}
+
+impl<S, F, B> Service<Request<B>> for UnknownMethods<S, F>
+where
+    S: Service<Request<B>, Response = Response<BoxBody>>,
+    S::Future: Send + 'static,
+    F: Service<Request<B>, Response = Response<BoxBody>, Error = S::Error> + Clone + Send + 'static,
+    F::Future: Send,
+    B: Send + 'static,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    // fallback은 넘길 때 준비되기를 기다린다. 그래야 fallback이 막혀도 있는 method는 받는다
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let path = req.uri().path();
+        if self.methods.contains(path) {
+            return Box::pin(self.inner.call(req));
+        }
+        self.stats.record_and_warn(path);
+        let mut fallback = self.fallback.clone();
+        Box::pin(async move {
+            poll_fn(|cx| fallback.poll_ready(cx)).await?;
+            fallback.call(req).await
+        })
+    }
+}
+
+/// 서버에 없는 서비스로 온 요청을 `UnknownMethods`와 같이 세고 `fallback`에 넘기는 layer.
+/// `known`에 준 서비스로 온 요청만 router에 넘긴다.
+#[derive(Debug, Clone)]
+pub struct UnknownServicesLayer<F = Unimplemented> {
+    services: Arc<HashSet<&'static str>>,
+    fallback: F,
+    stats: UnknownMethodStats,
+}
+
+impl Default for UnknownServicesLayer {
+    fn default() -> Self {
+        Self { services: Arc::default(), fallback: Unimplemented, stats: UnknownMethodStats::default() }
+    }
+}
+
+impl<F> UnknownServicesLayer<F> {
+    /// 서버에 추가한 서비스. 추가한 것을 모두 줘야 한다.
+    pub fn known<S: NamedService>(mut self, _service: &S) -> Self {
+        Arc::make_mut(&mut self.services).insert(S::NAME);
+        self
+    }
+
+    pub fn with_fallback<G>(self, fallback: G) -> UnknownServicesLayer<G> {
+        UnknownServicesLayer { services: self.services, fallback, stats: self.stats }
+    }
+
+    /// `UnknownMethods`와 같은 것을 주면 같이 센다.
+    pub fn with_stats(mut self, stats: UnknownMethodStats) -> Self {
+        self.stats = stats;
+        self
+    }
+}
+
+impl<S, F: Clone> Layer<S> for UnknownServicesLayer<F> {
+    type Service = UnknownServices<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        UnknownServices {
+            inner,
+            fallback: self.fallback.clone(),
+            services: self.services.clone(),
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+/// `UnknownServicesLayer`가 router를 감싼 것.
+#[derive(Debug, Clone)]
+pub struct UnknownServices<S, F> {
+    inner: S,
+    fallback: F,
+    services: Arc<HashSet<&'static str>>,
+    stats: UnknownMethodStats,
+}
+
+impl<S, F, B> Service<Request<B>> for UnknownServices<S, F>
+where
+    S: Service<Request<B>, Response = Response<BoxBody>>,
+    S::Future: Send + 'static,
+    F: Service<Request<B>, Response = Response<BoxBody>> + Clone + Send + 'static,
+    F::Error: Into<S::Error>,
+    F::Future: Send,
+    B: Send + 'static,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let path = req.uri().path();
+        let service = path.split('/').nth(1).unwrap_or_default();
+        if self.services.contains(service) {
+            return Box::pin(self.inner.call(req));
+        }
+        self.stats.record_and_warn(path);
+        let mut fallback = self.fallback.clone();
+        Box::pin(async move {
+            poll_fn(|cx| fallback.poll_ready(cx)).await.map_err(Into::into)?;
+            fallback.call(req).await.map_err(Into::into)
+        })
+    }
+}
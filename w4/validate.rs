@@ -0,0 +1,165 @@
+use bytes::Bytes;
+use prost::Message;
+use tonic::{Code, Status};
+
+use crate::hello::{CabLocationRequest, Location, RejectReason};
+
+pub const MAX_NAME_LEN: usize = 64;
+
+// google/rpc/status.proto, google/rpc/error_details.proto에서 쓰는 것만 옮겨 왔다.
+// 클라이언트는 grpc-status-details-bin을 google.rpc.Status로 읽으면 된다.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    pub code: i32,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "3")]
+    pub details: ::prost::alloc::vec::Vec<Any>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Any {
+    #[prost(string, tag = "1")]
+    pub type_url: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BadRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub field_violations: ::prost::alloc::vec::Vec<FieldViolation>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FieldViolation {
+    #[prost(string, tag = "1")]
+    pub field: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub description: ::prost::alloc::string::String,
+}
+
+pub const BAD_REQUEST_TYPE_URL: &str = "type.googleapis.com/google.rpc.BadRequest";
+
+/// 검사에 걸린 요청. `reason`은 처음 걸린 필드 기준.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invalid {
+    pub reason: RejectReason,
+    pub violations: Vec<FieldViolation>,
+}
+
+impl Invalid {
+    fn new() -> Self {
+        Self { reason: RejectReason::Unspecified, violations: Vec::new() }
+    }
+
+    fn add(&mut self, reason: RejectReason, field: &str, description: impl Into<String>) {
+        if self.violations.is_empty() {
+            self.reason = reason;
+        }
+        self.violations.push(FieldViolation { field: field.to_string(), description: description.into() });
+    }
+
+    fn into_result<T>(self, value: Option<T>) -> Result<T, Invalid> {
+        match value {
+            Some(value) if self.violations.is_empty() => Ok(value),
+            _ => Err(self),
+        }
+    }
+}
+
+/// INVALID_ARGUMENT에 `BadRequest`를 details로 붙인다.
+impl From<Invalid> for Status {
+    fn from(invalid: Invalid) -> Self {
+        let message = invalid
+            .violations
+            .iter()
+            .map(|v| format!("{}: {}", v.field, v.description))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let bad_request = BadRequest { field_violations: invalid.violations };
+        let details = RpcStatus {
+            code: Code::InvalidArgument as i32,
+            message: message.clone(),
+            details: vec![Any { type_url: BAD_REQUEST_TYPE_URL.to_string(), value: bad_request.encode_to_vec() }],
+        };
+        Status::with_details(Code::InvalidArgument, message, Bytes::from(details.encode_to_vec()))
+    }
+}
+
+/// `grpc-status-details-bin`에서 field violation을 꺼낸다. 없거나 깨졌으면 빈 것.
+pub fn field_violations(status: &Status) -> Vec<FieldViolation> {
+    RpcStatus::decode(status.details())
+        .map(|details| {
+            details
+                .details
+                .iter()
+                .filter(|any| any.type_url == BAD_REQUEST_TYPE_URL)
+                .filter_map(|any| BadRequest::decode(any.value.as_slice()).ok())
+                .flat_map(|bad_request| bad_request.field_violations)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 통과하면 요청의 위치를 `Location::normalized`해서 돌려준다.
+pub fn cab_location_request(req: &CabLocationRequest) -> Result<Location, Invalid> {
+    let mut invalid = Invalid::new();
+    if req.name.is_empty() {
+        invalid.add(RejectReason::InvalidName, "name", "must not be empty");
+    } else if req.name.chars().count() > MAX_NAME_LEN {
+        invalid.add(RejectReason::InvalidName, "name", format!("must be at most {} characters", MAX_NAME_LEN));
+    }
+    let location = check_location(&mut invalid, "location", req.location.as_ref());
+    invalid.into_result(location)
+}
+
+/// 검색 요청처럼 위치만 보는 곳에서 쓴다.
+pub fn location(field: &str, location: Option<&Location>) -> Result<Location, Invalid> {
+    let mut invalid = Invalid::new();
+    let location = check_location(&mut invalid, field, location);
+    invalid.into_result(location)
+}
+
+fn check_location(invalid: &mut Invalid, field: &str, location: Option<&Location>) -> Option<Location> {
+    let location = match location {
+        Some(location) => location.normalized(),
+        None => {
+            invalid.add(RejectReason::MissingLocation, field, "is required");
+            return None;
+        }
+    };
+    // NaN은 범위 비교에서 항상 false라 같이 걸러진다
+    if !(-90.0..=90.0).contains(&location.latitude) {
+        invalid.add(
+            RejectReason::InvalidLocation,
+            &format!("{}.latitude", field),
+            "must be between -90 and 90",
+        );
+    }
+    if !(-180.0..=180.0).contains(&location.longitude) {
+        invalid.add(
+            RejectReason::InvalidLocation,
+            &format!("{}.longitude", field),
+            "must be between -180 and 180",
+        );
+    }
+    if location.heading_degrees.is_some_and(|heading| !(0.0..360.0).contains(&heading)) {
+        invalid.add(
+            RejectReason::InvalidLocation,
+            &format!("{}.heading_degrees", field),
+            "must be in [0, 360)",
+        );
+    }
+    for (name, value) in [("speed_mps", location.speed_mps), ("accuracy_meters", location.accuracy_meters)] {
+        if value.is_some_and(|value| !(value.is_finite() && value >= 0.0)) {
+            invalid.add(
+                RejectReason::InvalidLocation,
+                &format!("{}.{}", field, name),
+                "must be a non-negative number",
+            );
+        }
+    }
+    Some(location)
+}
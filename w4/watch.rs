@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Status;
+
+use crate::distance::distance_meters;
+use crate::hello::watch_cabs_response::Event;
+use crate::hello::{CabSnapshot, CabStatus, CabUpdate, CabUpdateKind, Location, WatchCabsResponse};
+use crate::registry::{CabChange, CabRegistry};
+
+// 클라이언트가 못 따라오면 이만큼 쌓인 뒤 registry 알림 쪽에서 밀린다
+const EVENT_BUFFER: usize = 64;
+
+pub type WatchStream = ReceiverStream<Result<WatchCabsResponse, Status>>;
+
+/// 한 클라이언트가 보고 있는 원. 지금 원 안에 있는 cab을 기억해서 enter/move/leave를 가른다.
+#[derive(Debug, Clone)]
+pub struct CabWatch {
+    origin: Location,
+    radius_meters: f64,
+    inside: HashSet<String>,
+}
+
+impl CabWatch {
+    pub fn new(origin: Location, radius_meters: f64) -> Self {
+        Self { origin, radius_meters, inside: HashSet::new() }
+    }
+
+    /// 지금 원 안의 offline이 아닌 cab 전체. 기억하던 것은 버리고 새로 채운다.
+    pub fn snapshot(&mut self, registry: &CabRegistry) -> CabSnapshot {
+        let cabs: Vec<_> = registry
+            .within(&self.origin, self.radius_meters)
+            .into_iter()
+            .map(|(cab, _)| cab)
+            .filter(|cab| cab.status != CabStatus::Offline as i32)
+            .collect();
+        self.inside = cabs.iter().map(|cab| cab.name.clone()).collect();
+        CabSnapshot { cabs }
+    }
+
+    /// 원 밖에서 밖으로 움직인 것은 `None`. offline이 된 cab은 원 밖으로 나간 것으로 본다.
+    pub fn update(&mut self, change: &CabChange) -> Option<CabUpdate> {
+        let now_inside = change.entry.status != CabStatus::Offline
+            && distance_meters(&self.origin, &change.entry.location) <= self.radius_meters;
+        let was_inside = self.inside.contains(&change.name);
+        let kind = match (was_inside, now_inside) {
+            (false, true) => {
+                self.inside.insert(change.name.clone());
+                CabUpdateKind::Enter
+            }
+            (true, true) => CabUpdateKind::Move,
+            (true, false) => {
+                self.inside.remove(&change.name);
+                CabUpdateKind::Leave
+            }
+            (false, false) => return None,
+        };
+        Some(CabUpdate { kind: kind as i32, cab: Some(change.entry.to_cab(&change.name)) })
+    }
+}
+
+/// snapshot 하나를 보내고 그 뒤로 바뀐 것을 보내는 stream.
+/// 클라이언트가 끊어서 stream이 drop되면 뒤에서 돌던 task도 끝난다.
+pub fn watch(registry: Arc<CabRegistry>, origin: Location, radius_meters: f64) -> WatchStream {
+    let (tx, rx) = mpsc::channel(EVENT_BUFFER);
+    // snapshot을 뜨기 전에 구독해야 그 사이의 update를 놓치지 않는다
+    let mut changes = registry.subscribe();
+    let mut watch = CabWatch::new(origin, radius_meters);
+
+    tokio::spawn(async move {
+        let mut event = Event::Snapshot(watch.snapshot(&registry));
+        loop {
+            if tx.send(Ok(WatchCabsResponse { event: Some(event) })).await.is_err() {
+                break;
+            }
+            event = loop {
+                tokio::select! {
+                    change = changes.recv() => match change {
+                        Ok(change) => {
+                            if let Some(update) = watch.update(&change) {
+                                break Event::Update(update);
+                            }
+                        }
+                        // 알림을 놓쳤으니 처음부터 다시 보낸다
+                        Err(RecvError::Lagged(_)) => break Event::Snapshot(watch.snapshot(&registry)),
+                        Err(RecvError::Closed) => return,
+                    },
+                    _ = tx.closed() => return,
+                }
+            };
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
@@ -0,0 +1,137 @@
+//! 터미널용 random_chat 클라이언트.
+//!
+//!     cargo run --bin client -- ws://127.0.0.1:8080
+//!
+//! 한 줄 입력하면 상대에게 보내고, 받은 메시지는 시간과 함께 출력한다.
+//!
+//!     /next  지금 채팅을 끝내고 새 상대 찾기
+//!     /quit  종료 (stdin이 끝나도 종료)
+
+use std::{env, process};
+
+use chrono::Local;
+use futures_util::{SinkExt, StreamExt};
+use random_chat::{QUITMSG, STARTMSG, TIMEOUTMSG};
+use tokio::io::{AsyncBufReadExt, BufReader, Lines, Stdin};
+use tungstenite::protocol::Message;
+
+type Input = Lines<BufReader<Stdin>>;
+
+const HELP: &str = "Commands: /next (find a new partner), /quit (exit)";
+
+enum Next {
+    Reconnect,
+    Quit,
+}
+
+fn now() -> String {
+    Local::now().format("%H:%M:%S").to_string()
+}
+
+fn show_status(message: &str) {
+    println!("[{}] ** {}", now(), message);
+}
+
+fn show_message(who: &str, message: &str) {
+    println!("[{}] {} : {}", now(), who, message);
+}
+
+async fn session(url: &str, input: &mut Input) -> Result<Next, String> {
+    show_status(&format!("Connecting to {}...", url));
+    let (ws_stream, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .map_err(|e| format!("connection failed: {}", e))?;
+    let (mut outgoing, mut incoming) = ws_stream.split();
+    show_status("Now Loading... waiting for a partner.");
+
+    let mut chatting = false;
+    let mut ended = false;
+
+    loop {
+        tokio::select! {
+            msg = incoming.next(), if !ended => match msg {
+                Some(Ok(Message::Text(text))) => match text.as_str() {
+                    STARTMSG => {
+                        chatting = true;
+                        show_status("Connection established. Have a nice chat!");
+                    }
+                    TIMEOUTMSG => {
+                        ended = true;
+                        show_status("TIME OUT. Type /next to try again.");
+                    }
+                    QUITMSG => {
+                        chatting = false;
+                        ended = true;
+                        show_status("Chat has ended. Type /next for a new partner.");
+                    }
+                    _ => show_message("낯선상대", &text),
+                },
+                Some(Ok(Message::Close(_))) | None => {
+                    chatting = false;
+                    ended = true;
+                    show_status("Connection closed by server. Type /next to reconnect.");
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    chatting = false;
+                    ended = true;
+                    show_status(&format!("Connection error: {}. Type /next to reconnect.", e));
+                }
+            },
+            line = input.next_line() => {
+                let line = line.map_err(|e| format!("stdin: {}", e))?;
+                let next = match line.as_deref().map(str::trim) {
+                    None | Some("/quit") => Next::Quit,
+                    Some("/next") => Next::Reconnect,
+                    Some("") => continue,
+                    Some(command) if command.starts_with('/') => {
+                        show_status(HELP);
+                        continue;
+                    }
+                    Some(text) if chatting => {
+                        outgoing.send(Message::Text(text.to_string())).await
+                            .map_err(|e| format!("send failed: {}", e))?;
+                        show_message("나", text);
+                        continue;
+                    }
+                    Some(_) => {
+                        show_status(if ended { "Not in a chat. Type /next to find a partner." } else { "Still waiting for a partner..." });
+                        continue;
+                    }
+                };
+
+                // 상대에게 알리고 닫는다
+                if chatting {
+                    let _ = outgoing.send(Message::Text(QUITMSG.to_string())).await;
+                }
+                let _ = outgoing.send(Message::Close(None)).await;
+                if chatting {
+                    show_status("Chat has ended.");
+                }
+                return Ok(next);
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let url = env::args().nth(1).unwrap_or_else(|| "ws://127.0.0.1:8080".to_string());
+    if url == "-h" || url == "--help" {
+        println!("usage: client [ws://host:port]\n{}", HELP);
+        return;
+    }
+
+    show_status(HELP);
+    let mut input = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        match session(&url, &mut input).await {
+            Ok(Next::Reconnect) => continue,
+            Ok(Next::Quit) => break,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! random_chat 서버 부하 테스트 클라이언트.
+//!
+//!     cargo run --release --bin loadtest -- ws://127.0.0.1:8080 --clients 1000 --messages 20 --rate 5
+//!
+//! 클라이언트 N개가 동시에 접속해서 매칭을 기다리고, 매칭되면 상대와 ping/pong을 주고받는다.
+//! 매칭까지 걸린 시간, 메시지 왕복 시간, timeout, 에러를 표나 JSON(`--json`)으로 출력한다.
+
+use std::{
+    collections::HashMap,
+    env,
+    process,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures_util::{SinkExt, StreamExt};
+use random_chat::{QUITMSG, STARTMSG, TIMEOUTMSG};
+use serde_json::json;
+use tungstenite::protocol::Message;
+
+// 부하 테스트 클라이언트끼리만 쓰는 메시지
+const PING: &str = "$L$T$P$I$N$G:";
+const PONG: &str = "$L$T$P$O$N$G:";
+const DONEMSG: &str = "$L$T$D$O$N$E!!^^";
+
+const USAGE: &str = "usage: loadtest [ws://host:port] [--clients N] [--messages N] [--rate PER_SEC] [--ramp SECS] [--deadline SECS] [--json]";
+
+struct Options {
+    target: String,
+    clients: usize,
+    messages: usize,
+    // --rate로 정한 메시지 간격
+    interval: Duration,
+    ramp: Duration,
+    deadline: Duration,
+    json: bool,
+}
+
+#[derive(Default)]
+struct ClientReport {
+    time_to_match: Option<Duration>,
+    timed_out: bool,
+    error: Option<String>,
+    sent: usize,
+    rtts: Vec<Duration>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut opts = Options {
+        target: "ws://127.0.0.1:8080".to_string(),
+        clients: 100,
+        messages: 10,
+        interval: Duration::from_millis(500),
+        ramp: Duration::from_secs(0),
+        deadline: Duration::from_secs(60),
+        json: false,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => opts.json = true,
+            "--clients" => opts.clients = parse_value(&arg, args.next())?,
+            "--messages" => opts.messages = parse_value(&arg, args.next())?,
+            "--rate" => opts.interval = parse_rate(&arg, args.next())?,
+            "--ramp" => opts.ramp = parse_seconds(&arg, args.next())?,
+            "--deadline" => opts.deadline = parse_seconds(&arg, args.next())?,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with("ws://") || arg.starts_with("wss://") => opts.target = arg,
+            _ => return Err(format!("unknown argument: {}\n{}", arg, USAGE)),
+        }
+    }
+
+    if opts.deadline.is_zero() {
+        return Err(format!("--deadline must be greater than 0\n{}", USAGE));
+    }
+    Ok(opts)
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    value
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| format!("{} needs a valid value\n{}", flag, USAGE))
+}
+
+// 음수, NaN, 무한대는 Duration으로 만들 수 없어서 panic이 난다
+fn parse_seconds(flag: &str, value: Option<String>) -> Result<Duration, String> {
+    let secs: f64 = parse_value(flag, value)?;
+    Duration::try_from_secs_f64(secs)
+        .map_err(|_| format!("{} must be a finite number of seconds, 0 or more\n{}", flag, USAGE))
+}
+
+// 초당 메시지 수를 간격으로. 간격이 0이면 interval이 panic한다
+fn parse_rate(flag: &str, value: Option<String>) -> Result<Duration, String> {
+    let rate: f64 = parse_value(flag, value)?;
+    match Duration::try_from_secs_f64(1.0 / rate) {
+        Ok(interval) if rate.is_finite() && !interval.is_zero() => Ok(interval),
+        _ => Err(format!("{} must be a finite number greater than 0\n{}", flag, USAGE)),
+    }
+}
+
+async fn run_client(opts: Arc<Options>, delay: Duration) -> ClientReport {
+    tokio::time::sleep(delay).await;
+
+    let mut report = ClientReport::default();
+    let session = tokio::time::timeout(opts.deadline, client_session(&opts, &mut report)).await;
+    match session {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => report.error = Some(e),
+        Err(_) => report.error = Some("deadline exceeded".to_string()),
+    }
+    report
+}
+
+async fn client_session(opts: &Options, report: &mut ClientReport) -> Result<(), String> {
+    let started = Instant::now();
+    let (ws_stream, _) = tokio_tungstenite::connect_async(opts.target.as_str())
+        .await
+        .map_err(|e| format!("connect: {}", e))?;
+    let (mut outgoing, mut incoming) = ws_stream.split();
+
+    // 매칭 대기
+    loop {
+        match incoming.next().await {
+            Some(Ok(Message::Text(text))) if text == STARTMSG => {
+                report.time_to_match = Some(started.elapsed());
+                break;
+            }
+            Some(Ok(Message::Text(text))) if text == TIMEOUTMSG => {
+                report.timed_out = true;
+                return Ok(());
+            }
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(format!("waiting: {}", e)),
+            None => return Err("waiting: connection closed".to_string()),
+        }
+    }
+
+    // 채팅: 내 ping을 보내고, 상대 ping에는 pong으로 답한다.
+    // 둘 다 끝났다고 알린 뒤에 연결을 닫는다.
+    let mut ticker = tokio::time::interval(opts.interval);
+    let mut pending: HashMap<usize, Instant> = HashMap::new();
+    let mut seq = 0;
+    let mut sent_done = false;
+    let mut peer_done = false;
+
+    loop {
+        // --messages 0이면 보낼 것 없이 바로 끝났다고 알린다
+        if !sent_done && seq == opts.messages && pending.is_empty() {
+            outgoing.send(Message::Text(DONEMSG.to_string())).await
+                .map_err(|e| format!("send: {}", e))?;
+            sent_done = true;
+        }
+        if sent_done && peer_done {
+            break;
+        }
+
+        tokio::select! {
+            _ = ticker.tick(), if seq < opts.messages => {
+                outgoing.send(Message::Text(format!("{}{}", PING, seq))).await
+                    .map_err(|e| format!("send: {}", e))?;
+                pending.insert(seq, Instant::now());
+                report.sent += 1;
+                seq += 1;
+            }
+            msg = incoming.next() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    if let Some(peer_seq) = text.strip_prefix(PING) {
+                        outgoing.send(Message::Text(format!("{}{}", PONG, peer_seq))).await
+                            .map_err(|e| format!("send: {}", e))?;
+                    } else if let Some(my_seq) = text.strip_prefix(PONG) {
+                        if let Some(sent_at) = my_seq.parse().ok().and_then(|s: usize| pending.remove(&s)) {
+                            report.rtts.push(sent_at.elapsed());
+                        }
+                    } else if text == DONEMSG {
+                        peer_done = true;
+                    } else if text == QUITMSG {
+                        return Err("chat: peer quit early".to_string());
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(format!("chat: {}", e)),
+                None => return Err("chat: connection closed".to_string()),
+            }
+        }
+    }
+
+    let _ = outgoing.send(Message::Close(None)).await;
+    Ok(())
+}
+
+// nearest-rank
+fn percentile(sorted: &[Duration], p: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+fn millis(d: Option<Duration>) -> Option<f64> {
+    d.map(|d| d.as_secs_f64() * 1000.0)
+}
+
+fn summary(sorted: &[Duration]) -> serde_json::Value {
+    json!({
+        "count": sorted.len(),
+        "p50_ms": millis(percentile(sorted, 50.0)),
+        "p90_ms": millis(percentile(sorted, 90.0)),
+        "p99_ms": millis(percentile(sorted, 99.0)),
+        "max_ms": millis(sorted.last().copied()),
+    })
+}
+
+fn error_counts(reports: &[ClientReport]) -> HashMap<&str, usize> {
+    let mut errors = HashMap::new();
+    for e in reports.iter().filter_map(|r| r.error.as_deref()) {
+        *errors.entry(e).or_insert(0) += 1;
+    }
+    errors
+}
+
+fn print_table(opts: &Options, reports: &[ClientReport], elapsed: Duration, matched: &[Duration], rtts: &[Duration]) {
+    let timeouts = reports.iter().filter(|r| r.timed_out).count();
+    let sent: usize = reports.iter().map(|r| r.sent).sum();
+    let errors = error_counts(reports);
+
+    println!("target             {}", opts.target);
+    println!("clients            {}", reports.len());
+    println!("elapsed            {:.2}s", elapsed.as_secs_f64());
+    println!("matched            {}", matched.len());
+    println!("timeouts           {}", timeouts);
+    println!("errors             {}", errors.values().sum::<usize>());
+    println!("messages sent      {}", sent);
+    println!("messages answered  {}", rtts.len());
+    println!("messages lost      {}", sent - rtts.len());
+    println!();
+    println!("{:<20}{:>10}{:>10}{:>10}{:>10}", "", "p50 ms", "p90 ms", "p99 ms", "max ms");
+    for (name, sorted) in [("time to match", matched), ("round trip", rtts)] {
+        let cell = |d: Option<Duration>| match millis(d) {
+            Some(ms) => format!("{:.1}", ms),
+            None => "-".to_string(),
+        };
+        println!("{:<20}{:>10}{:>10}{:>10}{:>10}", name,
+            cell(percentile(sorted, 50.0)), cell(percentile(sorted, 90.0)),
+            cell(percentile(sorted, 99.0)), cell(sorted.last().copied()));
+    }
+
+    if !errors.is_empty() {
+        println!();
+        let mut errors: Vec<_> = errors.into_iter().collect();
+        errors.sort_by_key(|e| std::cmp::Reverse(e.1));
+        for (error, count) in errors {
+            println!("{:>8}  {}", count, error);
+        }
+    }
+}
+
+fn print_json(opts: &Options, reports: &[ClientReport], elapsed: Duration, matched: &[Duration], rtts: &[Duration]) {
+    let sent: usize = reports.iter().map(|r| r.sent).sum();
+    let errors = error_counts(reports);
+
+    let report = json!({
+        "target": opts.target,
+        "clients": reports.len(),
+        "elapsed_ms": elapsed.as_secs_f64() * 1000.0,
+        "matched": matched.len(),
+        "timeouts": reports.iter().filter(|r| r.timed_out).count(),
+        "errors": errors.values().sum::<usize>(),
+        "error_kinds": errors,
+        "messages": {
+            "sent": sent,
+            "answered": rtts.len(),
+            "lost": sent - rtts.len(),
+        },
+        "time_to_match": summary(matched),
+        "round_trip": summary(rtts),
+    });
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
+
+#[tokio::main]
+async fn main() {
+    let opts = match parse_args() {
+        Ok(opts) => Arc::new(opts),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+
+    if !opts.json {
+        println!("Starting {} clients against {}", opts.clients, opts.target);
+    }
+
+    // ramp 동안 접속 시점을 고르게 퍼뜨린다
+    let started = Instant::now();
+    let handles: Vec<_> = (0..opts.clients)
+        .map(|i| {
+            let delay = opts.ramp.mul_f64(i as f64 / opts.clients.max(1) as f64);
+            tokio::spawn(run_client(opts.clone(), delay))
+        })
+        .collect();
+
+    let mut reports = Vec::with_capacity(handles.len());
+    for handle in handles {
+        reports.push(handle.await.unwrap_or_else(|e| ClientReport {
+            error: Some(format!("task: {}", e)),
+            ..Default::default()
+        }));
+    }
+    let elapsed = started.elapsed();
+
+    let mut matched: Vec<Duration> = reports.iter().filter_map(|r| r.time_to_match).collect();
+    matched.sort();
+    let mut rtts: Vec<Duration> = reports.iter().flat_map(|r| r.rtts.iter().copied()).collect();
+    rtts.sort();
+
+    if opts.json {
+        print_json(&opts, &reports, elapsed, &matched, &rtts);
+    } else {
+        print_table(&opts, &reports, elapsed, &matched, &rtts);
+    }
+}
@@ -0,0 +1,53 @@
+//! 연결 하나하나의 생애주기에 끼어드는 hook.
+//!
+//! 로그, 금칙어 필터, 통계, 봇 같은 기능을 `handle_connection`을 고치지 않고 붙인다.
+//! `Config::with_hook`으로 등록한 순서대로 불리고, 구현하지 않은 callback은 아무것도 하지 않는다.
+//!
+//! ```
+//! use std::net::SocketAddr;
+//! use random_chat::{ChatHooks, Config};
+//! use tungstenite::protocol::Message;
+//!
+//! struct NoShouting;
+//!
+//! #[async_trait::async_trait]
+//! impl ChatHooks for NoShouting {
+//!     async fn on_message(&self, _from: SocketAddr, _to: SocketAddr, msg: Message) -> Option<Message> {
+//!         match msg {
+//!             Message::Text(text) => Some(Message::Text(text.to_lowercase())),
+//!             other => Some(other),
+//!         }
+//!     }
+//! }
+//!
+//! let config = Config::default().with_hook(NoShouting);
+//! ```
+
+use std::net::SocketAddr;
+
+use async_trait::async_trait;
+use tungstenite::protocol::Message;
+
+#[async_trait]
+pub trait ChatHooks: Send + Sync {
+    /// WebSocket handshake가 끝난 직후.
+    async fn on_connect(&self, _addr: SocketAddr) {}
+
+    /// 매칭 대기열에 들어갔을 때.
+    async fn on_queued(&self, _addr: SocketAddr) {}
+
+    /// 짝이 정해졌을 때. 양쪽 연결에서 한 번씩 불린다.
+    async fn on_matched(&self, _addr: SocketAddr, _peer: SocketAddr) {}
+
+    /// `from`이 `to`에게 보내는 메시지. 바꿔서 돌려주거나 `None`으로 버릴 수 있다.
+    /// 제어 메시지(QUITMSG 등)는 여기로 오지 않는다.
+    async fn on_message(&self, _from: SocketAddr, _to: SocketAddr, msg: Message) -> Option<Message> {
+        Some(msg)
+    }
+
+    /// 채팅이 끝났을 때. 양쪽 연결에서 한 번씩 불린다.
+    async fn on_chat_end(&self, _addr: SocketAddr, _peer: SocketAddr) {}
+
+    /// 연결이 정리되기 직전. 매칭이 안 됐어도 불린다.
+    async fn on_disconnect(&self, _addr: SocketAddr) {}
+}
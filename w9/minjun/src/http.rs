@@ -0,0 +1,184 @@
+//! WebSocket 업그레이드와 같은 포트로 들어오는 일반 HTTP 요청 처리.
+//!
+//! ```text
+//! GET /         채팅 클라이언트(index.html)
+//! GET /healthz  프로세스 살아있는지
+//! GET /readyz   매칭 상태를 읽을 수 있는지
+//! GET /stats    대기열, 채팅 쌍 개수 (JSON)
+//! ```
+
+use std::{io, net::Ipv6Addr, time::Duration};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::{ActiveClinetDeque, PeerMap, PeerVecMap, Shutdown};
+
+const INDEX_HTML: &str = include_str!("../index.html");
+// index.html에 박혀있는 주소. 요청 host로 바꿔서 내려준다.
+const INDEX_WS_URL: &str = "ws://localhost:8080";
+const MAX_HEAD_SIZE: usize = 8192;
+const PEEK_INTERVAL: u64 = 10;
+const PEEK_TIMEOUT: u64 = 5000;
+
+pub struct RequestHead {
+    pub method: String,
+    pub path: String,
+    pub host: Option<String>,
+    pub forwarded_proto: Option<String>,
+    pub is_websocket: bool,
+    len: usize,
+}
+
+// 소켓에서 읽지 않고 header까지만 훔쳐본다.
+// websocket이면 tungstenite가 처음부터 다시 읽어야 하기 때문.
+// 아무것도 안 보내고 붙어만 있는 연결이 종료를 막지 않도록 시간과 종료 신호로 끊는다.
+pub async fn peek_request_head(stream: &TcpStream, shutdown: &mut Shutdown) -> io::Result<Option<RequestHead>> {
+    let peek = tokio::time::timeout(Duration::from_millis(PEEK_TIMEOUT), peek_until_head_end(stream));
+    tokio::select! {
+        peeked = peek => peeked.unwrap_or(Ok(None)),
+        _ = shutdown.changed() => Ok(None),
+    }
+}
+
+async fn peek_until_head_end(stream: &TcpStream) -> io::Result<Option<RequestHead>> {
+    let mut buf = vec![0u8; MAX_HEAD_SIZE];
+
+    loop {
+        let n = stream.peek(&mut buf).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        if let Some(end) = find_head_end(&buf[..n]) {
+            return Ok(parse_request_head(&buf[..end]));
+        }
+        // header가 너무 길면 포기
+        if n == buf.len() {
+            return Ok(None);
+        }
+        tokio::time::sleep(Duration::from_millis(PEEK_INTERVAL)).await;
+    }
+}
+
+fn find_head_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|w| w == b"\r\n\r\n").map(|pos| pos + 4)
+}
+
+fn parse_request_head(raw: &[u8]) -> Option<RequestHead> {
+    let text = std::str::from_utf8(raw).ok()?;
+    let mut lines = text.split("\r\n");
+
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+    let path = target.split('?').next().unwrap_or("/").to_string();
+
+    let mut host = None;
+    let mut forwarded_proto = None;
+    let mut is_websocket = false;
+    for line in lines {
+        let (name, value) = match line.split_once(':') {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => continue,
+        };
+        if name.eq_ignore_ascii_case("host") {
+            host = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("x-forwarded-proto") {
+            forwarded_proto = Some(value.to_ascii_lowercase());
+        } else if name.eq_ignore_ascii_case("upgrade") {
+            is_websocket = value.eq_ignore_ascii_case("websocket");
+        }
+    }
+
+    Some(RequestHead { method, path, host, forwarded_proto, is_websocket, len: raw.len() })
+}
+
+pub async fn handle_http(mut stream: TcpStream, head: RequestHead, peer_map: PeerMap,
+    peer_vec_map: PeerVecMap, active_client_deque: ActiveClinetDeque) -> io::Result<()> {
+
+    // 훔쳐본 header는 소켓에서 비워준다. body는 받지 않음.
+    let mut consumed = vec![0u8; head.len];
+    stream.read_exact(&mut consumed).await?;
+
+    let response = if head.method != "GET" {
+        response("405 Method Not Allowed", "text/plain; charset=utf-8", "method not allowed\n")
+    } else {
+        match head.path.as_str() {
+            "/" | "/index.html" => {
+                let html = INDEX_HTML.replace(INDEX_WS_URL, &websocket_url(&head));
+                response("200 OK", "text/html; charset=utf-8", &html)
+            }
+            "/healthz" => response("200 OK", "text/plain; charset=utf-8", "ok\n"),
+            "/readyz" => {
+                // lock이 poison되면 매칭이 더 이상 돌 수 없음.
+                // 다른 곳과 잠그는 순서가 엇갈리지 않게 하나씩 잠갔다 바로 푼다.
+                let peers_ok = peer_map.lock().is_ok();
+                let pairs_ok = peer_vec_map.lock().is_ok();
+                let waiting_ok = active_client_deque.lock().is_ok();
+                if peers_ok && pairs_ok && waiting_ok {
+                    response("200 OK", "text/plain; charset=utf-8", "ready\n")
+                } else {
+                    response("503 Service Unavailable", "text/plain; charset=utf-8", "not ready\n")
+                }
+            }
+            "/stats" => {
+                let body = stats_json(&peer_map, &peer_vec_map, &active_client_deque);
+                response("200 OK", "application/json", &body)
+            }
+            _ => response("404 Not Found", "text/plain; charset=utf-8", "not found\n"),
+        }
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn websocket_url(head: &RequestHead) -> String {
+    let scheme = match head.forwarded_proto.as_deref() {
+        Some("https") => "wss",
+        _ => "ws",
+    };
+    // host는 그대로 script 안에 들어가므로 주소 모양이 아니면 쓰지 않는다
+    match &head.host {
+        Some(host) if is_valid_host(host) => format!("{}://{}", scheme, host),
+        _ => INDEX_WS_URL.to_string(),
+    }
+}
+
+// `name[:port]`나 `[ipv6][:port]`
+fn is_valid_host(host: &str) -> bool {
+    let (name_ok, port) = match host.strip_prefix('[') {
+        Some(rest) => match rest.split_once(']') {
+            Some((ip, port)) => (ip.parse::<Ipv6Addr>().is_ok(), port),
+            None => return false,
+        },
+        None => {
+            let (name, port) = match host.find(':') {
+                Some(i) => host.split_at(i),
+                None => (host, ""),
+            };
+            let name_ok = !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'-');
+            (name_ok, port)
+        }
+    };
+    let port_ok = match port.strip_prefix(':') {
+        Some(port) => !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) && port.parse::<u16>().is_ok(),
+        None => port.is_empty(),
+    };
+    name_ok && port_ok
+}
+
+fn stats_json(peer_map: &PeerMap, peer_vec_map: &PeerVecMap, active_client_deque: &ActiveClinetDeque) -> String {
+    let connected = peer_map.lock().map(|m| m.len()).unwrap_or(0);
+    let waiting = active_client_deque.lock().map(|d| d.len()).unwrap_or(0);
+    // 짝은 양쪽 방향으로 두 번 들어가 있음
+    let pairs = peer_vec_map.lock().map(|m| m.len() / 2).unwrap_or(0);
+    format!("{{\"connected\":{},\"waiting\":{},\"pairs\":{}}}\n", connected, waiting, pairs)
+}
+
+fn response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status, content_type, body.len(), body
+    )
+}
@@ -0,0 +1,387 @@
+//! 랜덤 채팅 서버.
+//!
+//! 접속한 순서대로 두 명씩 짝지어 주고, 짝이 된 둘 사이에서 메시지를 중계한다.
+//! 같은 포트로 들어온 일반 HTTP 요청은 `http` 모듈에서 처리한다.
+//!
+//! ```no_run
+//! use random_chat::Config;
+//! use tokio::net::TcpListener;
+//!
+//! # async fn serve() -> std::io::Result<()> {
+//! let listener = TcpListener::bind("0.0.0.0:8080").await?;
+//! random_chat::run(listener, Config::default()).await
+//! # }
+//! ```
+
+
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    io::Error as IoError,
+    net::SocketAddr,
+    sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex},
+    time::Duration,
+
+};
+
+use chrono::Utc;
+
+use futures_channel::mpsc::{unbounded, UnboundedSender, UnboundedReceiver};
+use futures_util::{future, pin_mut, stream::{TryStreamExt, SplitStream, SplitSink}, SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+use tungstenite::protocol::Message;
+use tokio_tungstenite::{WebSocketStream};
+type Tx = UnboundedSender<Message>;
+type Rx = UnboundedReceiver<Message>;
+type WS = WebSocketStream<TcpStream>;
+type PeerMap = Arc<Mutex<HashMap<SocketAddr, Tx>>>;
+type PeerVecMap = Arc<Mutex<HashMap<SocketAddr, SocketAddr>>>;
+type ActiveClinetDeque = Arc<Mutex<VecDeque<SocketAddr>>>;
+// 서버 종료 알림. true가 되면 모든 연결이 정리된다.
+type Shutdown = watch::Receiver<bool>;
+// 모든 연결 task가 이걸 하나씩 들고 있다가 끝나면 drop한다.
+type Alive = mpsc::Sender<()>;
+
+mod hooks;
+mod http;
+
+pub use hooks::ChatHooks;
+
+
+//global constants
+pub const TIMEOUTMSG: &str = "$T$I$M$E$O$U$T!!^^";
+pub const STARTMSG: &str = "$S$T$A$R$T!!^^";
+pub const QUITMSG: &str = "$q$u$i$t!!^^";
+const SLEEPTIME: u64 = 100;
+const MATCHTIMEOUT: u64 = 10_000;
+// 종료할 때 close handshake를 기다려주는 시간
+const SHUTDOWNGRACE: u64 = 1000;
+
+#[derive(Clone)]
+pub struct Config {
+    // 이 시간 안에 짝을 못 찾으면 TIMEOUTMSG를 보내고 끊는다.
+    pub match_timeout: Duration,
+    // 대기 중에 짝이 생겼는지 확인하는 주기
+    pub poll_interval: Duration,
+    // 등록한 순서대로 불린다
+    pub hooks: Vec<Arc<dyn ChatHooks>>,
+}
+
+impl Config {
+    pub fn with_hook(mut self, hook: impl ChatHooks + 'static) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            match_timeout: Duration::from_millis(MATCHTIMEOUT),
+            poll_interval: Duration::from_millis(SLEEPTIME),
+            hooks: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Config")
+            .field("match_timeout", &self.match_timeout)
+            .field("poll_interval", &self.poll_interval)
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+enum Matching {
+    Matched,
+    TimedOut,
+    ShuttingDown,
+}
+
+/// `listener`로 들어오는 연결을 계속 받는다. 종료 신호 없이 accept가 멈출 때까지 돈다.
+pub async fn run(listener: TcpListener, config: Config) -> Result<(), IoError> {
+    run_with_shutdown(listener, config, future::pending()).await
+}
+
+/// `shutdown`이 끝나면 더 이상 연결을 받지 않고, 열려 있는 연결을 모두 닫은 뒤 리턴한다.
+pub async fn run_with_shutdown(listener: TcpListener, config: Config, shutdown: impl Future<Output = ()>) -> Result<(), IoError> {
+
+    let state = PeerMap::new(Mutex::new(HashMap::new()));
+    let peer_vec_map = PeerVecMap::new(Mutex::new(HashMap::new()));
+    let active_client_deque = ActiveClinetDeque::new(Mutex::new(VecDeque::new()));
+    let config = Arc::new(config);
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (alive_tx, mut alive_rx) = mpsc::channel::<()>(1);
+
+    pin_mut!(shutdown);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, addr)) => {
+                    tokio::spawn(route_connection(state.clone(), peer_vec_map.clone(), active_client_deque.clone(),
+                        config.clone(), shutdown_rx.clone(), alive_tx.clone(), stream, addr));
+                }
+                // fd가 모자란 경우 등. 서버 전체를 죽이지 않고 잠깐 쉰다.
+                Err(e) => {
+                    println!("{} ## accept failed: {}", get_current_time(), e);
+                    tokio::time::sleep(config.poll_interval).await;
+                }
+            },
+            _ = &mut shutdown => break,
+        }
+    }
+
+    // 종료: 새 연결은 안 받고, 열린 연결이 다 끝날 때까지 기다림
+    println!("{} ## Shutting down", get_current_time());
+    drop(listener);
+    let _ = shutdown_tx.send(true);
+    drop(alive_tx);
+    let _ = alive_rx.recv().await;
+
+    Ok(())
+}
+
+// websocket 업그레이드 요청이 아니면 일반 HTTP로 응답
+#[allow(clippy::too_many_arguments)]
+async fn route_connection(peer_map: PeerMap, peer_vec_map: PeerVecMap, active_client_deque: ActiveClinetDeque,
+    config: Arc<Config>, mut shutdown: Shutdown, _alive: Alive, raw_stream: TcpStream, addr: SocketAddr){
+
+    let head = match http::peek_request_head(&raw_stream, &mut shutdown).await{
+        Ok(Some(head)) => head,
+        _ => {
+            println!("{} ## {} sent an invalid request", get_current_time(), addr);
+            return},
+    };
+
+    if head.is_websocket {
+        handle_connection(peer_map, peer_vec_map, active_client_deque, config, shutdown, raw_stream, addr).await;
+        return;
+    }
+
+    println!("{} ## HTTP {} {} from {}", get_current_time(), head.method, head.path, addr);
+    if let Err(e) = http::handle_http(raw_stream, head, peer_map, peer_vec_map, active_client_deque).await {
+        println!("{} ## HTTP response to {} failed: {}", get_current_time(), addr, e);
+    }
+}
+
+async fn handle_connection(peer_map: PeerMap, peer_vec_map: PeerVecMap, active_client_deque: ActiveClinetDeque,
+    config: Arc<Config>, mut shutdown: Shutdown, raw_stream: TcpStream, addr: SocketAddr){
+
+    // 문제 생겨도 panic하지 않고 죽도록 그냥 리턴함.
+    let ws_stream = match tokio_tungstenite::accept_async(raw_stream).await{
+        Ok(ws_stream) => ws_stream,
+        Err(_) => {
+            println!("WebSocket connection failed");
+            return},
+    };
+
+    // 성공 로그
+    println!("{} ## WebSocket connection established: {}", get_current_time(), addr);
+    for hook in &config.hooks {
+        hook.on_connect(addr).await;
+    }
+
+    // 스트림 분리
+    let (mut outgoing, incoming) = ws_stream.split();
+
+    //sender
+    let (tx, rx) = unbounded();
+
+    // map에 나 넣기
+    peer_map.lock().unwrap().insert(addr, tx);
+
+    // active에 나 추가
+    active_client_deque.lock().unwrap().push_back(addr);
+    for hook in &config.hooks {
+        hook.on_queued(addr).await;
+    }
+
+    match wait_for_peer(&peer_vec_map, &active_client_deque, &config, &mut shutdown, addr).await {
+        Matching::Matched => {
+            handle_chat(peer_vec_map.clone(), peer_map.clone(), &config, addr, rx, incoming, outgoing, shutdown).await;
+        }
+        Matching::TimedOut => {
+            handle_timeout(outgoing).await;
+            println!("{} ## {} Connection Failed : TIMEOUT", get_current_time(), addr);
+        }
+        Matching::ShuttingDown => {
+            let _ = outgoing.send(Message::Close(None)).await;
+        }
+    }
+
+    // 넣어둔거 제거
+    peer_map.lock().unwrap().remove(&addr);
+    for hook in &config.hooks {
+        hook.on_disconnect(addr).await;
+    }
+
+    println!("{} ## TCP connection closed: {}", get_current_time(), addr);
+
+}
+
+async fn wait_for_peer(peer_vec_map: &PeerVecMap, active_client_deque: &ActiveClinetDeque, config: &Config,
+    shutdown: &mut Shutdown, addr: SocketAddr) -> Matching {
+
+    let deadline = Instant::now() + config.match_timeout;
+
+    loop {
+        // 남이 나를 고른 경우
+        if peer_vec_map.lock().unwrap().contains_key(&addr) {
+            return Matching::Matched;
+        }
+
+        // 내 바로 앞에 기다리는 애가 있으면 내가 고른다
+        {
+            let mut lck = active_client_deque.lock().unwrap();
+            if lck.get(1) == Some(&addr) {
+                let peer_addr = lck.pop_front().unwrap();
+                lck.pop_front().unwrap();
+                let mut pairs = peer_vec_map.lock().unwrap();
+                pairs.insert(peer_addr, addr);
+                pairs.insert(addr, peer_addr);
+                return Matching::Matched;
+            }
+        }
+
+        let give_up = if Instant::now() >= deadline {
+            Some(Matching::TimedOut)
+        } else {
+            tokio::select! {
+                _ = tokio::time::sleep(config.poll_interval) => None,
+                _ = shutdown.changed() => Some(Matching::ShuttingDown),
+            }
+        };
+
+        if let Some(reason) = give_up {
+            // 대기열에 아직 있으면 빠진다.
+            // 이미 빠져 있으면 그 사이에 누가 나를 고른 것이므로 채팅으로 간다.
+            let mut lck = active_client_deque.lock().unwrap();
+            return match lck.iter().position(|a| a == &addr) {
+                Some(index) => {
+                    lck.remove(index);
+                    reason
+                }
+                None => Matching::Matched,
+            };
+        }
+    }
+}
+
+fn send_start_msg(peer_addr: SocketAddr, peer_map: PeerMap){
+
+    if let Some(_tx) = peer_map.lock().unwrap().get(&peer_addr) {
+        _tx.unbounded_send(Message::Text(STARTMSG.to_string())).unwrap();
+    }
+
+}
+
+fn get_current_time() -> String{
+    let now = Utc::now();
+    format!("{}", now)
+}
+
+async fn handle_timeout(mut outgoing: SplitSink<WS, Message>){
+    let _ = outgoing.send(Message::Text(TIMEOUTMSG.to_string())).await;
+    let _ = outgoing.send(Message::Close(None)).await;
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_chat(peer_vec_map: PeerVecMap, peer_map: PeerMap, config: &Config, addr: SocketAddr, rx: Rx,
+     incoming: SplitStream<WS>, mut outgoing: SplitSink<WS, Message>, mut shutdown: Shutdown){
+    // peer addr 가져오기. 상대가 벌써 나갔으면 그냥 닫는다.
+    let peer_addr = peer_vec_map.lock().unwrap().get(&addr).copied();
+    let peer_addr = match peer_addr{
+        Some(peer_addr) => peer_addr,
+        None => {
+            let _ = outgoing.send(Message::Close(None)).await;
+            return},
+    };
+    // 시작 로그
+    println!("{} ## {} started new chat with {}", get_current_time(), addr, peer_addr);
+    // start msg 전송
+    send_start_msg(peer_addr, peer_map.clone());
+    for hook in &config.hooks {
+        hook.on_matched(addr, peer_addr).await;
+    }
+
+    // 내가 직접 quit을 보냈으면 끝날 때 상대에게 다시 알리지 않는다
+    let quit_relayed = AtomicBool::new(false);
+
+    let send_to_peer = incoming.try_for_each(|msg| {
+        let (peer_map, quit_relayed) = (&peer_map, &quit_relayed);
+        async move {
+            //println!("Received a message from {}: {}", addr, msg.to_text().unwrap());
+
+            // close나 ping은 넘기지 않는다. 끝날 때 따로 알려줌.
+            if !(msg.is_text() || msg.is_binary()) {
+                return Ok(());
+            }
+
+            let mut msg = Some(msg);
+            if msg.as_ref().and_then(|m| m.to_text().ok()) == Some(QUITMSG) {
+                quit_relayed.store(true, Ordering::Relaxed);
+            } else {
+                // hook이 바꾸거나 버릴 수 있음
+                for hook in &config.hooks {
+                    msg = match msg {
+                        Some(m) => hook.on_message(addr, peer_addr, m).await,
+                        None => break,
+                    };
+                }
+            }
+
+            if let (Some(msg), Some(_tx)) = (msg, peer_map.lock().unwrap().get(&peer_addr)) {
+                let _ = _tx.unbounded_send(msg);
+            }
+            Ok(())
+        }
+    });
+    // close를 보내고 나면 클라이언트 응답을 기다리지 않고 끝낸다
+    let receive_from_peer = async move {
+        let (mut rx, mut outgoing) = (rx, outgoing);
+        while let Some(msg) = rx.next().await {
+            let closing = msg.is_close();
+            if outgoing.send(msg).await.is_err() || closing {
+                break;
+            }
+        }
+    };
+    pin_mut!(send_to_peer, receive_from_peer);
+    let mut chat = future::select(send_to_peer, receive_from_peer);
+
+    tokio::select! {
+        _ = &mut chat => {},
+        _ = shutdown.changed() => {
+            // 서버 종료: 나에게 끝났다고 알리고 close handshake를 잠깐 기다려준다
+            if let Some(_tx) = peer_map.lock().unwrap().get(&addr) {
+                let _ = _tx.unbounded_send(Message::Text(QUITMSG.to_string()));
+                let _ = _tx.unbounded_send(Message::Close(None));
+            }
+            let _ = tokio::time::timeout(Duration::from_millis(SHUTDOWNGRACE), &mut chat).await;
+        },
+    }
+
+    // 마무리: 상대가 아직 나와 채팅 중이면 끝났다고 알리고 닫아준다
+    {
+        let mut pairs = peer_vec_map.lock().unwrap();
+        pairs.remove(&addr);
+        if pairs.get(&peer_addr) == Some(&addr) {
+            pairs.remove(&peer_addr);
+            println!("{} ## {} left chat with {}", get_current_time(), addr, peer_addr);
+            if let Some(_tx) = peer_map.lock().unwrap().get(&peer_addr) {
+                if !quit_relayed.load(Ordering::Relaxed) {
+                    let _ = _tx.unbounded_send(Message::Text(QUITMSG.to_string()));
+                }
+                let _ = _tx.unbounded_send(Message::Close(None));
+            }
+        }
+    }
+    for hook in &config.hooks {
+        hook.on_chat_end(addr, peer_addr).await;
+    }
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{assert_closed, connect, next_text, paired, start_server};
+use futures_util::{SinkExt, StreamExt};
+use random_chat::{QUITMSG, STARTMSG, TIMEOUTMSG};
+use tokio::net::TcpStream;
+use tungstenite::protocol::Message;
+
+#[tokio::test]
+async fn two_clients_pair_and_exchange_messages() {
+    let server = start_server().await;
+    let (mut a, mut b) = paired(&server).await;
+
+    a.send(Message::Text("hello".to_string())).await.unwrap();
+    assert_eq!(next_text(&mut b).await.as_deref(), Some("hello"));
+
+    b.send(Message::Text("hi there".to_string())).await.unwrap();
+    assert_eq!(next_text(&mut a).await.as_deref(), Some("hi there"));
+}
+
+#[tokio::test]
+async fn lone_client_times_out() {
+    let server = start_server().await;
+    let mut a = connect(&server).await;
+
+    assert_eq!(next_text(&mut a).await.as_deref(), Some(TIMEOUTMSG));
+    assert_closed(&mut a).await;
+}
+
+#[tokio::test]
+async fn odd_third_client_waits_for_next_peer() {
+    let server = start_server().await;
+    let (_a, _b) = paired(&server).await;
+
+    // 세 번째는 짝이 없으니 아무것도 못 받고 기다린다
+    let mut c = connect(&server).await;
+    assert!(tokio::time::timeout(Duration::from_millis(200), c.next()).await.is_err());
+
+    let mut d = connect(&server).await;
+    assert_eq!(next_text(&mut c).await.as_deref(), Some(STARTMSG));
+    assert_eq!(next_text(&mut d).await.as_deref(), Some(STARTMSG));
+}
+
+#[tokio::test]
+async fn odd_third_client_times_out_alone() {
+    let server = start_server().await;
+    let (_a, _b) = paired(&server).await;
+
+    let mut c = connect(&server).await;
+    assert_eq!(next_text(&mut c).await.as_deref(), Some(TIMEOUTMSG));
+}
+
+#[tokio::test]
+async fn peer_disconnect_mid_chat_ends_chat() {
+    let server = start_server().await;
+    let (a, mut b) = paired(&server).await;
+
+    // close handshake 없이 끊어버린다
+    drop(a);
+
+    assert_eq!(next_text(&mut b).await.as_deref(), Some(QUITMSG));
+    assert_closed(&mut b).await;
+}
+
+#[tokio::test]
+async fn quit_message_is_relayed_once() {
+    let server = start_server().await;
+    let (mut a, mut b) = paired(&server).await;
+
+    a.send(Message::Text(QUITMSG.to_string())).await.unwrap();
+    a.close(None).await.unwrap();
+
+    assert_eq!(next_text(&mut b).await.as_deref(), Some(QUITMSG));
+    assert_closed(&mut b).await;
+}
+
+#[tokio::test]
+async fn messages_are_relayed_in_order() {
+    let server = start_server().await;
+    let (mut a, mut b) = paired(&server).await;
+
+    for i in 0..200 {
+        a.send(Message::Text(format!("message {}", i))).await.unwrap();
+    }
+    for i in 0..200 {
+        assert_eq!(next_text(&mut b).await, Some(format!("message {}", i)));
+    }
+}
+
+#[tokio::test]
+async fn shutdown_closes_connections_and_stops_listening() {
+    let mut server = start_server().await;
+    let (mut a, mut b) = paired(&server).await;
+    let mut waiting = connect(&server).await;
+
+    server.shutdown.take().unwrap().send(()).unwrap();
+
+    assert_eq!(next_text(&mut a).await.as_deref(), Some(QUITMSG));
+    assert_closed(&mut a).await;
+    assert_eq!(next_text(&mut b).await.as_deref(), Some(QUITMSG));
+    assert_closed(&mut b).await;
+    assert_closed(&mut waiting).await;
+
+    tokio::time::timeout(Duration::from_secs(3), server.handle)
+        .await
+        .expect("server did not stop")
+        .unwrap()
+        .unwrap();
+    assert!(TcpStream::connect(server.addr).await.is_err());
+}
+
+#[tokio::test]
+async fn idle_connection_does_not_block_shutdown() {
+    let mut server = start_server().await;
+    // 연결만 하고 아무것도 보내지 않는다
+    let _idle = TcpStream::connect(server.addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    server.shutdown.take().unwrap().send(()).unwrap();
+
+    // header를 기다리는 시간(5초)보다 빨리 끝나야 한다
+    tokio::time::timeout(Duration::from_secs(2), server.handle)
+        .await
+        .expect("server did not stop")
+        .unwrap()
+        .unwrap();
+}
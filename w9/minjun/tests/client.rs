@@ -0,0 +1,92 @@
+mod common;
+
+use std::{process::Stdio, time::Duration};
+
+use common::{assert_closed, connect, next_text, start_server, TestServer};
+use futures_util::SinkExt;
+use random_chat::{QUITMSG, STARTMSG};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tungstenite::protocol::Message;
+
+struct TerminalClient {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl TerminalClient {
+    fn spawn(server: &TestServer) -> TerminalClient {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_client"))
+            .arg(format!("ws://{}", server.addr))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+        TerminalClient { child, stdin, stdout }
+    }
+
+    async fn type_line(&mut self, line: &str) {
+        self.stdin.write_all(format!("{}\n", line).as_bytes()).await.unwrap();
+    }
+
+    // `expected`가 들어간 줄이 나올 때까지 읽는다
+    async fn expect_output(&mut self, expected: &str) -> String {
+        let found = tokio::time::timeout(Duration::from_secs(3), async {
+            while let Some(line) = self.stdout.next_line().await.unwrap() {
+                if line.contains(expected) {
+                    return line;
+                }
+            }
+            panic!("client exited before printing {:?}", expected);
+        });
+        found.await.unwrap_or_else(|_| panic!("client never printed {:?}", expected))
+    }
+}
+
+#[tokio::test]
+async fn terminal_client_chats_and_quits() {
+    let server = start_server().await;
+    let mut client = TerminalClient::spawn(&server);
+    client.expect_output("waiting for a partner").await;
+
+    let mut peer = connect(&server).await;
+    assert_eq!(next_text(&mut peer).await.as_deref(), Some(STARTMSG));
+    client.expect_output("Have a nice chat").await;
+
+    peer.send(Message::Text("hi".to_string())).await.unwrap();
+    let line = client.expect_output("낯선상대 : hi").await;
+    assert!(line.starts_with('['), "missing timestamp: {}", line);
+
+    client.type_line("hello").await;
+    assert_eq!(next_text(&mut peer).await.as_deref(), Some("hello"));
+
+    client.type_line("/quit").await;
+    assert_eq!(next_text(&mut peer).await.as_deref(), Some(QUITMSG));
+    assert_closed(&mut peer).await;
+
+    let status = tokio::time::timeout(Duration::from_secs(3), client.child.wait()).await.unwrap().unwrap();
+    assert!(status.success());
+}
+
+#[tokio::test]
+async fn terminal_client_next_finds_new_partner() {
+    let server = start_server().await;
+    let mut client = TerminalClient::spawn(&server);
+    client.expect_output("waiting for a partner").await;
+
+    let mut first = connect(&server).await;
+    assert_eq!(next_text(&mut first).await.as_deref(), Some(STARTMSG));
+    client.expect_output("Have a nice chat").await;
+
+    client.type_line("/next").await;
+    assert_eq!(next_text(&mut first).await.as_deref(), Some(QUITMSG));
+    client.expect_output("waiting for a partner").await;
+
+    let mut second = connect(&server).await;
+    assert_eq!(next_text(&mut second).await.as_deref(), Some(STARTMSG));
+    client.expect_output("Have a nice chat").await;
+}
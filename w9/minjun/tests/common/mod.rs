@@ -0,0 +1,126 @@
+// 여러 테스트 파일에서 같이 쓰는 도우미. 파일마다 쓰는 것만 골라 쓴다.
+#![allow(dead_code)]
+
+use std::{net::SocketAddr, time::Duration};
+
+use futures_util::StreamExt;
+use random_chat::{Config, STARTMSG};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tungstenite::protocol::Message;
+
+pub type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+pub struct TestServer {
+    pub addr: SocketAddr,
+    pub shutdown: Option<oneshot::Sender<()>>,
+    pub handle: JoinHandle<std::io::Result<()>>,
+}
+
+pub async fn start_server() -> TestServer {
+    start_server_with(Config::default()).await
+}
+
+// 테스트가 빨리 끝나도록 timeout만 짧게 바꿔서 띄운다
+pub async fn start_server_with(config: Config) -> TestServer {
+    start_server_with_timeout(config, Duration::from_millis(500)).await
+}
+
+// 대기 중인 클라이언트를 오래 붙잡아 둬야 하는 테스트용
+pub async fn start_server_with_timeout(config: Config, match_timeout: Duration) -> TestServer {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let config = Config {
+        match_timeout,
+        poll_interval: Duration::from_millis(10),
+        ..config
+    };
+    let (shutdown, signal) = oneshot::channel();
+    let handle = tokio::spawn(random_chat::run_with_shutdown(listener, config, async {
+        let _ = signal.await;
+    }));
+    TestServer { addr, shutdown: Some(shutdown), handle }
+}
+
+pub async fn connect(server: &TestServer) -> Client {
+    let (ws, _) = tokio_tungstenite::connect_async(format!("ws://{}", server.addr)).await.unwrap();
+    ws
+}
+
+// 다음 텍스트 메시지. 연결이 닫히면 None.
+pub async fn next_text(ws: &mut Client) -> Option<String> {
+    let next = tokio::time::timeout(Duration::from_secs(3), async {
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Text(text))) => return Some(text),
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return None,
+                Some(Ok(_)) => continue,
+            }
+        }
+    });
+    next.await.expect("no message from server")
+}
+
+pub async fn assert_closed(ws: &mut Client) {
+    assert_eq!(next_text(ws).await, None);
+}
+
+pub async fn paired(server: &TestServer) -> (Client, Client) {
+    let mut a = connect(server).await;
+    let mut b = connect(server).await;
+    assert_eq!(next_text(&mut a).await.as_deref(), Some(STARTMSG));
+    assert_eq!(next_text(&mut b).await.as_deref(), Some(STARTMSG));
+    (a, b)
+}
+
+// 서버 쪽에서 보이는 클라이언트 주소
+pub fn local_addr(ws: &Client) -> SocketAddr {
+    match ws.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.local_addr().unwrap(),
+        _ => unreachable!("tests only use plain TCP"),
+    }
+}
+
+pub struct HttpResponse {
+    pub status_line: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+// 요청 줄과 header를 그대로 보내고 서버가 닫을 때까지 읽는다
+pub async fn http_request(server: &TestServer, request_line: &str, headers: &[(&str, &str)]) -> HttpResponse {
+    let mut stream = TcpStream::connect(server.addr).await.unwrap();
+    let mut request = format!("{}\r\n", request_line);
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut raw = String::new();
+    tokio::time::timeout(Duration::from_secs(3), stream.read_to_string(&mut raw))
+        .await
+        .expect("no HTTP response from server")
+        .unwrap();
+    let (head, body) = raw.split_once("\r\n\r\n").expect("response has no header end");
+    let mut lines = head.split("\r\n");
+    let status_line = lines.next().unwrap().to_string();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    HttpResponse { status_line, headers, body: body.to_string() }
+}
+
+pub async fn http_get(server: &TestServer, path: &str) -> HttpResponse {
+    http_request(server, &format!("GET {} HTTP/1.1", path), &[("Host", "localhost")]).await
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use common::{assert_closed, connect, local_addr, next_text, paired, start_server_with};
+use futures_util::SinkExt;
+use random_chat::{ChatHooks, Config, QUITMSG, TIMEOUTMSG};
+use tungstenite::protocol::Message;
+
+// 불린 callback을 순서대로 기록한다
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<(SocketAddr, &'static str)>>>,
+}
+
+impl Recorder {
+    fn events_for(&self, addr: SocketAddr) -> Vec<&'static str> {
+        self.events.lock().unwrap().iter().filter(|(a, _)| *a == addr).map(|(_, e)| *e).collect()
+    }
+
+    fn record(&self, addr: SocketAddr, event: &'static str) {
+        self.events.lock().unwrap().push((addr, event));
+    }
+}
+
+#[async_trait]
+impl ChatHooks for Recorder {
+    async fn on_connect(&self, addr: SocketAddr) {
+        self.record(addr, "connect");
+    }
+    async fn on_queued(&self, addr: SocketAddr) {
+        self.record(addr, "queued");
+    }
+    async fn on_matched(&self, addr: SocketAddr, _peer: SocketAddr) {
+        self.record(addr, "matched");
+    }
+    async fn on_message(&self, from: SocketAddr, _to: SocketAddr, msg: Message) -> Option<Message> {
+        self.record(from, "message");
+        Some(msg)
+    }
+    async fn on_chat_end(&self, addr: SocketAddr, _peer: SocketAddr) {
+        self.record(addr, "chat_end");
+    }
+    async fn on_disconnect(&self, addr: SocketAddr) {
+        self.record(addr, "disconnect");
+    }
+}
+
+// 금칙어가 들어간 메시지는 버리고, 나머지는 대문자로 바꾼다
+struct Moderator;
+
+#[async_trait]
+impl ChatHooks for Moderator {
+    async fn on_message(&self, _from: SocketAddr, _to: SocketAddr, msg: Message) -> Option<Message> {
+        match msg {
+            Message::Text(text) if text.contains("spam") => None,
+            Message::Text(text) => Some(Message::Text(text.to_uppercase())),
+            other => Some(other),
+        }
+    }
+}
+
+async fn wait_for_events(recorder: &Recorder, addr: SocketAddr, count: usize) -> Vec<&'static str> {
+    for _ in 0..100 {
+        let events = recorder.events_for(addr);
+        if events.len() >= count {
+            return events;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    recorder.events_for(addr)
+}
+
+#[tokio::test]
+async fn hooks_see_full_chat_lifecycle() {
+    let recorder = Recorder::default();
+    let server = start_server_with(Config::default().with_hook(recorder.clone())).await;
+    let (mut a, mut b) = paired(&server).await;
+    let (a_addr, b_addr) = (local_addr(&a), local_addr(&b));
+
+    a.send(Message::Text("hello".to_string())).await.unwrap();
+    assert_eq!(next_text(&mut b).await.as_deref(), Some("hello"));
+    a.close(None).await.unwrap();
+    assert_eq!(next_text(&mut b).await.as_deref(), Some(QUITMSG));
+    assert_closed(&mut b).await;
+
+    let expected = ["connect", "queued", "matched", "message", "chat_end", "disconnect"];
+    assert_eq!(wait_for_events(&recorder, a_addr, expected.len()).await, expected);
+    let expected = ["connect", "queued", "matched", "chat_end", "disconnect"];
+    assert_eq!(wait_for_events(&recorder, b_addr, expected.len()).await, expected);
+}
+
+#[tokio::test]
+async fn hooks_run_for_unmatched_clients() {
+    let recorder = Recorder::default();
+    let server = start_server_with(Config::default().with_hook(recorder.clone())).await;
+    let mut a = connect(&server).await;
+    let a_addr = local_addr(&a);
+
+    assert_eq!(next_text(&mut a).await.as_deref(), Some(TIMEOUTMSG));
+    let expected = ["connect", "queued", "disconnect"];
+    assert_eq!(wait_for_events(&recorder, a_addr, expected.len()).await, expected);
+}
+
+#[tokio::test]
+async fn on_message_can_modify_and_drop_messages() {
+    let recorder = Recorder::default();
+    let config = Config::default().with_hook(Moderator).with_hook(recorder.clone());
+    let server = start_server_with(config).await;
+    let (mut a, mut b) = paired(&server).await;
+    let a_addr = local_addr(&a);
+
+    a.send(Message::Text("buy spam now".to_string())).await.unwrap();
+    a.send(Message::Text("hello".to_string())).await.unwrap();
+    assert_eq!(next_text(&mut b).await.as_deref(), Some("HELLO"));
+
+    // 버려진 메시지는 뒤의 hook까지 가지 않는다
+    let events = wait_for_events(&recorder, a_addr, 4).await;
+    assert_eq!(events.iter().filter(|e| **e == "message").count(), 1);
+}
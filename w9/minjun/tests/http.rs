@@ -0,0 +1,94 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{connect, http_get, http_request, paired, start_server, start_server_with_timeout};
+use random_chat::Config;
+
+const TEXT: &str = "text/plain; charset=utf-8";
+
+#[tokio::test]
+async fn health_endpoints_answer_plain_text() {
+    let server = start_server().await;
+
+    let health = http_get(&server, "/healthz").await;
+    assert_eq!(health.status_line, "HTTP/1.1 200 OK");
+    assert_eq!(health.header("Content-Type"), Some(TEXT));
+    assert_eq!(health.body, "ok\n");
+
+    let ready = http_get(&server, "/readyz").await;
+    assert_eq!(ready.status_line, "HTTP/1.1 200 OK");
+    assert_eq!(ready.header("Content-Type"), Some(TEXT));
+    assert_eq!(ready.body, "ready\n");
+}
+
+#[tokio::test]
+async fn unknown_paths_and_methods_are_refused() {
+    let server = start_server().await;
+
+    let missing = http_get(&server, "/nope").await;
+    assert_eq!(missing.status_line, "HTTP/1.1 404 Not Found");
+    assert_eq!(missing.header("Content-Type"), Some(TEXT));
+
+    let post = http_request(&server, "POST /stats HTTP/1.1", &[("Host", "localhost"), ("Content-Length", "0")]).await;
+    assert_eq!(post.status_line, "HTTP/1.1 405 Method Not Allowed");
+    assert_eq!(post.header("Content-Type"), Some(TEXT));
+}
+
+#[tokio::test]
+async fn stats_count_waiting_clients_and_pairs() {
+    // 대기 중인 클라이언트가 테스트 도중에 timeout되지 않게 한다
+    let server = start_server_with_timeout(Config::default(), Duration::from_secs(30)).await;
+
+    let stats = http_get(&server, "/stats").await;
+    assert_eq!(stats.status_line, "HTTP/1.1 200 OK");
+    assert_eq!(stats.header("Content-Type"), Some("application/json"));
+    assert_eq!(stats.body, "{\"connected\":0,\"waiting\":0,\"pairs\":0}\n");
+
+    let (_a, _b) = paired(&server).await;
+    let _waiting = connect(&server).await;
+    // 세 번째 클라이언트가 대기열에 들어갈 때까지
+    let mut body = String::new();
+    for _ in 0..100 {
+        body = http_get(&server, "/stats").await.body;
+        if body.contains("\"waiting\":1") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(body, "{\"connected\":3,\"waiting\":1,\"pairs\":1}\n");
+}
+
+#[tokio::test]
+async fn index_points_at_the_requested_host() {
+    let server = start_server().await;
+
+    let index = http_request(&server, "GET / HTTP/1.1", &[("Host", "chat.example.com:9000")]).await;
+    assert_eq!(index.status_line, "HTTP/1.1 200 OK");
+    assert_eq!(index.header("Content-Type"), Some("text/html; charset=utf-8"));
+    assert!(index.body.contains("new WebSocket('ws://chat.example.com:9000')"), "{}", index.body);
+    assert!(!index.body.contains("ws://localhost:8080"));
+
+    let behind_proxy = http_request(
+        &server,
+        "GET /index.html HTTP/1.1",
+        &[("Host", "chat.example.com"), ("X-Forwarded-Proto", "https")],
+    )
+    .await;
+    assert!(behind_proxy.body.contains("new WebSocket('wss://chat.example.com')"));
+
+    let ipv6 = http_request(&server, "GET / HTTP/1.1", &[("Host", "[::1]:8080")]).await;
+    assert!(ipv6.body.contains("new WebSocket('ws://[::1]:8080')"));
+}
+
+#[tokio::test]
+async fn bad_host_falls_back_to_the_default_url() {
+    let server = start_server().await;
+
+    for host in ["x');alert(document.cookie);//", "x:80'", "[::1]<script>", "a b", "host:99999", "[nothost]:80"] {
+        let index = http_request(&server, "GET / HTTP/1.1", &[("Host", host)]).await;
+        assert_eq!(index.status_line, "HTTP/1.1 200 OK");
+        assert!(index.body.contains("new WebSocket('ws://localhost:8080')"), "{}", host);
+        assert!(!index.body.contains(host), "{}", host);
+    }
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use std::process::Command;
+
+use common::{start_server, TestServer};
+
+// 잘못된 값은 panic이 아니라 사용법과 함께 2로 끝나야 한다
+fn usage_error(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_loadtest")).args(args).output().unwrap();
+    assert_eq!(output.status.code(), Some(2), "{:?}", args);
+    String::from_utf8(output.stderr).unwrap()
+}
+
+// 테스트 서버에 대고 돌린 뒤 stdout
+async fn run_against(server: &TestServer, args: &[&str]) -> String {
+    let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_loadtest"))
+        .arg(format!("ws://{}", server.addr))
+        .args(["--rate", "100", "--deadline", "5"])
+        .args(args)
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+async fn run_json(server: &TestServer, args: &[&str]) -> serde_json::Value {
+    let stdout = run_against(server, &[args, &["--json"]].concat()).await;
+    serde_json::from_str(&stdout).unwrap_or_else(|e| panic!("{}: {}", e, stdout))
+}
+
+#[test]
+fn rejects_bad_durations_and_rates() {
+    for args in [
+        ["--ramp", "-1"],
+        ["--ramp", "NaN"],
+        ["--ramp", "inf"],
+        ["--deadline", "-5"],
+        ["--deadline", "0"],
+        ["--deadline", "NaN"],
+        ["--rate", "0"],
+        ["--rate", "-2"],
+        ["--rate", "inf"],
+        ["--rate", "NaN"],
+        ["--rate", "1e-320"],
+        ["--rate", "1e300"],
+    ] {
+        let stderr = usage_error(&args);
+        assert!(stderr.contains(args[0]), "{}", stderr);
+        assert!(stderr.contains("usage:"), "{}", stderr);
+    }
+}
+
+#[tokio::test]
+async fn matched_clients_exchange_every_message() {
+    let server = start_server().await;
+    let report = run_json(&server, &["--clients", "2", "--messages", "3"]).await;
+
+    assert_eq!(report["clients"], 2);
+    assert_eq!(report["matched"], 2);
+    assert_eq!(report["timeouts"], 0);
+    assert_eq!(report["errors"], 0, "{}", report);
+    assert_eq!(report["messages"]["sent"], 6);
+    assert_eq!(report["messages"]["answered"], 6);
+    assert_eq!(report["messages"]["lost"], 0);
+    assert_eq!(report["round_trip"]["count"], 6);
+    assert!(report["round_trip"]["p50_ms"].is_number());
+}
+
+#[tokio::test]
+async fn no_messages_still_finishes() {
+    let server = start_server().await;
+    let report = run_json(&server, &["--clients", "2", "--messages", "0"]).await;
+
+    assert_eq!(report["matched"], 2);
+    assert_eq!(report["errors"], 0, "{}", report);
+    assert_eq!(report["messages"]["sent"], 0);
+    // 잰 것이 없으면 null
+    assert!(report["round_trip"]["p50_ms"].is_null());
+}
+
+#[tokio::test]
+async fn table_counts_the_odd_client_as_timed_out() {
+    let server = start_server().await;
+    let stdout = run_against(&server, &["--clients", "3", "--messages", "1"]).await;
+
+    let line = |name: &str| {
+        let line = stdout.lines().find(|line| line.starts_with(name)).unwrap_or_else(|| panic!("{}", stdout));
+        line[name.len()..].trim().to_string()
+    };
+    assert_eq!(line("clients"), "3");
+    assert_eq!(line("matched"), "2");
+    assert_eq!(line("timeouts"), "1");
+    assert_eq!(line("errors"), "0", "{}", stdout);
+    assert_eq!(line("messages sent"), "2");
+    assert_eq!(line("messages lost"), "0");
+    assert!(stdout.contains("round trip"), "{}", stdout);
+}